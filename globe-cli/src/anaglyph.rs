@@ -0,0 +1,99 @@
+//! Red and cyan stereo frames for `--anaglyph`, to be seen with red/cyan
+//! glasses. The globe is rendered once for each eye, from cameras swung
+//! slightly apart, and the two renders are merged into one frame with a
+//! color for each character.
+
+use globe::{Canvas, Color, Float, Globe};
+
+use crate::{canvas_for, overlay, Settings};
+
+/// Foreground color of each character of a frame, by row, or `None` to
+/// leave the terminal's own.
+pub type Colors = Vec<Vec<Option<Color>>>;
+
+/// Radians each eye's camera is swung away from the view, around the globe.
+const EYE_ANGLE: Float = 0.02;
+
+/// How bright a character seen by one eye only is.
+const SINGLE_EYE: Float = 0.6;
+
+/// Canvases for both eyes, and the colors of the merged frame.
+pub struct Anaglyph {
+    left: Canvas,
+    right: Canvas,
+    palette: Vec<char>,
+    pub colors: Colors,
+}
+
+impl Anaglyph {
+    /// Renders frames of `size` cells, like `canvas_for` does.
+    pub fn new(size: (u16, u16), settings: &Settings) -> Anaglyph {
+        Anaglyph {
+            left: canvas_for(size, settings),
+            right: canvas_for(size, settings),
+            palette: settings.palette.clone(),
+            colors: Vec::new(),
+        }
+    }
+
+    /// Renders `globe` for both eyes and merges them into `canvas`, which
+    /// has to be as large as the ones given to `new`. The camera is left as
+    /// it was.
+    ///
+    /// Where both eyes see the globe, the character of the brighter one is
+    /// drawn, red as bright as the left eye sees it and cyan as bright as
+    /// the right one does. Where only one eye does, it's drawn in that
+    /// eye's color, dimmed.
+    pub fn render(&mut self, globe: &mut Globe, canvas: &mut Canvas) {
+        let camera = globe.camera;
+        let mut eye = |canvas: &mut Canvas, side: Float| {
+            globe.camera = camera.with_eye_offset(side * EYE_ANGLE);
+            canvas.clear();
+            globe.render_on(canvas);
+            surface(globe, canvas)
+        };
+        let hits = [eye(&mut self.left, -1.), eye(&mut self.right, 1.)];
+        globe.camera = camera;
+
+        let (width, height) = overlay::grid_size(canvas);
+        self.colors = vec![vec![None; width]; height];
+        for (y, (left, right)) in hits[0].iter().zip(&hits[1]).enumerate() {
+            for (x, hit) in left.iter().zip(right).enumerate() {
+                let (l, r) = (self.left.matrix[y][x], self.right.matrix[y][x]);
+                let (lb, rb) = (self.brightness(l), self.brightness(r));
+                let (c, red, cyan) = match hit {
+                    (true, true) if rb > lb => (r, lb, rb),
+                    (true, true) => (l, lb, rb),
+                    (true, false) => (l, lb * SINGLE_EYE, 0.),
+                    (false, true) => (r, 0., rb * SINGLE_EYE),
+                    (false, false) => continue,
+                };
+                let red = (red * 255.) as u8;
+                let cyan = (cyan * 255.) as u8;
+                canvas.matrix[y][x] = c;
+                self.colors[y][x] = Some(Color::rgb(red, cyan, cyan));
+            }
+        }
+    }
+
+    /// How bright a character is, from 0 to 1, by its place in the palette.
+    /// Markers and other characters outside of it are fully bright.
+    fn brightness(&self, c: char) -> Float {
+        match self.palette.iter().position(|&p| p == c) {
+            Some(i) => (i + 1) as Float / self.palette.len() as Float,
+            None => 1.,
+        }
+    }
+}
+
+/// Which characters of `canvas` fall on the globe, by row.
+fn surface(globe: &Globe, canvas: &Canvas) -> Vec<Vec<bool>> {
+    let (width, height) = overlay::grid_size(canvas);
+    (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| globe.unproject(canvas, x, y).is_some())
+                .collect()
+        })
+        .collect()
+}
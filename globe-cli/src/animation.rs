@@ -0,0 +1,211 @@
+//! Time-based animation of view changes.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use globe::{angles, Float};
+
+/// Value moving from one number to another over a fixed time.
+pub struct Tween {
+    from: Float,
+    to: Float,
+    start: Instant,
+    duration: Duration,
+}
+
+impl Tween {
+    pub fn new(from: Float, to: Float, duration: Duration) -> Tween {
+        Tween {
+            from,
+            to,
+            start: Instant::now(),
+            duration,
+        }
+    }
+
+    /// Current value. Starts fast and slows down towards the end.
+    pub fn value(&self) -> Float {
+        let t = self.progress();
+        let eased = 1. - (1. - t) * (1. - t);
+        self.from + (self.to - self.from) * eased
+    }
+
+    /// Value at the end of the animation.
+    pub fn target(&self) -> Float {
+        self.to
+    }
+
+    pub fn done(&self) -> bool {
+        self.progress() >= 1.
+    }
+
+    fn progress(&self) -> Float {
+        let elapsed = self.start.elapsed().as_secs_f32();
+        (elapsed / self.duration.as_secs_f32()).min(1.)
+    }
+}
+
+/// Camera flight to another view, animating the globe angle, the camera
+/// tilt and its distance together.
+pub struct Flight {
+    angle: Tween,
+    tilt: Tween,
+    zoom: Tween,
+}
+
+impl Flight {
+    /// Flies from one `(angle, tilt, zoom)` to another, turning the globe
+    /// the short way round.
+    pub fn new(
+        from: (Float, Float, Float),
+        to: (Float, Float, Float),
+        duration: Duration,
+    ) -> Flight {
+        let turn = angles::shortest_arc(from.0, to.0);
+        Flight {
+            angle: Tween::new(from.0, from.0 + turn, duration),
+            tilt: Tween::new(from.1, to.1, duration),
+            zoom: Tween::new(from.2, to.2, duration),
+        }
+    }
+
+    /// Current angle, tilt and zoom.
+    pub fn value(&self) -> (Float, Float, Float) {
+        (self.angle.value(), self.tilt.value(), self.zoom.value())
+    }
+
+    pub fn done(&self) -> bool {
+        self.angle.done()
+    }
+}
+
+/// Spin speed easing in when the screensaver starts and out when it's asked
+/// to stop, as a factor of the full speed. Starts now.
+pub struct Ramp {
+    start: Instant,
+    stop: Option<Instant>,
+}
+
+impl Default for Ramp {
+    fn default() -> Ramp {
+        Ramp {
+            start: Instant::now(),
+            stop: None,
+        }
+    }
+}
+
+impl Ramp {
+    /// Time to reach full speed.
+    const SPIN_UP: Duration = Duration::from_secs(2);
+    /// Time to come to a halt.
+    const SPIN_DOWN: Duration = Duration::from_millis(500);
+
+    /// Starts slowing down, from whatever speed it's at.
+    pub fn stop(&mut self) {
+        if self.stop.is_none() {
+            self.stop = Some(Instant::now());
+        }
+    }
+
+    pub fn stopping(&self) -> bool {
+        self.stop.is_some()
+    }
+
+    /// Whether it came to a halt after `stop`.
+    pub fn done(&self) -> bool {
+        self.stop
+            .is_some_and(|stop| stop.elapsed() >= Ramp::SPIN_DOWN)
+    }
+
+    /// Whether the speed is still changing.
+    pub fn easing(&self) -> bool {
+        self.stop.is_some() || self.start.elapsed() < Ramp::SPIN_UP
+    }
+
+    /// Current speed, from 0 to 1.
+    pub fn factor(&self) -> Float {
+        let smooth = |t: Float| t * t * (3. - 2. * t);
+        let fraction = |time: Duration, duration: Duration| {
+            (time.as_secs_f32() / duration.as_secs_f32()).min(1.)
+        };
+        match self.stop {
+            // slow down from the speed it had when told to stop
+            Some(stop) => {
+                let up = smooth(fraction(stop - self.start, Ramp::SPIN_UP));
+                up * (1. - smooth(fraction(stop.elapsed(), Ramp::SPIN_DOWN)))
+            }
+            None => smooth(fraction(self.start.elapsed(), Ramp::SPIN_UP)),
+        }
+    }
+}
+
+/// Frame deadlines on a fixed schedule, `start + n * interval`, so the time
+/// spent rendering and printing doesn't add up into a slower frame rate.
+/// Deadlines missed by a late frame are skipped rather than caught up on.
+pub struct Pacer {
+    start: Instant,
+    interval: Duration,
+    /// Number of the next frame on the schedule.
+    next: u64,
+    /// When the last frame was drawn.
+    last: Instant,
+    /// Times between recent frames, for `fps`.
+    recent: VecDeque<Duration>,
+}
+
+/// Number of frames `Pacer::fps` averages over.
+const FPS_FRAMES: usize = 20;
+
+impl Pacer {
+    pub fn new(interval: Duration) -> Pacer {
+        let now = Instant::now();
+        Pacer {
+            start: now,
+            interval,
+            next: 1,
+            last: now,
+            recent: VecDeque::with_capacity(FPS_FRAMES),
+        }
+    }
+
+    fn deadline(&self) -> Instant {
+        let nanos = self.interval.as_nanos() as u64;
+        self.start + Duration::from_nanos(nanos.saturating_mul(self.next))
+    }
+
+    /// Time left until the next frame is due, to wait for input meanwhile.
+    pub fn wait(&self) -> Duration {
+        self.deadline().saturating_duration_since(Instant::now())
+    }
+
+    /// Whether the next frame is due.
+    pub fn due(&self) -> bool {
+        Instant::now() >= self.deadline()
+    }
+
+    /// Records a frame drawn now and schedules the next one in the first
+    /// free slot. Gives the time since the last frame, to move animations
+    /// by.
+    pub fn tick(&mut self) -> Duration {
+        let now = Instant::now();
+        let slots = (now - self.start).as_nanos() / self.interval.as_nanos().max(1);
+        self.next = slots as u64 + 1;
+        let dt = now - self.last;
+        self.last = now;
+        if self.recent.len() == FPS_FRAMES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(dt);
+        dt
+    }
+
+    /// Frames drawn per second, measured over the last few frames.
+    pub fn fps(&self) -> Float {
+        let total: Duration = self.recent.iter().sum();
+        match total.is_zero() {
+            true => 0.,
+            false => self.recent.len() as Float / total.as_secs_f32(),
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! The frame loop shared by the screensaver, interactive mode and the other
+//! modes shown frame after frame. Each mode keeps its state in a type
+//! implementing `Mode`, which takes input and draws frames without touching
+//! the terminal itself, so it can be driven by made up events as well.
+
+use std::io::{stdout, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{Event, MouseEvent};
+use crossterm::style::Print;
+use crossterm::{cursor, terminal, QueueableCommand};
+use globe::{Canvas, Float, Globe};
+
+use crate::anaglyph::Colors;
+use crate::animation::Pacer;
+use crate::clock::ClockThread;
+use crate::control::{self, Command};
+use crate::input::InputThread;
+use crate::term::{self, TerminalGuard, Title};
+use crate::{draw_colored_frame, start_recording, stop_recording, Settings};
+
+/// Whether to keep running after some input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Continue,
+    Quit,
+}
+
+/// State of a mode shown frame after frame in the terminal.
+pub trait Mode {
+    /// Reacts to a key, mouse or resize event.
+    fn handle_event(&mut self, event: Event) -> Action;
+
+    /// Carries out a command read from stdin with `--control-stdin`.
+    fn handle_command(&mut self, command: Command) -> Action;
+
+    /// Moves animations along by `dt`, the time since the last frame.
+    fn update(&mut self, dt: Duration);
+
+    /// Writes the frame to `out` if it changed since the last one, with
+    /// the frame rate for `--stats`. Returns the canvas drawn, to be
+    /// recorded.
+    fn draw(&mut self, out: &mut impl Write, fps: Float) -> Option<&Canvas>;
+
+    /// How long to wait for input before the next frame, which `pacer`
+    /// would have due after its `wait`.
+    fn wait(&self, pacer: &Pacer) -> Duration {
+        pacer.wait()
+    }
+
+    /// Whether the mode ended on its own.
+    fn done(&self) -> bool {
+        false
+    }
+}
+
+/// Runs a mode in the terminal until it quits or is interrupted, reporting
+/// the mouse if `mouse`. `new` creates the mode for a terminal of some
+/// size, and whether mouse events are reported; it's returned at the end.
+pub fn run<M: Mode>(
+    settings: &Settings,
+    mouse: bool,
+    new: impl FnOnce((u16, u16), bool) -> M,
+) -> M {
+    let mut recorder = start_recording(settings);
+    let guard = TerminalGuard::new(mouse, settings.alt_screen);
+    let mut stdout = stdout();
+    let _clock = settings.clock.as_ref().map(ClockThread::spawn);
+    let commands = settings.control_stdin.then(control::spawn);
+    let mut mode = new(term::size(), guard.mouse());
+    let input = InputThread::spawn();
+    let mut pacer = Pacer::new(settings.frame_time());
+    // the latest size while the terminal is being resized, and when it was
+    // reported
+    let mut resizing: Option<(Event, Instant)> = None;
+
+    'frames: while !guard.interrupted() && !mode.done() {
+        let wait = match resizing {
+            Some((_, at)) => {
+                let left = settings.resize_delay.saturating_sub(at.elapsed());
+                mode.wait(&pacer).min(left)
+            }
+            None => mode.wait(&pacer),
+        };
+        // take everything queued up before drawing, so a flood of events
+        // doesn't leave the view lagging behind
+        let events = input.wait(wait);
+        // a termination signal wakes the wait up with a resize
+        if guard.interrupted() {
+            break;
+        }
+        for event in coalesce(events) {
+            // resizing a window reports many sizes in a row; only the last
+            // one is drawn at, once they stop coming
+            if let Event::Resize(width, height) = event {
+                if !settings.resize_delay.is_zero() {
+                    draw_resizing(&mut stdout, (width, height));
+                    resizing = Some((event, Instant::now()));
+                    continue;
+                }
+            }
+            if mode.handle_event(event) == Action::Quit {
+                break 'frames;
+            }
+        }
+        if let Some((event, at)) = resizing {
+            if at.elapsed() >= settings.resize_delay {
+                resizing = None;
+                if mode.handle_event(event) == Action::Quit {
+                    break 'frames;
+                }
+            }
+        }
+        // input is handled as it comes, frames only on schedule
+        if !pacer.due() {
+            continue;
+        }
+        let dt = pacer.tick();
+
+        for command in commands.iter().flat_map(|c| c.try_iter()) {
+            let action = match command {
+                Ok(command) => mode.handle_command(command),
+                Err(e) => {
+                    eprintln!("error: {}", e);
+                    Action::Continue
+                }
+            };
+            if action == Action::Quit {
+                break 'frames;
+            }
+        }
+
+        mode.update(dt);
+        // frames at the old size could scroll a smaller terminal
+        if resizing.is_some() {
+            continue;
+        }
+        if let Some(frame) = mode.draw(&mut stdout, pacer.fps()) {
+            if let Some(recorder) = &mut recorder {
+                recorder.record(frame);
+            }
+        }
+    }
+    drop(input);
+    drop(guard);
+    stop_recording(settings, recorder);
+    mode
+}
+
+/// Prints a frame of `globe` seen from `zoom`, in `colors` if any and with
+/// a `status` line if any, clearing the terminal first if `clear` and
+/// putting the view center in the title with `--title-coords`.
+#[allow(clippy::too_many_arguments)]
+pub fn print_frame(
+    out: &mut impl Write,
+    settings: &Settings,
+    title: &mut Title,
+    globe: &Globe,
+    zoom: Float,
+    frame: &mut Canvas,
+    colors: Option<&Colors>,
+    term_size: (u16, u16),
+    clear: bool,
+    status: Option<&str>,
+) {
+    if clear {
+        out.queue(terminal::Clear(terminal::ClearType::All))
+            .unwrap();
+        frame.mark_dirty();
+    }
+    if settings.title_coords {
+        title.update(out, globe.facing(), zoom);
+    }
+    draw_colored_frame(out, settings, globe, frame, colors, term_size, status);
+}
+
+/// Clears the terminal of `size` cells and says it's being resized, until
+/// frames are drawn at the new size.
+fn draw_resizing(out: &mut impl Write, (width, height): (u16, u16)) {
+    let text: String = "resizing\u{2026}".chars().take(width as usize).collect();
+    let left = (width as usize).saturating_sub(text.chars().count()) / 2;
+    out.queue(terminal::Clear(terminal::ClearType::All))
+        .unwrap();
+    out.queue(cursor::MoveTo(left as u16, height / 2)).unwrap();
+    out.queue(Print(text)).unwrap();
+    out.flush().unwrap();
+}
+
+/// Input read since the last frame, with each run of drag events merged
+/// into its first and last one. Drags move the view by how far the pointer
+/// got from the previous position, so the positions in between don't
+/// matter; the first one is kept since a drag right after a press only
+/// starts measuring from there.
+fn coalesce(events: Vec<Event>) -> Vec<Event> {
+    let is_drag = |e: &Event| matches!(e, Event::Mouse(MouseEvent::Drag(..)));
+    let mut merged: Vec<Event> = Vec::with_capacity(events.len());
+    for event in events {
+        let run = merged.len() >= 2 && merged[merged.len() - 2..].iter().all(is_drag);
+        if is_drag(&event) && run {
+            merged.pop();
+        }
+        merged.push(event);
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton};
+
+    fn drag(x: u16) -> Event {
+        Event::Mouse(MouseEvent::Drag(
+            MouseButton::Left,
+            x,
+            0,
+            KeyModifiers::NONE,
+        ))
+    }
+
+    fn down(x: u16) -> Event {
+        Event::Mouse(MouseEvent::Down(
+            MouseButton::Left,
+            x,
+            0,
+            KeyModifiers::NONE,
+        ))
+    }
+
+    #[test]
+    fn coalesce_keeps_each_runs_ends() {
+        let key = Event::Key(KeyEvent::from(KeyCode::Char('a')));
+        let events = vec![
+            down(0),
+            drag(1),
+            drag(2),
+            drag(3),
+            drag(4),
+            key,
+            drag(5),
+            drag(6),
+            down(7),
+            drag(8),
+        ];
+        assert_eq!(
+            coalesce(events),
+            vec![
+                down(0),
+                drag(1),
+                drag(4),
+                key,
+                drag(5),
+                drag(6),
+                down(7),
+                drag(8)
+            ]
+        );
+        // nothing to merge
+        assert_eq!(coalesce(vec![]), vec![]);
+        assert_eq!(coalesce(vec![drag(1), drag(2)]), vec![drag(1), drag(2)]);
+    }
+}
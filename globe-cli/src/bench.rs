@@ -0,0 +1,106 @@
+//! Rendering benchmark for `--bench`.
+
+use std::f32::consts::PI;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use globe::{Camera, Canvas};
+
+use crate::json::Json;
+use crate::Settings;
+
+/// Canvas sizes benchmarked, the largest being about a 64x32 cell frame.
+pub const SIZES: [u16; 3] = [64, 128, 256];
+
+/// Timings of the frames rendered at one canvas size.
+pub struct Stats {
+    pub size: u16,
+    pub frames: usize,
+    pub total: Duration,
+    pub p50: Duration,
+    pub p99: Duration,
+}
+
+impl Stats {
+    pub fn fps(&self) -> f64 {
+        self.frames as f64 / self.total.as_secs_f64()
+    }
+}
+
+/// Renders `frames` frames of a turning globe at every size in `SIZES`,
+/// the way the screensaver does minus writing them to the terminal.
+pub fn run(settings: &Settings, frames: u32) -> Vec<Stats> {
+    SIZES
+        .iter()
+        .map(|&size| {
+            let mut canvas = Canvas::new(size, size, None)
+                .with_palette(&settings.palette)
+                .with_cell_aspect(settings.cell_aspect);
+            let mut globe = settings.globe();
+            let (angle, cam_z) = settings.initial_view();
+            globe.angle = angle;
+            globe.camera = Camera::new(settings.zoom, 0., cam_z);
+
+            let mut times: Vec<Duration> = (0..frames)
+                .map(|_| {
+                    let start = Instant::now();
+                    globe.angle -= PI / 50. * settings.speed;
+                    settings.update_globe(&mut globe);
+                    canvas.clear();
+                    globe.render_on(&mut canvas);
+                    settings.apply_effects(&mut canvas);
+                    settings.layer.draw(&globe, &mut canvas);
+                    // keep the text from being optimized away
+                    std::hint::black_box(crate::canvas_text(&canvas));
+                    start.elapsed()
+                })
+                .collect();
+            let total = times.iter().sum();
+            times.sort();
+            let percentile = |p: usize| times[(times.len() - 1) * p / 100];
+            Stats {
+                size,
+                frames: times.len(),
+                total,
+                p50: percentile(50),
+                p99: percentile(99),
+            }
+        })
+        .collect()
+}
+
+/// Results as a table with a row per canvas size.
+pub fn table(stats: &[Stats]) -> String {
+    let mut out = format!(
+        "{:>6} {:>8} {:>10} {:>10}\n",
+        "size", "fps", "p50 ms", "p99 ms"
+    );
+    for s in stats {
+        let ms = |d: Duration| d.as_secs_f64() * 1000.;
+        writeln!(
+            out,
+            "{:>6} {:>8.1} {:>10.3} {:>10.3}",
+            s.size,
+            s.fps(),
+            ms(s.p50),
+            ms(s.p99)
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Results as a JSON array with an object per canvas size.
+pub fn json(stats: &[Stats]) -> Json {
+    let results = stats.iter().map(|s| {
+        let ms = |d: Duration| Json::Num(d.as_secs_f64() * 1000.);
+        Json::Object(vec![
+            ("size".to_string(), Json::Num(s.size as f64)),
+            ("frames".to_string(), Json::Num(s.frames as f64)),
+            ("fps".to_string(), Json::Num(s.fps())),
+            ("p50_ms".to_string(), ms(s.p50)),
+            ("p99_ms".to_string(), ms(s.p99)),
+        ])
+    });
+    Json::Array(results.collect())
+}
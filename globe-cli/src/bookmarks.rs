@@ -0,0 +1,115 @@
+//! Saved views for interactive mode, kept between sessions.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use globe::Float;
+
+use crate::config;
+use crate::json::Json;
+
+/// Number of bookmarks, one per key from 1 to 9.
+pub const COUNT: usize = 9;
+
+/// View center in degrees and camera distance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct View {
+    pub lat: Float,
+    pub lon: Float,
+    pub zoom: Float,
+}
+
+/// Bookmarked views, stored as JSON next to the configuration file.
+pub struct Bookmarks {
+    path: Option<PathBuf>,
+    views: [Option<View>; COUNT],
+}
+
+impl Bookmarks {
+    /// Loads the bookmarks from the configuration directory. A missing file
+    /// means no bookmarks; a broken one is reported but still gives a usable
+    /// empty set, which is only written back once a bookmark is saved.
+    pub fn load() -> (Bookmarks, Option<String>) {
+        let path = config::config_dir().map(|dir| dir.join("bookmarks.json"));
+        let mut bookmarks = Bookmarks {
+            path: path.clone(),
+            views: [None; COUNT],
+        };
+        let path = match path {
+            Some(path) => path,
+            None => return (bookmarks, None),
+        };
+        let error = match fs::read_to_string(&path) {
+            Ok(text) => bookmarks.parse(&text).err(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => Some(e.to_string()),
+        };
+        let error = error.map(|e| format!("{}: {}", path.display(), e));
+        (bookmarks, error)
+    }
+
+    fn parse(&mut self, text: &str) -> Result<(), String> {
+        let members = match Json::parse(text)? {
+            Json::Object(members) => members,
+            _ => return Err("expected an object".to_string()),
+        };
+        for (key, value) in &members {
+            let slot = match key.parse::<usize>() {
+                Ok(n) if (1..=COUNT).contains(&n) => n - 1,
+                _ => {
+                    return Err(format!(
+                        "invalid bookmark `{}`, expected 1 to {}",
+                        key, COUNT
+                    ))
+                }
+            };
+            let field = |name| value.get(name).and_then(Json::as_f64);
+            match (field("lat"), field("lon"), field("zoom")) {
+                (Some(lat), Some(lon), Some(zoom)) => {
+                    self.views[slot] = Some(View {
+                        lat: lat as Float,
+                        lon: lon as Float,
+                        zoom: zoom as Float,
+                    });
+                }
+                _ => return Err(format!("bookmark {} needs `lat`, `lon` and `zoom`", key)),
+            }
+        }
+        Ok(())
+    }
+
+    /// View saved under the key `n`, from 1 to 9.
+    pub fn get(&self, n: usize) -> Option<View> {
+        self.views.get(n.checked_sub(1)?).copied().flatten()
+    }
+
+    /// Saves `view` under the key `n` and writes all bookmarks to disk.
+    pub fn set(&mut self, n: usize, view: View) -> Result<(), String> {
+        self.views[n - 1] = Some(view);
+        let path = self
+            .path
+            .as_ref()
+            .ok_or("no configuration directory to save bookmarks in")?;
+        let members = self
+            .views
+            .iter()
+            .enumerate()
+            .filter_map(|(i, view)| Some(((i + 1).to_string(), view.as_ref()?)))
+            .map(|(key, view)| {
+                let fields = [("lat", view.lat), ("lon", view.lon), ("zoom", view.zoom)];
+                let fields = fields
+                    .iter()
+                    .map(|&(name, n)| (name.to_string(), Json::Num(n as f64)));
+                (key, Json::Object(fields.collect()))
+            });
+        let json = Json::Object(members.collect());
+        let write = |path: &PathBuf| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(path, format!("{}\n", json))
+        };
+        write(path).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
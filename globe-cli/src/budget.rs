@@ -0,0 +1,67 @@
+//! Frame budget for `--adaptive-quality`: lowers the render quality while
+//! frames take longer than the target frame rate allows, and raises it
+//! again once there's time to spare.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use globe::{Float, QualitySettings};
+
+/// Number of frames averaged before the quality changes. Changes start a
+/// new window, so the next one is measured at the new quality.
+const WINDOW: usize = 10;
+
+/// Share of the budget frames have to stay under to raise the quality.
+/// Well below 1, so one level up doesn't go straight over budget again.
+const HEADROOM: Float = 0.6;
+
+pub struct Budget {
+    /// Time a frame may take.
+    frame: Duration,
+    /// Times recent frames took to draw.
+    recent: VecDeque<Duration>,
+    level: usize,
+}
+
+impl Budget {
+    /// Budget for holding `fps` frames per second, starting at full quality.
+    pub fn new(fps: Float) -> Budget {
+        Budget {
+            frame: Duration::from_secs_f32(1. / fps),
+            recent: VecDeque::with_capacity(WINDOW),
+            level: QualitySettings::LEVELS - 1,
+        }
+    }
+
+    /// Records a frame that took `time` to draw, changing the quality level
+    /// once a full window of frames is over or well under budget.
+    pub fn record(&mut self, time: Duration) {
+        self.recent.push_back(time);
+        if self.recent.len() < WINDOW {
+            return;
+        }
+        let average = self.recent.iter().sum::<Duration>() / WINDOW as u32;
+        let level = if average > self.frame {
+            self.level.saturating_sub(1)
+        } else if average < self.frame.mul_f32(HEADROOM) {
+            (self.level + 1).min(QualitySettings::LEVELS - 1)
+        } else {
+            self.level
+        };
+        if level != self.level {
+            self.level = level;
+            self.recent.clear();
+        } else {
+            self.recent.pop_front();
+        }
+    }
+
+    /// Current quality level, see `QualitySettings::level`.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    pub fn quality(&self) -> QualitySettings {
+        QualitySettings::level(self.level)
+    }
+}
@@ -0,0 +1,512 @@
+//! Clock overlay and time zone support.
+//!
+//! Time zones are read from the system's IANA time zone database
+//! (`/usr/share/zoneinfo`, or `$TZDIR`), so no time zone data is bundled.
+
+use std::convert::TryInto;
+use std::env;
+use std::fs;
+use std::io::{stdout, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crossterm::{cursor, style::Print, terminal, QueueableCommand};
+
+use crate::term;
+
+/// Clock drawn in the top right corner of the terminal.
+#[derive(Clone)]
+pub struct Clock {
+    zone: Zone,
+    /// Show the date before the time.
+    pub date: bool,
+}
+
+impl Clock {
+    /// Parses a `--clock` argument: `utc`, `local` or an IANA time zone
+    /// name such as `Europe/Paris`.
+    pub fn new(zone: &str, date: bool) -> Result<Clock, String> {
+        let zone = match zone {
+            "utc" | "UTC" => Zone::Utc,
+            "local" => Zone::local(),
+            name => Zone::named(name)?,
+        };
+        Ok(Clock { zone, date })
+    }
+
+    /// Current time, formatted for display.
+    pub fn text(&self) -> String {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        let (offset, abbr) = self.zone.offset_at(secs);
+        let local = secs + offset as i64;
+        let (y, m, d) = civil_from_days(local.div_euclid(86400));
+        let time = local.rem_euclid(86400);
+        let mut text = String::new();
+        if self.date {
+            text.push_str(&format!("{:04}-{:02}-{:02} ", y, m, d));
+        }
+        text.push_str(&format!(
+            "{:02}:{:02}:{:02} {}",
+            time / 3600,
+            time / 60 % 60,
+            time % 60,
+            abbr
+        ));
+        text
+    }
+
+    /// Queues drawing the clock on the first row of the terminal.
+    pub fn queue(&self, out: &mut impl Write, term_width: u16) {
+        let text = self.text();
+        let x = term_width.saturating_sub(text.chars().count() as u16);
+        out.queue(cursor::MoveTo(x, 0)).unwrap();
+        out.queue(Print(text)).unwrap();
+    }
+}
+
+/// Redraws the clock every second between frames, so it keeps ticking at
+/// low frame rates. Stops when dropped.
+pub struct ClockThread {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ClockThread {
+    pub fn spawn(clock: &Clock) -> ClockThread {
+        let clock = clock.clone();
+        let stop = Arc::new(AtomicBool::new(false));
+        if term::legacy_console() {
+            // cursor movements can't be buffered there, so they could land
+            // in the middle of a frame; the clock only updates with frames
+            return ClockThread { stop, handle: None };
+        }
+        let handle = {
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                while !stop.load(Ordering::SeqCst) {
+                    // frames are written in one go, so this never lands in
+                    // the middle of one
+                    let mut buf = Vec::new();
+                    let width = terminal::size().map_or(0, |s| s.0);
+                    clock.queue(&mut buf, width);
+                    let mut stdout = stdout();
+                    let _ = stdout.write_all(&buf);
+                    let _ = stdout.flush();
+
+                    // wake up right after the next second starts
+                    let now = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap_or_default();
+                    let mut left = Duration::from_nanos(1_000_000_000 - now.subsec_nanos() as u64);
+                    while !left.is_zero() && !stop.load(Ordering::SeqCst) {
+                        let nap = left.min(Duration::from_millis(50));
+                        thread::sleep(nap);
+                        left -= nap;
+                    }
+                }
+            })
+        };
+        ClockThread {
+            stop,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for ClockThread {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Time zone, as an offset from UTC at any given time.
+#[derive(Clone)]
+enum Zone {
+    Utc,
+    Tz(TzData),
+}
+
+impl Zone {
+    /// Zone from `$TZ`, falling back to `/etc/localtime` and then UTC.
+    fn local() -> Zone {
+        if let Some(tz) = env::var("TZ").ok().filter(|tz| !tz.is_empty()) {
+            let tz = tz.trim_start_matches(':');
+            let path = if tz.starts_with('/') {
+                PathBuf::from(tz)
+            } else {
+                zoneinfo_dir().join(tz)
+            };
+            if let Some(data) = read_tzif(&path) {
+                return Zone::Tz(data);
+            }
+            if let Some(rule) = Rule::parse(tz) {
+                return Zone::Tz(TzData {
+                    rule: Some(rule),
+                    ..TzData::default()
+                });
+            }
+        }
+        read_tzif(Path::new("/etc/localtime")).map_or(Zone::Utc, Zone::Tz)
+    }
+
+    fn named(name: &str) -> Result<Zone, String> {
+        let valid = !name.is_empty()
+            && !name.starts_with('/')
+            && name.split('/').all(|part| !part.is_empty() && part != "..");
+        valid
+            .then(|| read_tzif(&zoneinfo_dir().join(name)))
+            .flatten()
+            .map(Zone::Tz)
+            .ok_or_else(|| {
+                format!(
+                    "unknown time zone `{}`, expected utc, local or a name like Europe/Paris",
+                    name
+                )
+            })
+    }
+
+    /// Offset from UTC in seconds and the zone abbreviation at a unix time.
+    fn offset_at(&self, time: i64) -> (i32, String) {
+        match self {
+            Zone::Utc => (0, "UTC".to_string()),
+            Zone::Tz(data) => data.offset_at(time),
+        }
+    }
+}
+
+fn zoneinfo_dir() -> PathBuf {
+    env::var_os("TZDIR").map_or_else(|| PathBuf::from("/usr/share/zoneinfo"), PathBuf::from)
+}
+
+/// Contents of a TZif file.
+#[derive(Clone, Default)]
+struct TzData {
+    /// Transition times and the index of the local time type from then on.
+    transitions: Vec<(i64, usize)>,
+    /// Offset from UTC in seconds and abbreviation.
+    types: Vec<(i32, String)>,
+    /// Rule for times after the last transition.
+    rule: Option<Rule>,
+}
+
+impl TzData {
+    fn offset_at(&self, time: i64) -> (i32, String) {
+        let after_last = self.transitions.last().is_none_or(|&(t, _)| time >= t);
+        if let (true, Some(rule)) = (after_last, &self.rule) {
+            return rule.offset_at(time);
+        }
+        let index = match self.transitions.iter().rposition(|&(t, _)| t <= time) {
+            Some(i) => self.transitions[i].1,
+            None => 0,
+        };
+        self.types
+            .get(index)
+            .cloned()
+            .unwrap_or_else(|| (0, "UTC".to_string()))
+    }
+}
+
+/// Reads a TZif file, see RFC 8536.
+fn read_tzif(path: &Path) -> Option<TzData> {
+    let data = fs::read(path).ok()?;
+    parse_tzif(&data)
+}
+
+fn parse_tzif(data: &[u8]) -> Option<TzData> {
+    let header = |at: usize| -> Option<[usize; 6]> {
+        if data.get(at..at + 4)? != b"TZif" {
+            return None;
+        }
+        let mut counts = [0; 6];
+        for (i, count) in counts.iter_mut().enumerate() {
+            let at = at + 20 + i * 4;
+            *count = u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?) as usize;
+        }
+        Some(counts)
+    };
+
+    let [isut, isstd, leap, time, types, chars] = header(0)?;
+    let version = *data.get(4)?;
+    let (mut at, time_size) = (44, 4);
+    let (counts, time_size) = if version >= b'2' {
+        // skip the 32-bit data, the 64-bit version follows
+        at += time * 4 + time + types * 6 + chars + leap * 8 + isstd + isut;
+        let counts = header(at)?;
+        at += 44;
+        (counts, 8)
+    } else {
+        ([isut, isstd, leap, time, types, chars], time_size)
+    };
+    let [isut, isstd, leap, time, types, chars] = counts;
+
+    let mut transitions = Vec::with_capacity(time);
+    for i in 0..time {
+        let start = at + i * time_size;
+        let bytes = data.get(start..start + time_size)?;
+        let t = if time_size == 8 {
+            i64::from_be_bytes(bytes.try_into().ok()?)
+        } else {
+            i32::from_be_bytes(bytes.try_into().ok()?) as i64
+        };
+        let index = *data.get(at + time * time_size + i)? as usize;
+        transitions.push((t, index));
+    }
+    at += time * time_size + time;
+
+    let abbrs = data.get(at + types * 6..at + types * 6 + chars)?;
+    let mut type_list = Vec::with_capacity(types);
+    for i in 0..types {
+        let entry = data.get(at + i * 6..at + i * 6 + 6)?;
+        let offset = i32::from_be_bytes(entry[..4].try_into().ok()?);
+        let abbr = abbrs.get(entry[5] as usize..)?;
+        let end = abbr.iter().position(|&b| b == 0).unwrap_or(abbr.len());
+        type_list.push((offset, String::from_utf8_lossy(&abbr[..end]).into_owned()));
+    }
+    at += types * 6 + chars + leap * (time_size + 4) + isstd + isut;
+
+    // the footer holds a POSIX TZ string between newlines
+    let rule = data
+        .get(at..)
+        .filter(|_| version >= b'2')
+        .and_then(|footer| std::str::from_utf8(footer).ok())
+        .and_then(|footer| {
+            footer
+                .trim_matches('\n')
+                .lines()
+                .next()
+                .and_then(Rule::parse)
+        });
+
+    Some(TzData {
+        transitions,
+        types: type_list,
+        rule,
+    })
+}
+
+/// Daylight saving time rule in the POSIX TZ format, e.g.
+/// `CET-1CEST,M3.5.0,M10.5.0/3`.
+#[derive(Clone)]
+struct Rule {
+    std: (i32, String),
+    dst: Option<Dst>,
+}
+
+#[derive(Clone)]
+struct Dst {
+    zone: (i32, String),
+    /// Local day and time in seconds when DST starts and ends.
+    start: (Day, i32),
+    end: (Day, i32),
+}
+
+#[derive(Clone, Copy)]
+enum Day {
+    /// Day of the year from 1 to 365, never counting February 29.
+    Julian(i64),
+    /// Day of the year from 0 to 365.
+    Zero(i64),
+    /// Month, week from 1 to 5 (5 meaning the last) and weekday from 0
+    /// (Sunday) to 6.
+    Month(i64, i64, i64),
+}
+
+impl Rule {
+    fn parse(s: &str) -> Option<Rule> {
+        let mut p = Parser(s);
+        let std_name = p.name()?;
+        let std_offset = -p.time()?;
+        if p.0.is_empty() {
+            return Some(Rule {
+                std: (std_offset, std_name),
+                dst: None,
+            });
+        }
+        let dst_name = p.name()?;
+        let dst_offset = if p.0.starts_with(',') {
+            std_offset + 3600
+        } else {
+            -p.time()?
+        };
+        // US rules are the default when none are given
+        let (start, end) = if p.0.is_empty() {
+            ((Day::Month(3, 2, 0), 7200), (Day::Month(11, 1, 0), 7200))
+        } else {
+            (p.transition()?, p.transition()?)
+        };
+        Some(Rule {
+            std: (std_offset, std_name),
+            dst: Some(Dst {
+                zone: (dst_offset, dst_name),
+                start,
+                end,
+            }),
+        })
+    }
+
+    fn offset_at(&self, time: i64) -> (i32, String) {
+        let dst = match &self.dst {
+            Some(dst) => dst,
+            None => return self.std.clone(),
+        };
+        let (y, ..) = civil_from_days((time + self.std.0 as i64).div_euclid(86400));
+        let start = dst.start.0.days(y) * 86400 + dst.start.1 as i64 - self.std.0 as i64;
+        let end = dst.end.0.days(y) * 86400 + dst.end.1 as i64 - dst.zone.0 as i64;
+        let in_dst = if start < end {
+            time >= start && time < end
+        } else {
+            // southern hemisphere, DST spans the new year
+            time >= start || time < end
+        };
+        if in_dst {
+            dst.zone.clone()
+        } else {
+            self.std.clone()
+        }
+    }
+}
+
+impl Day {
+    /// Days since the unix epoch of this day in `year`.
+    fn days(self, year: i64) -> i64 {
+        let jan1 = days_from_civil(year, 1, 1);
+        match self {
+            Day::Julian(n) => {
+                let leap = days_from_civil(year, 3, 1) - days_from_civil(year, 2, 1) == 29;
+                jan1 + n - 1 + (leap && n >= 60) as i64
+            }
+            Day::Zero(n) => jan1 + n,
+            Day::Month(m, w, d) => {
+                let first = days_from_civil(year, m, 1);
+                let next = if m == 12 {
+                    days_from_civil(year + 1, 1, 1)
+                } else {
+                    days_from_civil(year, m + 1, 1)
+                };
+                // 1970-01-01 was a Thursday
+                let weekday = (first + 4).rem_euclid(7);
+                let mut day = first + (d - weekday).rem_euclid(7) + (w - 1) * 7;
+                while day >= next {
+                    day -= 7;
+                }
+                day
+            }
+        }
+    }
+}
+
+/// Cursor over a POSIX TZ string.
+struct Parser<'a>(&'a str);
+
+impl Parser<'_> {
+    fn name(&mut self) -> Option<String> {
+        let (name, rest) = if let Some(quoted) = self.0.strip_prefix('<') {
+            let end = quoted.find('>')?;
+            (&quoted[..end], &quoted[end + 1..])
+        } else {
+            let end = self
+                .0
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(self.0.len());
+            (&self.0[..end], &self.0[end..])
+        };
+        if name.len() < 3 {
+            return None;
+        }
+        self.0 = rest;
+        Some(name.to_string())
+    }
+
+    /// `[+-]hh[:mm[:ss]]` in seconds.
+    fn time(&mut self) -> Option<i32> {
+        let sign = match self.0.chars().next()? {
+            '-' => -1,
+            _ => 1,
+        };
+        self.0 = self.0.trim_start_matches(['+', '-']);
+        let mut secs = 0;
+        for (i, unit) in [3600, 60, 1].iter().enumerate() {
+            if i > 0 {
+                match self.0.strip_prefix(':') {
+                    Some(rest) => self.0 = rest,
+                    None => break,
+                }
+            }
+            secs += self.number()? as i32 * unit;
+        }
+        Some(sign * secs)
+    }
+
+    /// `,date[/time]`, with the time defaulting to 02:00.
+    fn transition(&mut self) -> Option<(Day, i32)> {
+        self.0 = self.0.strip_prefix(',')?;
+        let day = if let Some(rest) = self.0.strip_prefix('M') {
+            self.0 = rest;
+            let m = self.number()?;
+            self.0 = self.0.strip_prefix('.')?;
+            let w = self.number()?;
+            self.0 = self.0.strip_prefix('.')?;
+            let d = self.number()?;
+            if !(1..=12).contains(&m) || !(1..=5).contains(&w) || d > 6 {
+                return None;
+            }
+            Day::Month(m, w, d)
+        } else if let Some(rest) = self.0.strip_prefix('J') {
+            self.0 = rest;
+            Day::Julian(self.number()?.clamp(1, 365))
+        } else {
+            Day::Zero(self.number()?.min(365))
+        };
+        let time = match self.0.strip_prefix('/') {
+            Some(rest) => {
+                self.0 = rest;
+                self.time()?
+            }
+            None => 7200,
+        };
+        Some((day, time))
+    }
+
+    fn number(&mut self) -> Option<i64> {
+        let end = self
+            .0
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.0.len());
+        let n = self.0[..end].parse().ok()?;
+        self.0 = &self.0[end..];
+        Some(n)
+    }
+}
+
+// Days since the unix epoch for a proleptic gregorian date and back.
+// See http://howardhinnant.github.io/date_algorithms.html
+
+pub fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+pub fn civil_from_days(z: i64) -> (i64, i64, i64) {
+    let z = z + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    (y, m, d)
+}
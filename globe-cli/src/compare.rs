@@ -0,0 +1,270 @@
+//! Two globes side by side, for `--compare`.
+
+use std::f32::consts::PI;
+use std::io::Write;
+use std::time::Duration;
+
+use crossterm::event::{Event, KeyCode};
+use globe::{Camera, Canvas, Float, Globe, GlobeTemplate, MAX_TILT};
+
+use crate::app::{self, Mode};
+use crate::control::Command;
+use crate::keys::Action;
+use crate::term::{self, Title};
+use crate::{canvas_for, overlay, Settings};
+
+/// Which panes input applies to, switched with Tab.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Focus {
+    Both,
+    Left,
+    Right,
+}
+
+impl Focus {
+    pub fn next(self) -> Focus {
+        match self {
+            Focus::Both => Focus::Left,
+            Focus::Left => Focus::Right,
+            Focus::Right => Focus::Both,
+        }
+    }
+
+    /// Whether input goes to the pane with index `i`, 0 being the left one.
+    pub fn includes(self, i: usize) -> bool {
+        match self {
+            Focus::Both => true,
+            Focus::Left => i == 0,
+            Focus::Right => i == 1,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Focus::Both => "both",
+            Focus::Left => "left",
+            Focus::Right => "right",
+        }
+    }
+}
+
+/// A globe in one pane with its own view.
+pub struct Pane {
+    pub name: String,
+    pub globe: Globe,
+    /// Camera tilt and distance.
+    pub tilt: Float,
+    pub zoom: Float,
+}
+
+impl Pane {
+    pub fn render(&mut self, canvas: &mut Canvas) {
+        self.globe.camera = Camera::new(self.zoom, 0., self.tilt + self.globe.wobble_offset());
+        canvas.clear();
+        self.globe.render_on(canvas);
+    }
+}
+
+/// Size in cells of each pane when the frame is `size` cells, with the
+/// width split in half.
+pub fn pane_size(size: (u16, u16)) -> (u16, u16) {
+    ((size.0 / 2).max(1), size.1)
+}
+
+/// Canvas holding `left` and `right` next to each other, both being the
+/// same size.
+pub fn side_by_side(left: &Canvas, right: &Canvas, palette: &[char]) -> Canvas {
+    let (width, height) = overlay::grid_size(left);
+    let mut canvas = Canvas::new(width as u16 * 8, height as u16 * 8, None).with_palette(palette);
+    for (offset, pane) in [(0, left), (width, right)] {
+        for (y, row) in pane.matrix.iter().take(height).enumerate() {
+            for (x, &c) in row.iter().take(width).enumerate() {
+                canvas.draw_point(offset + x, y, c);
+            }
+        }
+    }
+    canvas
+}
+
+/// Shows the `left` and `right` templates, each with its name, until it's
+/// interrupted or quit.
+pub fn start(left: &(String, GlobeTemplate), right: &(String, GlobeTemplate), settings: &Settings) {
+    app::run(settings, false, |term_size, _| {
+        Compare::new([left, right], settings, term_size)
+    });
+}
+
+/// State of `--compare` between frames.
+pub struct Compare<'a> {
+    settings: &'a Settings,
+    panes: [Pane; 2],
+    /// Canvases the panes are rendered on, and the frame they're put
+    /// together in.
+    canvases: [Canvas; 2],
+    frame: Canvas,
+    term_size: (u16, u16),
+    focus: Focus,
+    paused: bool,
+    speed: Float,
+    /// Initial view, for `Action::Reset`.
+    angle: Float,
+    tilt: Float,
+    title: Title,
+    /// Whether the terminal needs clearing before the next frame.
+    clear: bool,
+}
+
+/// Canvases for the panes in a terminal of `term_size` cells, leaving a row
+/// for the status line.
+fn pane_canvases(settings: &Settings, term_size: (u16, u16)) -> [Canvas; 2] {
+    let (width, height) = settings.frame_size(term_size);
+    let size = pane_size((width, height.saturating_sub(1)));
+    [(); 2].map(|_| canvas_for(size, settings))
+}
+
+impl<'a> Compare<'a> {
+    /// The templates of `panes`, by name, at the initial view.
+    pub fn new(
+        panes: [&(String, GlobeTemplate); 2],
+        settings: &'a Settings,
+        term_size: (u16, u16),
+    ) -> Compare<'a> {
+        let (angle, tilt) = settings.initial_view();
+        let panes = panes.map(|(name, template)| {
+            let mut globe = settings.globe_of(*template);
+            globe.angle = angle;
+            Pane {
+                name: name.clone(),
+                globe,
+                tilt,
+                zoom: settings.zoom,
+            }
+        });
+        let canvases = pane_canvases(settings, term_size);
+        Compare {
+            settings,
+            panes,
+            frame: side_by_side(&canvases[0], &canvases[1], &settings.palette),
+            canvases,
+            term_size,
+            focus: Focus::Both,
+            paused: false,
+            speed: settings.speed,
+            angle,
+            tilt,
+            title: Title::default(),
+            clear: false,
+        }
+    }
+
+    /// Carries out `action` on the panes with the focus.
+    fn act(&mut self, action: Action) {
+        let (angle, tilt, zoom) = (self.angle, self.tilt, self.settings.zoom);
+        for (i, pane) in self.panes.iter_mut().enumerate() {
+            if !self.focus.includes(i) {
+                continue;
+            }
+            match action {
+                Action::ZoomOut => pane.zoom += 0.1,
+                Action::ZoomIn => pane.zoom = (pane.zoom - 0.1).max(1.),
+                Action::TiltUp => pane.tilt = (pane.tilt + 0.1).min(MAX_TILT),
+                Action::TiltDown => pane.tilt = (pane.tilt - 0.1).max(-MAX_TILT),
+                Action::RotateLeft => pane.globe.angle += PI / 30.,
+                Action::RotateRight => pane.globe.angle -= PI / 30.,
+                action if action.diagonal().is_some() => {
+                    let (turn, tilt) = action.diagonal().unwrap();
+                    pane.globe.angle += turn * PI / 30.;
+                    pane.tilt = (pane.tilt + tilt * 0.1).clamp(-MAX_TILT, MAX_TILT);
+                }
+                Action::Reset => {
+                    pane.globe.angle = angle;
+                    pane.tilt = tilt;
+                    pane.zoom = zoom;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Mode for Compare<'_> {
+    fn handle_event(&mut self, event: Event) -> app::Action {
+        match event {
+            Event::Key(event) if term::is_interrupt(&event) => return app::Action::Quit,
+            Event::Key(event) if event.code == KeyCode::Tab => self.focus = self.focus.next(),
+            Event::Key(event) => match self.settings.bindings.action(event) {
+                Some(Action::Quit) => return app::Action::Quit,
+                Some(Action::Pause) => self.paused = !self.paused,
+                Some(action) => self.act(action),
+                None => {}
+            },
+            Event::Resize(width, height) => {
+                self.term_size = (width, height);
+                self.canvases = pane_canvases(self.settings, self.term_size);
+                self.clear = true;
+            }
+            Event::Mouse(_) => {}
+        }
+        app::Action::Continue
+    }
+
+    fn handle_command(&mut self, command: Command) -> app::Action {
+        for pane in &mut self.panes {
+            match command {
+                Command::Focus(lat, lon) => {
+                    pane.globe.angle = -lon.to_radians();
+                    pane.tilt = globe::tilt_for_latitude(lat);
+                }
+                Command::Zoom(zoom) => pane.zoom = zoom,
+                _ => {}
+            }
+        }
+        match command {
+            Command::Speed(s) => self.speed = s,
+            Command::Quit => return app::Action::Quit,
+            _ => {}
+        }
+        app::Action::Continue
+    }
+
+    fn update(&mut self, dt: Duration) {
+        // both spin at the same rate, whatever their own rotation, and as
+        // fast as the screensaver does
+        let turn = PI / 50. * self.speed * self.settings.fps * dt.as_secs_f32();
+        for pane in &mut self.panes {
+            self.settings.update_globe(&mut pane.globe);
+            if !self.paused {
+                pane.globe.angle -= turn;
+            }
+        }
+    }
+
+    fn draw(&mut self, out: &mut impl Write, _fps: Float) -> Option<&Canvas> {
+        let settings = self.settings;
+        for (pane, canvas) in self.panes.iter_mut().zip(&mut self.canvases) {
+            pane.render(canvas);
+            settings.apply_effects(canvas);
+        }
+        self.frame = side_by_side(&self.canvases[0], &self.canvases[1], &settings.palette);
+
+        let status = format!(
+            "{} | {}  controlling: {} (Tab to switch)",
+            self.panes[0].name,
+            self.panes[1].name,
+            self.focus.name(),
+        );
+        app::print_frame(
+            out,
+            settings,
+            &mut self.title,
+            &self.panes[0].globe,
+            self.panes[0].zoom,
+            &mut self.frame,
+            None,
+            self.term_size,
+            std::mem::take(&mut self.clear),
+            Some(&status),
+        );
+        Some(&self.frame)
+    }
+}
@@ -0,0 +1,107 @@
+//! Shell completion scripts, for the `completions` command.
+
+use std::fmt::Write;
+
+use clap::App;
+
+/// Shells completion scripts can be generated for.
+pub const SHELLS: [&str; 3] = ["bash", "zsh", "fish"];
+
+/// Flag or option of the command line.
+struct Flag {
+    long: Option<String>,
+    short: Option<char>,
+    help: String,
+    takes_value: bool,
+}
+
+/// Completion script for `shell` completing the subcommands and options of
+/// `app`, or `None` for an unknown shell. Values aren't completed.
+pub fn generate(app: &App, shell: &str) -> Option<String> {
+    let flags: Vec<Flag> = app
+        .get_arguments()
+        .filter(|a| !a.is_hide_set() && (a.get_long().is_some() || a.get_short().is_some()))
+        .map(|a| Flag {
+            long: a.get_long().map(str::to_string),
+            short: a.get_short(),
+            help: a.get_help().unwrap_or("").to_string(),
+            takes_value: a.is_takes_value_set(),
+        })
+        .collect();
+    let commands: Vec<(&str, &str)> = app
+        .get_subcommands()
+        .map(|c| (c.get_name(), c.get_about().unwrap_or("")))
+        .collect();
+    match shell {
+        "bash" => Some(bash(&flags, &commands)),
+        "zsh" => Some(zsh(&flags, &commands)),
+        "fish" => Some(fish(&flags, &commands)),
+        _ => None,
+    }
+}
+
+fn bash(flags: &[Flag], commands: &[(&str, &str)]) -> String {
+    let mut words: Vec<String> = commands.iter().map(|(name, _)| name.to_string()).collect();
+    for flag in flags {
+        words.extend(flag.long.iter().map(|l| format!("--{}", l)));
+        words.extend(flag.short.iter().map(|s| format!("-{}", s)));
+    }
+    format!(
+        "_globe() {{\n    \
+             local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"\n    \
+             COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n\
+         }}\n\
+         complete -o default -F _globe globe\n",
+        words.join(" ")
+    )
+}
+
+fn zsh(flags: &[Flag], commands: &[(&str, &str)]) -> String {
+    let mut out = "#compdef globe\n\n_arguments \\\n".to_string();
+    for flag in flags {
+        let help = flag.help.replace('\'', "'\\''").replace(['[', ']'], "");
+        let value = if flag.takes_value { ":value:" } else { "" };
+        let names = flag
+            .long
+            .iter()
+            .map(|l| format!("--{}", l))
+            .chain(flag.short.iter().map(|s| format!("-{}", s)));
+        for name in names {
+            writeln!(out, "    '{}[{}]{}' \\", name, help, value).unwrap();
+        }
+    }
+    let commands: Vec<String> = commands
+        .iter()
+        .map(|(name, about)| format!("{}\\:\"{}\"", name, about.replace('\'', "'\\''")))
+        .collect();
+    writeln!(out, "    '1:command:(({}))'", commands.join(" ")).unwrap();
+    out
+}
+
+fn fish(flags: &[Flag], commands: &[(&str, &str)]) -> String {
+    let quote = |s: &str| format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"));
+    let mut out = String::new();
+    for (name, about) in commands {
+        writeln!(
+            out,
+            "complete -c globe -n __fish_use_subcommand -f -a {} -d {}",
+            name,
+            quote(about)
+        )
+        .unwrap();
+    }
+    for flag in flags {
+        let mut line = "complete -c globe".to_string();
+        if let Some(long) = &flag.long {
+            write!(line, " -l {}", long).unwrap();
+        }
+        if let Some(short) = flag.short {
+            write!(line, " -s {}", short).unwrap();
+        }
+        if flag.takes_value {
+            line.push_str(" -r");
+        }
+        writeln!(out, "{} -d {}", line, quote(&flag.help)).unwrap();
+    }
+    out
+}
@@ -0,0 +1,568 @@
+//! Configuration file support.
+//!
+//! The file lives at `$XDG_CONFIG_HOME/globe/config.toml` (`~/.config` when
+//! unset, the platform's application data directory on Windows and macOS)
+//! and uses a small subset of TOML: `key = value` pairs with string, number,
+//! boolean or array values, `[section]` headers and `#` comments.
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use globe::{Color, Float, Theme, PALETTE};
+
+use crate::keys::{self, Action, Key};
+use crate::term::ColorChoice;
+
+/// Values read from the configuration file. Unset keys are `None`.
+#[derive(Default)]
+pub struct Config {
+    pub template: Option<String>,
+    pub speed: Option<Float>,
+    pub zoom: Option<Float>,
+    pub coords: Option<(Float, Float)>,
+    pub fps: Option<Float>,
+    /// When to color the globe like `--color`, `true` being `auto` and
+    /// `false` `never`.
+    pub color: Option<ColorChoice>,
+    /// Whether to light the night side with city lights like
+    /// `--city-lights`.
+    pub night: Option<bool>,
+    /// Palette selected by `charset`.
+    pub charset: Option<Vec<char>>,
+    /// How held arrow keys move the view, `hold` or `step`.
+    pub arrows: Option<String>,
+    /// Degrees per second held arrow keys turn the view by.
+    pub arrow_speed: Option<Float>,
+    /// Key bindings from the `[keys]` section.
+    pub keys: Vec<(Action, Vec<Key>)>,
+    /// Theme of `--color` from the `[theme]` section: a `base` theme like
+    /// `--theme` takes, with colors like `land_equator = "#c87d23"` and
+    /// the `ice_latitude` changed.
+    pub theme: Option<Theme>,
+}
+
+impl Config {
+    /// Default location of the configuration file.
+    pub fn default_path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join("config.toml"))
+    }
+
+    /// Loads the configuration at `path`. A missing file is not an error.
+    pub fn load(path: &Path) -> Result<Config, Error> {
+        match fs::read_to_string(path) {
+            Ok(text) => Config::parse(&text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(Error {
+                line: 0,
+                key: None,
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    /// Parses the contents of a configuration file.
+    pub fn parse(text: &str) -> Result<Config, Error> {
+        let mut config = Config::default();
+        let mut theme_entries = Vec::new();
+        for entry in parse_document(text)? {
+            let Entry {
+                section,
+                key,
+                value,
+                line,
+            } = entry;
+            let err = |message: &str| Error {
+                line,
+                key: Some(key.clone()),
+                message: message.to_string(),
+            };
+            if section == "theme" {
+                theme_entries.push((key, value, line));
+                continue;
+            }
+            if section == "keys" {
+                let action =
+                    Action::from_name(&key).ok_or_else(|| err(&keys::unknown_action(&key)))?;
+                let names = match &value {
+                    Value::Str(s) => vec![s.as_str()],
+                    Value::Array(a) => a
+                        .iter()
+                        .map(|v| v.as_str())
+                        .collect::<Option<Vec<_>>>()
+                        .ok_or_else(|| err("expected a key or a list of keys"))?,
+                    _ => return Err(err("expected a key or a list of keys")),
+                };
+                let action_keys = names
+                    .into_iter()
+                    .map(Key::parse)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| err(&e))?;
+                config.keys.push((action, action_keys));
+                continue;
+            }
+            if !section.is_empty() {
+                return Err(Error {
+                    line,
+                    key: None,
+                    message: format!("unknown section `{}`", section),
+                });
+            }
+            match key.as_str() {
+                "template" => {
+                    config.template = Some(
+                        value
+                            .as_str()
+                            .ok_or_else(|| err("expected a string"))?
+                            .to_string(),
+                    )
+                }
+                "speed" => {
+                    config.speed = Some(value.as_float().ok_or_else(|| err("expected a number"))?)
+                }
+                "zoom" => {
+                    config.zoom = Some(
+                        value
+                            .as_float()
+                            .filter(|&zoom| zoom >= 1.)
+                            .ok_or_else(|| err("expected a number of at least 1"))?,
+                    )
+                }
+                "fps" => {
+                    config.fps = Some(
+                        value
+                            .as_float()
+                            .filter(|&fps| fps > 0.)
+                            .ok_or_else(|| err("expected a positive number"))?,
+                    )
+                }
+                "color" => {
+                    config.color = Some(match (value.as_bool(), value.as_str()) {
+                        (Some(true), _) => ColorChoice::Auto,
+                        (Some(false), _) => ColorChoice::Never,
+                        (_, Some(s)) => ColorChoice::parse(s).map_err(|e| err(&e))?,
+                        _ => return Err(err("expected true, false, always, auto or never")),
+                    })
+                }
+                "night" => {
+                    config.night = Some(
+                        value
+                            .as_bool()
+                            .ok_or_else(|| err("expected true or false"))?,
+                    )
+                }
+                "arrows" => {
+                    config.arrows = Some(
+                        value
+                            .as_str()
+                            .ok_or_else(|| err("expected a string"))?
+                            .to_string(),
+                    )
+                }
+                "arrow_speed" => {
+                    config.arrow_speed =
+                        Some(value.as_float().ok_or_else(|| err("expected a number"))?)
+                }
+                "charset" => {
+                    let name = value.as_str().ok_or_else(|| err("expected a string"))?;
+                    config.charset = Some(parse_charset(name).map_err(|e| err(&e))?);
+                }
+                "coords" => {
+                    let coords = match &value {
+                        Value::Str(s) => parse_coords(s).ok(),
+                        Value::Array(a) if a.len() == 2 => a[0].as_float().zip(a[1].as_float()),
+                        _ => None,
+                    };
+                    config.coords = Some(
+                        coords
+                            .ok_or_else(|| err("expected \"lat,lon\" or [lat, lon] in degrees"))?,
+                    );
+                }
+                _ => return Err(err("unknown key")),
+            }
+        }
+        if !theme_entries.is_empty() {
+            config.theme = Some(parse_theme_section(theme_entries)?);
+        }
+        Ok(config)
+    }
+}
+
+/// Builds the theme of a `[theme]` section from its entries, starting from
+/// the `base` theme wherever it's given.
+fn parse_theme_section(entries: Vec<(String, Value, usize)>) -> Result<Theme, Error> {
+    let err = |key: &str, line: usize, message: &str| Error {
+        line,
+        key: Some(key.to_string()),
+        message: message.to_string(),
+    };
+    let mut theme = Theme::default();
+    for (key, value, line) in entries.iter().filter(|(key, ..)| key == "base") {
+        let name = value
+            .as_str()
+            .ok_or_else(|| err(key, *line, "expected a string"))?;
+        theme = parse_theme(name).map_err(|e| err(key, *line, &e))?;
+    }
+    for (key, value, line) in entries.iter().filter(|(key, ..)| key != "base") {
+        let slot = match key.as_str() {
+            "ice_latitude" => {
+                theme.ice_latitude = value
+                    .as_float()
+                    .filter(|lat| (0. ..=90.).contains(lat))
+                    .ok_or_else(|| err(key, *line, "expected degrees from 0 to 90"))?;
+                continue;
+            }
+            "ocean_equator" => &mut theme.ocean_equator,
+            "ocean_pole" => &mut theme.ocean_pole,
+            "land_equator" => &mut theme.land_equator,
+            "land_pole" => &mut theme.land_pole,
+            "ice" => &mut theme.ice,
+            "marker" => &mut theme.marker,
+            "background" => &mut theme.background,
+            _ => return Err(err(key, *line, "unknown key")),
+        };
+        *slot = value
+            .as_str()
+            .and_then(parse_color)
+            .ok_or_else(|| err(key, *line, "expected a color like \"#1e6ed2\""))?;
+    }
+    Ok(theme)
+}
+
+/// Parses a `--theme` name.
+pub fn parse_theme(s: &str) -> Result<Theme, String> {
+    Theme::by_name(s).ok_or_else(|| {
+        format!(
+            "unknown theme `{}`, expected one of {}",
+            s,
+            Theme::NAMES.join(", ")
+        )
+    })
+}
+
+/// Parses a `#rrggbb` color.
+pub fn parse_color(s: &str) -> Option<Color> {
+    let hex = s.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some(Color::rgb(channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Parses a `lat,lon` pair in degrees.
+pub fn parse_coords(s: &str) -> Result<(Float, Float), String> {
+    let mut parts = s.split(',');
+    let lat = parts.next().and_then(|p| p.trim().parse::<Float>().ok());
+    let lon = parts.next().and_then(|p| p.trim().parse::<Float>().ok());
+    match (lat, lon, parts.next()) {
+        (Some(lat), Some(lon), None) if lat.abs() <= 90. && lon.abs() <= 180. => Ok((lat, lon)),
+        _ => Err(format!(
+            "invalid coordinates `{}`, expected `lat,lon` in degrees",
+            s
+        )),
+    }
+}
+
+/// Parses a `--charset` name, or `custom:` followed by the characters to
+/// use from darkest to brightest.
+pub fn parse_charset(s: &str) -> Result<Vec<char>, String> {
+    let chars = match s {
+        "ascii" => return Ok(PALETTE.to_vec()),
+        "unicode" => " ·:∙•◦○◍◎●◉",
+        "blocks" => " ░▒▓█",
+        "braille" => " ⠁⠃⠇⠏⠟⠿⡿⣿",
+        _ => match s.strip_prefix("custom:") {
+            Some("") => return Err("custom charset can't be empty".to_string()),
+            Some(chars) => chars,
+            None => {
+                return Err(format!(
+                "unknown charset `{}`, expected ascii, unicode, blocks, braille or custom:<chars>",
+                s
+            ))
+            }
+        },
+    };
+    Ok(chars.chars().collect())
+}
+
+/// Error in a configuration file.
+pub struct Error {
+    /// Line the error was found on, starting at 1. Zero if not applicable.
+    pub line: usize,
+    pub key: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line > 0 {
+            write!(f, "line {}: ", self.line)?;
+        }
+        if let Some(key) = &self.key {
+            write!(f, "`{}`: ", key)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Value of a configuration entry.
+#[derive(Clone)]
+pub enum Value {
+    Str(String),
+    Num(f64),
+    Bool(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+    /// The value as a finite number; NaN and infinities are no numbers
+    /// any key takes.
+    pub fn as_float(&self) -> Option<Float> {
+        match self {
+            Value::Num(n) => Some(*n as Float).filter(|n| n.is_finite()),
+            _ => None,
+        }
+    }
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
+
+/// Single `key = value` line of a configuration file.
+pub struct Entry {
+    /// Name of the enclosing `[section]`, empty at the top level.
+    pub section: String,
+    pub key: String,
+    pub value: Value,
+    pub line: usize,
+}
+
+/// Splits a configuration file into its entries.
+pub fn parse_document(text: &str) -> Result<Vec<Entry>, Error> {
+    let mut entries = Vec::new();
+    let mut section = String::new();
+    for (i, raw) in text.lines().enumerate() {
+        let line = i + 1;
+        let content = strip_comment(raw).trim();
+        if content.is_empty() {
+            continue;
+        }
+        let err = |key: Option<&str>, message: &str| Error {
+            line,
+            key: key.map(str::to_string),
+            message: message.to_string(),
+        };
+        if let Some(name) = content.strip_prefix('[') {
+            let name = name
+                .strip_suffix(']')
+                .ok_or_else(|| err(None, "unterminated section header"))?;
+            section = name.trim().to_string();
+            continue;
+        }
+        let eq = content
+            .find('=')
+            .ok_or_else(|| err(None, "expected `key = value`"))?;
+        let key = unquote(content[..eq].trim());
+        if key.is_empty() {
+            return Err(err(None, "missing key"));
+        }
+        let value = parse_value(content[eq + 1..].trim())
+            .ok_or_else(|| err(Some(&key), "invalid value"))?;
+        if entries
+            .iter()
+            .any(|e: &Entry| e.section == section && e.key == key)
+        {
+            return Err(err(Some(&key), "duplicate key"));
+        }
+        entries.push(Entry {
+            section: section.clone(),
+            key,
+            value,
+            line,
+        });
+    }
+    Ok(entries)
+}
+
+/// Removes a trailing `#` comment, ignoring `#` inside strings.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match (c, quote) {
+            ('"', None) | ('\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('#', None) => return &line[..i],
+            _ => (),
+        }
+    }
+    line
+}
+
+fn unquote(s: &str) -> String {
+    if s.len() >= 2
+        && (s.starts_with('"') && s.ends_with('"') || s.starts_with('\'') && s.ends_with('\''))
+    {
+        s[1..s.len() - 1].to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+fn parse_value(s: &str) -> Option<Value> {
+    if s.len() >= 2
+        && (s.starts_with('"') && s.ends_with('"') || s.starts_with('\'') && s.ends_with('\''))
+    {
+        let inner = &s[1..s.len() - 1];
+        if s.starts_with('\'') {
+            return Some(Value::Str(inner.to_string()));
+        }
+        return unescape(inner).map(Value::Str);
+    }
+    if let Some(inner) = s.strip_prefix('[') {
+        let inner = inner.strip_suffix(']')?.trim();
+        if inner.is_empty() {
+            return Some(Value::Array(Vec::new()));
+        }
+        return split_array(inner)
+            .iter()
+            .map(|item| parse_value(item.trim()))
+            .collect::<Option<Vec<_>>>()
+            .map(Value::Array);
+    }
+    match s {
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ => s.replace('_', "").parse().ok().map(Value::Num),
+    }
+}
+
+/// Splits array items on commas outside of strings. A trailing comma is
+/// allowed.
+fn split_array(s: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match (c, quote) {
+            ('"', None) | ('\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            (',', None) => {
+                items.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    if !s[start..].trim().is_empty() {
+        items.push(&s[start..]);
+    }
+    items
+}
+
+fn unescape(s: &str) -> Option<String> {
+    let mut out = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => out.push('\n'),
+            't' => out.push('\t'),
+            '"' => out.push('"'),
+            '\\' => out.push('\\'),
+            'u' => {
+                let hex: String = chars.by_ref().take(4).collect();
+                out.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+            }
+            _ => return None,
+        }
+    }
+    Some(out)
+}
+
+/// Directory holding the configuration file.
+pub fn config_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir).join("globe"));
+    }
+    if cfg!(windows) {
+        return env::var_os("APPDATA").map(|dir| PathBuf::from(dir).join("globe"));
+    }
+    let home = PathBuf::from(env::var_os("HOME")?);
+    if cfg!(target_os = "macos") {
+        Some(home.join("Library/Application Support/globe"))
+    } else {
+        Some(home.join(".config/globe"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(text: &str) -> String {
+        match Config::parse(text) {
+            Ok(_) => panic!("{:?} parsed", text),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn unusable_numbers() {
+        assert_eq!(
+            error("fps = nan"),
+            "line 1: `fps`: expected a positive number"
+        );
+        assert_eq!(
+            error("fps = 0"),
+            "line 1: `fps`: expected a positive number"
+        );
+        assert_eq!(
+            error("\nzoom = inf"),
+            "line 2: `zoom`: expected a number of at least 1"
+        );
+        assert_eq!(error("speed = -inf"), "line 1: `speed`: expected a number");
+        let config = Config::parse("fps = 1e-30\nzoom = 1").ok().unwrap();
+        assert_eq!((config.fps, config.zoom), (Some(1e-30), Some(1.)));
+    }
+
+    #[test]
+    fn switches() {
+        let config = Config::parse("color = true\nnight = false").ok().unwrap();
+        assert_eq!(
+            (config.color, config.night),
+            (Some(ColorChoice::Auto), Some(false))
+        );
+        assert_eq!(
+            error("night = 1"),
+            "line 1: `night`: expected true or false"
+        );
+    }
+
+    #[test]
+    fn color() {
+        let color = |text| Config::parse(text).ok().unwrap().color;
+        assert_eq!(color("color = false"), Some(ColorChoice::Never));
+        assert_eq!(color("color = \"always\""), Some(ColorChoice::Always));
+        assert_eq!(color("color = \"auto\""), Some(ColorChoice::Auto));
+        assert_eq!(
+            error("color = \"true\""),
+            "line 1: `color`: invalid color `true`, expected always, auto or never"
+        );
+        assert_eq!(
+            error("color = 1"),
+            "line 1: `color`: expected true, false, always, auto or never"
+        );
+    }
+}
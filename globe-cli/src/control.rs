@@ -0,0 +1,99 @@
+//! Commands read from stdin while rendering, for `--control-stdin`.
+
+use std::io::{stdin, BufRead};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use globe::Float;
+
+use crate::config;
+use crate::markers::Marker;
+
+/// Help text listing the commands.
+pub const USAGE: &str = "focus LAT,LON | zoom N | speed N | marker add LAT,LON CHAR [LABEL] \
+                         | marker clear | quit";
+
+/// Command changing what's on screen.
+pub enum Command {
+    /// Center the view on a latitude and longitude in degrees.
+    Focus(Float, Float),
+    /// Set the camera distance from the globe center.
+    Zoom(Float),
+    /// Set the screensaver rotation speed multiplier.
+    Speed(Float),
+    AddMarker(Marker),
+    ClearMarkers,
+    Quit,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Command, String> {
+        let mut words = line.split_whitespace();
+        let command = words.next().ok_or("empty command")?;
+        let mut arg = |name: &str| {
+            words
+                .next()
+                .ok_or_else(|| format!("`{}` expects {}", command, name))
+        };
+        let number = |s: &str| s.parse().map_err(|_| format!("invalid number `{}`", s));
+        let parsed = match command {
+            "focus" => {
+                let (lat, lon) = config::parse_coords(arg("LAT,LON")?)?;
+                Command::Focus(lat, lon)
+            }
+            "zoom" => {
+                let zoom = number(arg("a distance")?)?;
+                if zoom < 1. {
+                    return Err("zoom must be at least 1".to_string());
+                }
+                Command::Zoom(zoom)
+            }
+            "speed" => Command::Speed(number(arg("a speed")?)?),
+            "marker" => match arg("`add` or `clear`")? {
+                "add" => {
+                    let (lat, lon) = config::parse_coords(arg("LAT,LON")?)?;
+                    let mut chars = arg("a character")?.chars();
+                    let c = match (chars.next(), chars.next()) {
+                        (Some(c), None) => c,
+                        _ => return Err("markers are a single character".to_string()),
+                    };
+                    let label: Vec<&str> = words.by_ref().collect();
+                    let label = Some(label.join(" ")).filter(|l| !l.is_empty());
+                    return Ok(Command::AddMarker(Marker { lat, lon, label, c }));
+                }
+                "clear" => Command::ClearMarkers,
+                other => return Err(format!("unknown marker command `{}`", other)),
+            },
+            "quit" => Command::Quit,
+            other => return Err(format!("unknown command `{}`, expected {}", other, USAGE)),
+        };
+        match words.next() {
+            Some(extra) => Err(format!("unexpected `{}` after `{}`", extra, command)),
+            None => Ok(parsed),
+        }
+    }
+}
+
+/// Reads commands from stdin on a separate thread, so waiting for input
+/// never holds up a frame. Blank lines and lines starting with `#` are
+/// skipped. The channel disconnects at the end of the input.
+pub fn spawn() -> Receiver<Result<Command, String>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in stdin().lock().lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let command = Command::parse(line).map_err(|e| format!("`{}`: {}", line, e));
+            if sender.send(command).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
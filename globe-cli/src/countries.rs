@@ -0,0 +1,156 @@
+//! Countries known by code or name, for `--country`.
+
+use globe::{Float, Highlight};
+
+/// A country with its approximate center and extent.
+pub struct Country {
+    /// ISO 3166-1 alpha-2 code.
+    pub code: &'static str,
+    pub name: &'static str,
+    /// Rough center of the country in degrees.
+    pub lat: Float,
+    pub lon: Float,
+    /// Radius in degrees of arc of a circle around the center covering most
+    /// of the country.
+    pub radius: Float,
+}
+
+impl Country {
+    /// The country's circle, to highlight it on the globe.
+    pub fn highlight(&self) -> Highlight {
+        Highlight {
+            lat: self.lat,
+            lon: self.lon,
+            radius: self.radius,
+        }
+    }
+}
+
+const fn country(
+    code: &'static str,
+    name: &'static str,
+    lat: Float,
+    lon: Float,
+    radius: Float,
+) -> Country {
+    Country {
+        code,
+        name,
+        lat,
+        lon,
+        radius,
+    }
+}
+
+/// Known countries, sorted by code.
+pub const COUNTRIES: &[Country] = &[
+    country("AE", "United Arab Emirates", 24.0, 54.0, 2.5),
+    country("AF", "Afghanistan", 33.9, 67.7, 6.),
+    country("AR", "Argentina", -38.4, -63.6, 14.),
+    country("AT", "Austria", 47.5, 14.6, 2.5),
+    country("AU", "Australia", -25.3, 133.8, 19.),
+    country("BD", "Bangladesh", 23.7, 90.4, 2.5),
+    country("BE", "Belgium", 50.5, 4.5, 1.5),
+    country("BO", "Bolivia", -16.3, -63.6, 7.),
+    country("BR", "Brazil", -10.8, -52.9, 20.),
+    country("CA", "Canada", 60.0, -96.8, 22.),
+    country("CD", "Democratic Republic of the Congo", -2.9, 23.7, 10.),
+    country("CH", "Switzerland", 46.8, 8.2, 1.5),
+    country("CL", "Chile", -35.7, -71.5, 19.),
+    country("CN", "China", 35.9, 104.2, 20.),
+    country("CO", "Colombia", 4.6, -74.3, 8.),
+    country("CU", "Cuba", 21.5, -79.5, 5.),
+    country("CZ", "Czechia", 49.8, 15.5, 2.5),
+    country("DE", "Germany", 51.2, 10.5, 4.5),
+    country("DK", "Denmark", 56.0, 10.0, 2.5),
+    country("DZ", "Algeria", 28.0, 1.7, 12.),
+    country("EG", "Egypt", 26.8, 30.8, 6.),
+    country("ES", "Spain", 40.5, -3.7, 5.),
+    country("ET", "Ethiopia", 9.1, 40.5, 7.),
+    country("FI", "Finland", 64.5, 26.0, 6.),
+    country("FR", "France", 46.6, 2.2, 5.5),
+    country("GB", "United Kingdom", 54.4, -2.4, 5.),
+    country("GR", "Greece", 39.1, 22.9, 3.5),
+    country("ID", "Indonesia", -2.5, 118.0, 22.),
+    country("IE", "Ireland", 53.4, -8.2, 2.5),
+    country("IL", "Israel", 31.0, 34.9, 2.),
+    country("IN", "India", 21.1, 78.9, 14.),
+    country("IQ", "Iraq", 33.2, 43.7, 5.),
+    country("IR", "Iran", 32.4, 53.7, 10.),
+    country("IS", "Iceland", 64.9, -18.6, 3.),
+    country("IT", "Italy", 42.8, 12.6, 6.),
+    country("JP", "Japan", 36.2, 138.3, 11.),
+    country("KE", "Kenya", 0.0, 37.9, 5.),
+    country("KP", "North Korea", 40.3, 127.5, 3.),
+    country("KR", "South Korea", 36.0, 127.8, 2.5),
+    country("KZ", "Kazakhstan", 48.0, 66.9, 14.),
+    country("LY", "Libya", 26.3, 17.2, 10.),
+    country("MA", "Morocco", 31.8, -7.1, 6.),
+    country("MG", "Madagascar", -19.4, 46.7, 7.),
+    country("MN", "Mongolia", 46.9, 103.8, 10.),
+    country("MX", "Mexico", 23.6, -102.6, 14.),
+    country("MY", "Malaysia", 4.1, 109.5, 8.),
+    country("NG", "Nigeria", 9.1, 8.7, 6.),
+    country("NL", "Netherlands", 52.1, 5.3, 1.8),
+    country("NO", "Norway", 64.5, 12.0, 9.),
+    country("NZ", "New Zealand", -41.3, 173.0, 8.),
+    country("PE", "Peru", -9.2, -75.0, 9.),
+    country("PH", "Philippines", 12.9, 121.8, 9.),
+    country("PK", "Pakistan", 30.4, 69.3, 8.),
+    country("PL", "Poland", 51.9, 19.1, 4.),
+    country("PT", "Portugal", 39.6, -8.0, 3.),
+    country("RO", "Romania", 45.9, 25.0, 3.5),
+    country("RU", "Russia", 61.5, 98.0, 35.),
+    country("SA", "Saudi Arabia", 23.9, 45.1, 10.),
+    country("SD", "Sudan", 15.6, 30.2, 9.),
+    country("SE", "Sweden", 62.0, 15.0, 8.),
+    country("TH", "Thailand", 15.9, 101.0, 7.),
+    country("TR", "Turkey", 39.0, 35.2, 7.),
+    country("TW", "Taiwan", 23.7, 121.0, 2.),
+    country("TZ", "Tanzania", -6.4, 34.9, 7.),
+    country("UA", "Ukraine", 48.4, 31.2, 6.),
+    country("US", "United States", 39.8, -98.6, 22.),
+    country("VE", "Venezuela", 6.4, -66.6, 7.),
+    country("VN", "Vietnam", 16.0, 106.0, 9.),
+    country("ZA", "South Africa", -30.6, 22.9, 8.),
+];
+
+/// The country with a code or name, ignoring case. An unknown one is an
+/// error listing the closest known countries.
+pub fn find(s: &str) -> Result<&'static Country, String> {
+    let s = s.trim();
+    let found = COUNTRIES
+        .iter()
+        .find(|c| c.code.eq_ignore_ascii_case(s) || c.name.eq_ignore_ascii_case(s));
+    if let Some(country) = found {
+        return Ok(country);
+    }
+
+    // near matches: a code one letter off, or a name a couple of letters
+    // off or starting with the query
+    let query = s.to_lowercase();
+    let distance = |c: &Country| {
+        let code = crate::edit_distance(&query, &c.code.to_lowercase());
+        let name = crate::edit_distance(&query, &c.name.to_lowercase());
+        match c.name.to_lowercase().starts_with(&query) {
+            true => 0,
+            false if code <= 1 => code,
+            false => name,
+        }
+    };
+    let mut near: Vec<&Country> = COUNTRIES.iter().filter(|c| distance(c) <= 2).collect();
+    near.sort_by_key(|c| distance(c));
+    let names: Vec<String> = near
+        .iter()
+        .take(3)
+        .map(|c| format!("{} ({})", c.code, c.name))
+        .collect();
+    match names.is_empty() {
+        true => Err(format!("unknown country `{}`", s)),
+        false => Err(format!(
+            "unknown country `{}`, did you mean {}?",
+            s,
+            names.join(", ")
+        )),
+    }
+}
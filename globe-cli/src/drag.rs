@@ -0,0 +1,92 @@
+//! Mouse dragging with momentum.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use globe::Float;
+
+/// Drag movements older than this don't count towards the release velocity.
+const SAMPLE_WINDOW: Duration = Duration::from_millis(100);
+/// Rate at which the momentum decays, per second.
+const FRICTION: Float = 3.;
+/// Speed in cells per second below which the globe stops.
+const MIN_SPEED: Float = 2.;
+
+/// Tracks a drag in terminal cells and keeps it going after release.
+#[derive(Default)]
+pub struct Drag {
+    last_pos: Option<(u16, u16)>,
+    /// Recent movements, as time and distance moved.
+    samples: VecDeque<(Instant, Float, Float)>,
+    /// Velocity in cells per second after the last release.
+    velocity: Option<(Float, Float)>,
+    last_step: Option<Instant>,
+}
+
+impl Drag {
+    /// Grabbing the globe stops any momentum.
+    pub fn press(&mut self) {
+        *self = Drag::default();
+    }
+
+    /// Records the pointer moving to `(x, y)`, returning the distance moved
+    /// since the previous position.
+    pub fn moved(&mut self, x: u16, y: u16) -> (Float, Float) {
+        let now = Instant::now();
+        let diff = match self.last_pos {
+            Some((last_x, last_y)) => (x as Float - last_x as Float, y as Float - last_y as Float),
+            None => (0., 0.),
+        };
+        self.last_pos = Some((x, y));
+        self.velocity = None;
+        self.samples.push_back((now, diff.0, diff.1));
+        while let Some(&(time, ..)) = self.samples.front() {
+            if now - time <= SAMPLE_WINDOW {
+                break;
+            }
+            self.samples.pop_front();
+        }
+        diff
+    }
+
+    /// Lets go of the globe, which keeps moving at the speed of the last
+    /// few movements. Holding still before releasing leaves no momentum.
+    pub fn release(&mut self) {
+        let now = Instant::now();
+        let recent = self
+            .samples
+            .iter()
+            .filter(|(time, ..)| now - *time <= SAMPLE_WINDOW);
+        let (dx, dy) = recent.fold((0., 0.), |(x, y), (_, dx, dy)| (x + dx, y + dy));
+        let secs = SAMPLE_WINDOW.as_secs_f32();
+        self.velocity = Some((dx / secs, dy / secs)).filter(|&v| speed(v) >= MIN_SPEED);
+        self.last_pos = None;
+        self.samples.clear();
+        self.last_step = Some(now);
+    }
+
+    /// Distance the globe coasted since the previous step, if it's still
+    /// moving.
+    pub fn step(&mut self) -> Option<(Float, Float)> {
+        let (vx, vy) = self.velocity?;
+        let now = Instant::now();
+        let dt = self.last_step.map_or(0., |last| (now - last).as_secs_f32());
+        self.last_step = Some(now);
+
+        let decay = (-FRICTION * dt).exp();
+        let velocity = (vx * decay, vy * decay);
+        self.velocity = Some(velocity).filter(|&v| speed(v) >= MIN_SPEED);
+        // distance covered while slowing down from v to v * decay
+        let scale = (1. - decay) / FRICTION;
+        Some((vx * scale, vy * scale))
+    }
+
+    /// Stops any momentum.
+    pub fn stop(&mut self) {
+        self.velocity = None;
+    }
+}
+
+fn speed((x, y): (Float, Float)) -> Float {
+    (x * x + y * y).sqrt()
+}
@@ -0,0 +1,147 @@
+//! Point events such as earthquakes, loaded from CSV files.
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use globe::{Event, Float};
+
+use crate::clock;
+
+/// Reads events from a CSV file with a time, latitude, longitude and
+/// magnitude per line. A header row naming the columns (`time`,
+/// `latitude`, `longitude` and `mag` as in USGS exports, or the short forms
+/// `lat`, `lon` and `magnitude`) allows other columns and any order;
+/// without one the columns are expected in that order.
+///
+/// Times are Unix timestamps in seconds or UTC dates like
+/// `2024-01-01T12:30:00Z`, and become relative to the earliest event.
+pub fn load_csv(path: &Path) -> Result<Vec<Event>, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    let mut columns = [0, 1, 2, 3];
+    let mut rows = Vec::new();
+    if let Some((i, first)) = lines.next() {
+        let names = fields(first);
+        match header_columns(&names) {
+            Some(found) => columns = found,
+            None if names
+                .iter()
+                .any(|n| n.parse::<f64>().is_err() && parse_time(n).is_none()) =>
+            {
+                return Err(
+                    "line 1: header needs `time`, `latitude`, `longitude` and `mag` columns"
+                        .to_string(),
+                )
+            }
+            None => rows.push((i, names)),
+        }
+    }
+    rows.extend(lines.map(|(i, line)| (i, fields(line))));
+
+    let mut events = Vec::new();
+    let mut times = Vec::new();
+    for (i, row) in rows {
+        let field = |column: usize| row.get(column).map(|s| s.trim()).unwrap_or("");
+        let time = parse_time(field(columns[0]))
+            .ok_or_else(|| format!("line {}: invalid time `{}`", i + 1, field(columns[0])))?;
+        let number = |column: usize, what: &str| {
+            field(columns[column]).parse::<Float>().map_err(|_| {
+                format!(
+                    "line {}: invalid {} `{}`",
+                    i + 1,
+                    what,
+                    field(columns[column])
+                )
+            })
+        };
+        let lat = number(1, "latitude")?;
+        let lon = number(2, "longitude")?;
+        if lat.abs() > 90. || lon.abs() > 180. {
+            return Err(format!("line {}: position out of range", i + 1));
+        }
+        let magnitude = number(3, "magnitude")?;
+        times.push(time);
+        events.push(Event {
+            time: Duration::default(),
+            lat,
+            lon,
+            magnitude,
+        });
+    }
+
+    let start = times.iter().copied().fold(f64::INFINITY, f64::min);
+    for (event, time) in events.iter_mut().zip(times) {
+        event.time = Duration::from_secs_f64(time - start);
+    }
+    Ok(events)
+}
+
+/// Positions of the time, latitude, longitude and magnitude columns in a
+/// header row, if it is one.
+fn header_columns(names: &[String]) -> Option<[usize; 4]> {
+    let find = |candidates: &[&str]| {
+        names
+            .iter()
+            .position(|n| candidates.contains(&n.trim().to_ascii_lowercase().as_str()))
+    };
+    Some([
+        find(&["time", "timestamp"])?,
+        find(&["latitude", "lat"])?,
+        find(&["longitude", "lon", "lng"])?,
+        find(&["mag", "magnitude"])?,
+    ])
+}
+
+/// Splits a CSV line, allowing double-quoted fields with commas and `""`
+/// escapes in them.
+pub fn fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().unwrap();
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => field.push(c),
+        }
+    }
+    fields
+}
+
+/// Seconds since the Unix epoch of a timestamp, either as a number or as
+/// `YYYY-MM-DD`, optionally followed by `THH:MM:SS` with fractional
+/// seconds and a `Z`.
+fn parse_time(s: &str) -> Option<f64> {
+    if let Ok(secs) = s.parse::<f64>() {
+        return Some(secs);
+    }
+    let s = s.trim_end_matches('Z');
+    let (date, time) = match s.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (s, None),
+    };
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let mut secs = (clock::days_from_civil(year, month, day) * 86400) as f64;
+    if let Some(time) = time {
+        let mut parts = time.splitn(3, ':');
+        let hours: f64 = parts.next()?.parse().ok()?;
+        let minutes: f64 = parts.next()?.parse().ok()?;
+        let seconds: f64 = parts.next().unwrap_or("0").parse().ok()?;
+        secs += hours * 3600. + minutes * 60. + seconds;
+    }
+    Some(secs)
+}
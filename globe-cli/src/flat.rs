@@ -0,0 +1,147 @@
+//! The texture as a flat map, for `--flat`.
+
+use std::io::{stdout, IsTerminal, Write};
+use std::time::Duration;
+
+use crossterm::event::Event;
+use crossterm::{terminal, QueueableCommand};
+use globe::{Canvas, Float, Globe};
+
+use crate::app::{self, Action, Mode};
+use crate::control::Command;
+use crate::term;
+use crate::{canvas_text, draw_frame, Settings};
+
+/// Spacing of the graticule lines in degrees.
+const GRATICULE_STEP: i32 = 30;
+
+/// Canvas holding the largest flat map that fits in a frame of `size`
+/// cells. Maps are four times as many cells wide as tall, which is twice as
+/// wide as tall on screen.
+pub fn canvas_for((width, height): (u16, u16), palette: &[char]) -> Canvas {
+    let width = width.min(height.saturating_mul(4)).max(4);
+    Canvas::new(width * 4, width * 2, None).with_palette(palette)
+}
+
+/// Renders the map with the `--geojson` layer and, with `graticule`,
+/// meridians and parallels.
+pub fn render(settings: &Settings, globe: &Globe, canvas: &mut Canvas, graticule: bool) {
+    canvas.clear();
+    globe.render_flat_on(canvas);
+    settings.apply_effects(canvas);
+    if graticule {
+        draw_graticule(canvas);
+    }
+    settings.layer.draw_with(canvas, globe::project_flat);
+}
+
+fn draw_graticule(canvas: &mut Canvas) {
+    let (width, height) = globe::flat_size(canvas);
+    let cell = |lat: i32, lon: i32| globe::project_flat(canvas, lat as Float, lon as Float);
+    let meridians: Vec<usize> = (-180..180)
+        .step_by(GRATICULE_STEP as usize)
+        .filter_map(|lon| cell(0, lon).map(|(x, _)| x))
+        .collect();
+    let parallels: Vec<usize> = (-90 + GRATICULE_STEP..90)
+        .step_by(GRATICULE_STEP as usize)
+        .filter_map(|lat| cell(lat, 0).map(|(_, y)| y))
+        .collect();
+    for y in 0..height {
+        for x in 0..width {
+            let c = match (meridians.contains(&x), parallels.contains(&y)) {
+                (true, true) => '+',
+                (true, false) => '|',
+                (false, true) => '-',
+                (false, false) => continue,
+            };
+            canvas.draw_point(x, y, c);
+        }
+    }
+}
+
+/// Prints the map to stdout, fitted to the terminal or 80 cells across
+/// without one.
+pub fn print(settings: &Settings, graticule: bool) {
+    let term_size = match stdout().is_terminal() {
+        true => term::size(),
+        false => (80, 20),
+    };
+    let mut canvas = canvas_for(settings.frame_size(term_size), &settings.palette);
+    render(settings, &settings.globe(), &mut canvas, graticule);
+    // stop quietly when piped into something like `head`
+    let _ = writeln!(stdout(), "{}", canvas_text(&canvas));
+}
+
+/// Shows the map in the terminal until a key is pressed.
+pub fn show(settings: &Settings, graticule: bool) {
+    app::run(settings, false, |term_size, _| FlatMap {
+        settings,
+        globe: settings.globe(),
+        graticule,
+        term_size,
+        canvas: canvas_for(settings.frame_size(term_size), &settings.palette),
+        redraw: true,
+        clear: false,
+    });
+}
+
+/// State of the map shown with `--flat`, which only changes with the size
+/// of the terminal.
+struct FlatMap<'a> {
+    settings: &'a Settings,
+    globe: Globe,
+    graticule: bool,
+    term_size: (u16, u16),
+    canvas: Canvas,
+    redraw: bool,
+    /// Whether the terminal needs clearing before the next frame.
+    clear: bool,
+}
+
+impl Mode for FlatMap<'_> {
+    fn handle_event(&mut self, event: Event) -> Action {
+        match event {
+            // any key closes the map, Ctrl+C included
+            Event::Key(_) => return Action::Quit,
+            Event::Resize(width, height) => {
+                self.term_size = (width, height);
+                let size = self.settings.frame_size(self.term_size);
+                self.canvas = canvas_for(size, &self.settings.palette);
+                self.redraw = true;
+                self.clear = true;
+            }
+            Event::Mouse(_) => {}
+        }
+        Action::Continue
+    }
+
+    fn handle_command(&mut self, command: Command) -> Action {
+        match command {
+            Command::Quit => Action::Quit,
+            _ => Action::Continue,
+        }
+    }
+
+    fn update(&mut self, _dt: Duration) {}
+
+    fn draw(&mut self, out: &mut impl Write, _fps: Float) -> Option<&Canvas> {
+        if !std::mem::take(&mut self.redraw) {
+            return None;
+        }
+        if std::mem::take(&mut self.clear) {
+            // nothing overwrites what the old size left behind
+            out.queue(terminal::Clear(terminal::ClearType::All))
+                .unwrap();
+        }
+        render(self.settings, &self.globe, &mut self.canvas, self.graticule);
+        draw_frame(
+            out,
+            self.settings,
+            &self.globe,
+            &mut self.canvas,
+            self.term_size,
+            None,
+        );
+        Some(&self.canvas)
+    }
+}
@@ -0,0 +1,225 @@
+//! Flights along great circles between airports, for `--flight`.
+
+use std::f32::consts::PI;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use crossterm::event::Event;
+use globe::{Camera, Canvas, Float, Globe};
+
+use crate::app::{self, Action, Mode};
+use crate::control::Command;
+use crate::locations::{self, LOCATIONS};
+use crate::markers::Layer;
+use crate::term::{self, Title};
+use crate::{config, format_position, overlay, status_canvas, ExitOn, Settings};
+
+/// Start and end of a flight, with names for the status line.
+pub struct Route {
+    pub from: (Float, Float),
+    pub to: (Float, Float),
+    pub name: String,
+}
+
+impl Route {
+    /// Parses `JFK-LHR` with airport codes, or `LAT,LON:LAT,LON` with
+    /// positions in degrees.
+    pub fn parse(s: &str) -> Result<Route, String> {
+        if let Some((from, to)) = s.split_once(':') {
+            return Ok(Route {
+                from: config::parse_coords(from)?,
+                to: config::parse_coords(to)?,
+                name: format!("{} to {}", from, to),
+            });
+        }
+        let (from, to) = s.split_once('-').ok_or_else(|| {
+            format!(
+                "invalid flight `{}`, expected airports like JFK-LHR or LAT,LON:LAT,LON",
+                s
+            )
+        })?;
+        Ok(Route {
+            from: airport(from)?,
+            to: airport(to)?,
+            name: format!(
+                "{} to {}",
+                from.to_ascii_uppercase(),
+                to.to_ascii_uppercase()
+            ),
+        })
+    }
+}
+
+fn airport(code: &str) -> Result<(Float, Float), String> {
+    locations::by_code(code)
+        .map(|l| (l.lat, l.lon))
+        .ok_or_else(|| {
+            let codes: Vec<&str> = LOCATIONS.iter().map(|l| l.code).collect();
+            format!(
+                "unknown airport `{}`, expected one of: {}",
+                code,
+                codes.join(", ")
+            )
+        })
+}
+
+/// Flies `route` over and over until it's interrupted or a character key
+/// is pressed.
+pub fn start(route: &Route, settings: &Settings) {
+    app::run(settings, false, |term_size, _| {
+        Flight::new(route, settings, term_size)
+    });
+}
+
+/// State of `--flight` between frames.
+pub struct Flight<'a> {
+    settings: &'a Settings,
+    route: &'a Route,
+    globe: Globe,
+    term_size: (u16, u16),
+    canvas: Canvas,
+    /// The `--geojson` layer with the route drawn on it.
+    layer: Layer,
+    cam_zoom: Float,
+    cam_z: Float,
+    speed: Float,
+    distance_km: Float,
+    start: Instant,
+    /// How far along the route the plane is, from 0 to 1, and where that
+    /// is in degrees.
+    done: Float,
+    position: (Float, Float),
+    title: Title,
+    /// Whether the terminal needs clearing before the next frame.
+    clear: bool,
+}
+
+impl<'a> Flight<'a> {
+    pub fn new(route: &'a Route, settings: &'a Settings, term_size: (u16, u16)) -> Flight<'a> {
+        let mut globe = settings.globe();
+        let (angle, cam_z) = settings.initial_view();
+        globe.angle = angle;
+        let mut layer = settings.layer.clone();
+        layer.lines.push(vec![route.from, route.to]);
+        Flight {
+            settings,
+            route,
+            globe,
+            term_size,
+            canvas: status_canvas(term_size, settings),
+            layer,
+            cam_zoom: settings.zoom,
+            cam_z,
+            speed: settings.speed,
+            distance_km: globe::geo::distance_km(route.from, route.to),
+            start: Instant::now(),
+            done: 0.,
+            position: route.from,
+            title: Title::default(),
+            clear: false,
+        }
+    }
+}
+
+impl Mode for Flight<'_> {
+    fn handle_event(&mut self, event: Event) -> Action {
+        match event {
+            Event::Key(event) if term::is_interrupt(&event) => return Action::Quit,
+            _ if ExitOn::Char.exits(&event) => return Action::Quit,
+            Event::Resize(width, height) => {
+                self.term_size = (width, height);
+                self.canvas = status_canvas(self.term_size, self.settings);
+                self.clear = true;
+            }
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    fn handle_command(&mut self, command: Command) -> Action {
+        match command {
+            Command::Focus(lat, lon) => {
+                self.globe.angle = -lon.to_radians();
+                self.cam_z = globe::tilt_for_latitude(lat);
+            }
+            Command::Zoom(zoom) => self.cam_zoom = zoom,
+            Command::Speed(s) => self.speed = s,
+            Command::AddMarker(marker) => self.layer.markers.push(marker),
+            Command::ClearMarkers => self.layer.markers.clear(),
+            Command::Quit => return Action::Quit,
+        }
+        Action::Continue
+    }
+
+    fn update(&mut self, dt: Duration) {
+        let settings = self.settings;
+        let t = self.start.elapsed().as_secs_f32() / settings.flight_time.as_secs_f32();
+        self.done = t.fract();
+        self.position = globe::great_circle(self.route.from, self.route.to, self.done);
+
+        if settings.follow {
+            self.globe.follow(self.position.0, self.position.1);
+        }
+        settings.update_globe(&mut self.globe);
+        if let Some((view_lat, _)) = self.globe.follow.view {
+            self.cam_z = globe::tilt_for_latitude(view_lat);
+        } else {
+            // the plane disappears behind the globe and comes back, turning
+            // as fast as the screensaver does
+            self.globe.angle -= PI / 50. * self.speed * settings.fps * dt.as_secs_f32();
+        }
+    }
+
+    fn draw(&mut self, out: &mut impl Write, _fps: Float) -> Option<&Canvas> {
+        let settings = self.settings;
+        let globe = &mut self.globe;
+        globe.camera = Camera::new(self.cam_zoom, 0., self.cam_z + globe.wobble_offset());
+
+        let canvas = &mut self.canvas;
+        canvas.clear();
+        globe.render_on(canvas);
+        settings.apply_effects(canvas);
+        self.layer.draw(globe, canvas);
+        let (lat, lon) = self.position;
+        if let Some((x, y)) = globe.project(canvas, lat, lon) {
+            canvas.draw_point(x, y, '>');
+        }
+        if settings.progress {
+            draw_progress(globe, canvas, self.done);
+        }
+
+        let status = format!(
+            "{}  {:.0}%  {}  {:.0} km to go",
+            self.route.name,
+            self.done * 100.,
+            format_position(lat, lon),
+            self.distance_km * (1. - self.done),
+        );
+        app::print_frame(
+            out,
+            settings,
+            &mut self.title,
+            &self.globe,
+            self.cam_zoom,
+            &mut self.canvas,
+            None,
+            self.term_size,
+            std::mem::take(&mut self.clear),
+            Some(&status),
+        );
+        Some(&self.canvas)
+    }
+}
+
+/// Draws a ring just outside the globe, filled clockwise from the top for
+/// the `done` fraction of the way, or along the edge of the canvas if the
+/// globe doesn't leave room for it.
+fn draw_progress(globe: &Globe, canvas: &mut Canvas, done: Float) {
+    const TRACK: char = '\u{2802}';
+    const FILLED: char = '\u{28ff}';
+    let (width, height) = overlay::grid_size(canvas);
+    let center = (width as Float / 2. - 0.5, height as Float / 2. - 0.5);
+    let radius = (globe.limb_radius(canvas) + 1.5).min(center.0);
+    canvas.draw_ring_segment(center, radius, done, 1., TRACK);
+    canvas.draw_ring_segment(center, radius, 0., done, FILLED);
+}
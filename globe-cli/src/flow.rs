@@ -0,0 +1,89 @@
+//! Vector fields for `--flow` and `--flow-demo`, drawn as particles
+//! drifting over the ocean.
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use globe::{Float, FlowField};
+
+/// Particles drifting along the field at once.
+pub const PARTICLES: usize = 400;
+
+/// How long each particle lives.
+pub const LIFETIME: Duration = Duration::from_secs(3);
+
+/// Speed in degrees per second the fastest vector of a field is drawn at,
+/// whatever the units of the file. Real winds would barely move.
+const TOP_SPEED: Float = 6.;
+
+/// Idealized trade winds: easterlies towards the equator in the tropics,
+/// westerlies away from it at mid latitudes and easterlies again around
+/// the poles, with a slight wave along the longitudes.
+pub fn demo() -> FlowField {
+    let field = FlowField::from_fn(72, 36, |lat, lon| {
+        let (lat, lon) = (lat.to_radians(), lon.to_radians());
+        let u = -(6. * lat.abs()).sin() * (1. + 0.3 * (2. * lon).sin());
+        let v = -0.4 * (6. * lat).sin() + 0.15 * (3. * lon).cos() * lat.cos();
+        (u, v)
+    });
+    scaled(field)
+}
+
+/// Reads a field from a text file with a row of vectors per line, from
+/// north to south and west to east starting at the antimeridian. Each
+/// vector is an eastward and a northward component separated by a comma,
+/// like `-3.5,1.2`, and vectors are separated by whitespace. All rows need
+/// as many vectors; blank lines and lines starting with `#` are skipped.
+///
+/// Only the directions and relative speeds matter: the field is scaled so
+/// its fastest vector moves a few degrees a second.
+pub fn load(path: &Path) -> Result<FlowField, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut cols = None;
+    let mut rows = 0;
+    let mut vectors = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let row = line
+            .split_whitespace()
+            .map(|vector| parse_vector(vector).map_err(|e| format!("line {}: {}", i + 1, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        match cols {
+            Some(n) if n != row.len() => {
+                return Err(format!(
+                    "line {}: expected {} vectors like the first row, found {}",
+                    i + 1,
+                    n,
+                    row.len()
+                ))
+            }
+            _ => cols = Some(row.len()),
+        }
+        vectors.extend(row);
+        rows += 1;
+    }
+    let cols = cols.ok_or("no vectors")?;
+    Ok(scaled(FlowField::new(cols, rows, vectors)))
+}
+
+fn parse_vector(s: &str) -> Result<(Float, Float), String> {
+    let invalid = || format!("invalid vector `{}`, expected e.g. -3.5,1.2", s);
+    let (u, v) = s.split_once(',').ok_or_else(invalid)?;
+    match (u.parse::<Float>(), v.parse::<Float>()) {
+        (Ok(u), Ok(v)) if u.is_finite() && v.is_finite() => Ok((u, v)),
+        _ => Err(invalid()),
+    }
+}
+
+/// `field` with its fastest vector at `TOP_SPEED`.
+fn scaled(field: FlowField) -> FlowField {
+    let top = field.max_speed();
+    if top == 0. {
+        return field;
+    }
+    field.scaled(TOP_SPEED / top)
+}
@@ -0,0 +1,285 @@
+//! Drawing the globe as an image through terminal graphics protocols.
+
+use std::convert::TryFrom;
+use std::fmt::Write;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use crossterm::terminal;
+
+use globe::{Canvas, Globe, PALETTE};
+
+use crate::term;
+
+/// How frames are sent to the terminal.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Output {
+    /// Characters, the default.
+    Text,
+    /// Sixel images, with the size of a terminal cell in pixels.
+    Sixel { cell: (u16, u16) },
+    /// Images sent through the kitty graphics protocol.
+    Kitty { cell: (u16, u16) },
+}
+
+/// Grayscale image of the globe.
+pub struct Raster {
+    pub width: usize,
+    pub height: usize,
+    /// Brightness of every pixel from 0 to 255, row by row.
+    pub pixels: Vec<u8>,
+}
+
+impl Raster {
+    /// Renders the globe with its current camera, one ray per pixel, or
+    /// `None` if there are no pixels or too many for a canvas.
+    pub fn render(globe: &Globe, width: usize, height: usize) -> Option<Raster> {
+        let (x, y) = (u16::try_from(width).ok()?, u16::try_from(height).ok()?);
+        let mut canvas = Canvas::try_new(x, y, Some((1, 1)))?;
+        globe.render_on(&mut canvas);
+        let pixels = canvas.matrix[..height]
+            .iter()
+            .flat_map(|row| row[..width].iter())
+            .map(|&c| shade(c))
+            .collect();
+        Some(Raster {
+            width,
+            height,
+            pixels,
+        })
+    }
+}
+
+/// Brightness of a shading character.
+fn shade(c: char) -> u8 {
+    let index = PALETTE.iter().position(|&p| p == c).unwrap_or(0);
+    (index * 255 / (PALETTE.len() - 1)) as u8
+}
+
+/// Number of gray levels in sixel images.
+const SIXEL_LEVELS: usize = 16;
+
+/// Whether the terminal lists sixel graphics in its device attributes.
+pub fn sixel_supported() -> bool {
+    if terminal::enable_raw_mode().is_err() {
+        return false;
+    }
+    // primary device attributes, answered with `ESC [ ? 62 ; 4 ; ... c`
+    let reply = term::query("\x1b[c", Duration::from_millis(500), |r| {
+        r.starts_with(b"\x1b[?") && r.ends_with(b"c")
+    });
+    let _ = terminal::disable_raw_mode();
+
+    match reply {
+        Some(reply) => {
+            let reply = String::from_utf8_lossy(&reply[3..reply.len() - 1]).into_owned();
+            reply.split(';').any(|attr| attr == "4")
+        }
+        None => false,
+    }
+}
+
+/// Whether the terminal understands the kitty graphics protocol.
+pub fn kitty_supported() -> bool {
+    if terminal::enable_raw_mode().is_err() {
+        return false;
+    }
+    // terminals without support ignore the query but still answer the
+    // device attributes request sent right after it
+    let request = format!(
+        "\x1b_Gi={},s=1,v=1,a=q,t=d,f=24;AAAA\x1b\\\x1b[c",
+        KITTY_QUERY_ID
+    );
+    let reply = term::query(&request, Duration::from_millis(500), |r| {
+        r.ends_with(b"c") && r.windows(3).any(|w| w == b"\x1b[?")
+    });
+    let _ = terminal::disable_raw_mode();
+
+    let ok = format!("\x1b_Gi={};OK", KITTY_QUERY_ID);
+    reply.is_some_and(|r| String::from_utf8_lossy(&r).contains(&ok))
+}
+
+/// Image id of the support query, which is never displayed.
+const KITTY_QUERY_ID: u32 = 31;
+/// Image id of the frames, replaced by every new frame.
+const KITTY_IMAGE_ID: u32 = 1;
+/// Largest payload in a single escape sequence allowed by the protocol.
+const KITTY_CHUNK: usize = 4096;
+
+/// Encodes the raster as a kitty graphics command showing it at the cursor,
+/// replacing the previous frame.
+pub fn kitty(raster: &Raster) -> String {
+    let rgb: Vec<u8> = raster.pixels.iter().flat_map(|&p| [p, p, p]).collect();
+    let data = base64(&rgb);
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK).collect();
+
+    let mut out = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = (i + 1 < chunks.len()) as u8;
+        out.push_str("\x1b_G");
+        if i == 0 {
+            // same image and placement ids, so the previous frame is replaced
+            // in place; C=1 leaves the cursor where it was
+            write!(
+                out,
+                "a=T,f=24,s={},v={},i={},p=1,q=2,C=1,",
+                raster.width, raster.height, KITTY_IMAGE_ID
+            )
+            .unwrap();
+        }
+        write!(out, "m={};", more).unwrap();
+        out.push_str(std::str::from_utf8(chunk).unwrap());
+        out.push_str("\x1b\\");
+    }
+    term::KITTY_IMAGE.store(true, Ordering::SeqCst);
+    out
+}
+
+/// Kitty graphics command deleting the frames.
+pub fn kitty_delete() -> String {
+    format!("\x1b_Ga=d,d=I,i={},q=2\x1b\\", KITTY_IMAGE_ID)
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Encodes the raster as a grayscale PNG file. The image data is stored
+/// without compression, which keeps the encoder small.
+pub fn png(raster: &Raster) -> Vec<u8> {
+    // every row starts with its filter type, none
+    let mut data = Vec::with_capacity((raster.width + 1) * raster.height);
+    for row in raster.pixels.chunks(raster.width.max(1)) {
+        data.push(0);
+        data.extend_from_slice(row);
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(raster.width as u32).to_be_bytes());
+    header.extend_from_slice(&(raster.height as u32).to_be_bytes());
+    // 8 bits per pixel of gray, default compression, filtering and no
+    // interlacing
+    header.extend_from_slice(&[8, 0, 0, 0, 0]);
+
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+    push_chunk(&mut out, b"IHDR", &header);
+    push_chunk(&mut out, b"IDAT", &zlib_stored(&data));
+    push_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+/// Appends a PNG chunk, with its length and checksum.
+fn push_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wraps data in a zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const BLOCK: usize = 0xffff;
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        out.push(blocks.peek().is_none() as u8);
+        let len = block.len() as u16;
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    // Adler-32 of the uncompressed data
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    out.extend_from_slice(&(b << 16 | a).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+/// Encodes the raster as a sixel image.
+pub fn sixel(raster: &Raster) -> String {
+    // `0;0` keeps the pixel aspect ratio at 1:1 and paints the background
+    let mut out = format!("\x1bP0;0;0q\"1;1;{};{}", raster.width, raster.height);
+    for level in 0..SIXEL_LEVELS {
+        let percent = level * 100 / (SIXEL_LEVELS - 1);
+        write!(out, "#{};2;{};{};{}", level, percent, percent, percent).unwrap();
+    }
+
+    let level =
+        |x: usize, y: usize| raster.pixels[y * raster.width + x] as usize * SIXEL_LEVELS / 256;
+    let mut band = vec![0u8; raster.width];
+    for top in (0..raster.height).step_by(6) {
+        let rows = (raster.height - top).min(6);
+        for color in 0..SIXEL_LEVELS {
+            let mut used = false;
+            for (x, bits) in band.iter_mut().enumerate() {
+                *bits = (0..rows)
+                    .filter(|&dy| level(x, top + dy) == color)
+                    .fold(0, |bits, dy| bits | 1 << dy);
+                used |= *bits != 0;
+            }
+            if !used {
+                continue;
+            }
+            write!(out, "#{}", color).unwrap();
+            push_runs(&mut out, &band);
+            // go back to the start of the band for the next color
+            out.push('$');
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+/// Appends one color of a sixel band, run-length encoded.
+fn push_runs(out: &mut String, band: &[u8]) {
+    // nothing needs to be drawn after the last pixel of this color
+    let band = &band[..band.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1)];
+    let mut i = 0;
+    while i < band.len() {
+        let run = band[i..].iter().take_while(|&&b| b == band[i]).count();
+        let c = (b'?' + band[i]) as char;
+        if run > 3 {
+            write!(out, "!{}{}", run, c).unwrap();
+        } else {
+            out.extend(std::iter::repeat_n(c, run));
+        }
+        i += run;
+    }
+}
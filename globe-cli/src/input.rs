@@ -0,0 +1,94 @@
+//! Terminal input read on its own thread, so a slow frame never holds up
+//! keys and waiting for keys never holds up a frame.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crossterm::event::{poll, read, Event};
+
+use crate::term;
+
+/// Longest the thread waits for input before checking whether to stop.
+const STOP_CHECK: Duration = Duration::from_millis(50);
+
+/// Forwards key, mouse and resize events as soon as they arrive, with the
+/// sizes of resizes checked by `term::checked_resize`. Ctrl+C is recorded
+/// with `term::interrupt` before it's forwarded. Stops when dropped,
+/// which has to happen before the terminal is restored so nothing reads it
+/// afterwards.
+pub struct InputThread {
+    events: Receiver<Event>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl InputThread {
+    pub fn spawn() -> InputThread {
+        let (sender, events) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                while !stop.load(Ordering::SeqCst) {
+                    // stops on errors instead of panicking, which would
+                    // restore the terminal under the frame loop
+                    match poll(STOP_CHECK) {
+                        Ok(false) => continue,
+                        Ok(true) => {}
+                        Err(_) => break,
+                    }
+                    let event = match read() {
+                        Ok(event) => term::checked_resize(event),
+                        Err(_) => break,
+                    };
+                    if let Event::Key(key) = &event {
+                        if term::is_interrupt(key) {
+                            term::interrupt();
+                        }
+                    }
+                    if sender.send(event).is_err() {
+                        break;
+                    }
+                }
+            })
+        };
+        InputThread {
+            events,
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    /// Waits up to `timeout` for input, and returns the first event along
+    /// with everything queued up behind it.
+    pub fn wait(&self, timeout: Duration) -> Vec<Event> {
+        let mut events: Vec<Event> = self.next(timeout).into_iter().collect();
+        events.extend(self.events.try_iter());
+        events
+    }
+
+    /// The next event within `timeout`, if one arrives. If the terminal
+    /// can't be read the timeout is waited out, so frames keep their pace.
+    pub fn next(&self, timeout: Duration) -> Option<Event> {
+        match self.events.recv_timeout(timeout) {
+            Ok(event) => Some(event),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => {
+                thread::sleep(timeout);
+                None
+            }
+        }
+    }
+}
+
+impl Drop for InputThread {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
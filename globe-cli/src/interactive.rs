@@ -0,0 +1,960 @@
+//! Interactive mode, where the view follows the keyboard and mouse.
+
+use std::f32::consts::PI;
+use std::io::Write;
+use std::time::{Duration, Instant, SystemTime};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, MouseEvent};
+use globe::{angles, Bounds, Camera, Canvas, Continent, Float, Globe, Presets, MAX_TILT};
+
+use crate::anaglyph::Anaglyph;
+use crate::animation::{Flight, Tween};
+use crate::app::{self, Mode};
+use crate::bookmarks::{self, Bookmarks};
+use crate::budget::Budget;
+use crate::control::Command;
+use crate::drag::Drag;
+use crate::keys::Action;
+use crate::markers::{self, Layer};
+use crate::session::Session;
+use crate::term::{self, Title};
+use crate::tour::Tour;
+use crate::{
+    canvas_for, compare, format_position, frame_origin_of, overlay, save_screenshot, Arrows,
+    Settings,
+};
+
+/// Runs interactive mode until it's quit or interrupted, saving the view
+/// it was left at with `--resume`.
+pub fn start(settings: &Settings) {
+    let app = app::run(settings, settings.mouse, |term_size, mouse| {
+        Interactive::new(settings, term_size, mouse)
+    });
+    if settings.resume {
+        let session = Session {
+            view: current_view(&app.globe, app.cam_z, app.cam_zoom),
+            template: settings.template.name().to_string(),
+        };
+        if let Err(e) = session.save() {
+            eprintln!("error: saving the session failed: {}", e);
+        }
+    }
+}
+
+/// How long a tour takes to fly from one stop to the next.
+const TOUR_FLIGHT: Duration = Duration::from_secs(3);
+
+/// Radius in degrees of the circle painted by a click in paint mode.
+const PAINT_RADIUS: Float = 2.;
+
+/// A key turning or tilting the view that's being held down. Terminals
+/// don't report keys being released, so it counts as released once its
+/// repeats stop coming.
+struct Held {
+    action: Action,
+    last: Instant,
+    /// Whether the key repeated yet, after the keyboard's repeat delay.
+    repeating: bool,
+}
+
+impl Held {
+    /// Longest wait for the first repeat.
+    const FIRST_REPEAT: Duration = Duration::from_millis(600);
+    /// Longest gap between repeats.
+    const REPEAT_GAP: Duration = Duration::from_millis(150);
+
+    fn released(&self) -> bool {
+        let timeout = match self.repeating {
+            true => Held::REPEAT_GAP,
+            false => Held::FIRST_REPEAT,
+        };
+        self.last.elapsed() > timeout
+    }
+}
+
+/// State of interactive mode between frames.
+pub struct Interactive<'a> {
+    settings: &'a Settings,
+    globe: Globe,
+    /// With `--dual`, a second globe lit by the sun shown on the right, in
+    /// the same view as the first.
+    lit: Option<Globe>,
+    term_size: (u16, u16),
+    canvas: Canvas,
+    lit_canvas: Canvas,
+    /// Both panes of `--dual` side by side.
+    dual_frame: Option<Canvas>,
+    /// With `--anaglyph`, the renders for each eye.
+    anaglyph: Option<Anaglyph>,
+    cam_zoom: Float,
+    cam_z: Float,
+    /// Whether mouse events are reported, for the help.
+    mouse: bool,
+    title: Title,
+    layer: Layer,
+    drag: Drag,
+    zoom: Zoom,
+    show_help: bool,
+    paused: bool,
+    message: Option<(String, Instant)>,
+    bookmarks: Bookmarks,
+    /// Whether the next number key saves a bookmark.
+    saving_bookmark: bool,
+    flight: Option<Flight>,
+    last_input: Instant,
+    /// (angle, tilt) of the last antipode jump's start and end, so jumping
+    /// again from where it ended goes back exactly.
+    antipode: Option<((Float, Float), (Float, Float))>,
+    /// Points clicked so far in measure mode, in degrees.
+    measure: Option<Vec<(Float, Float)>>,
+    /// Whether clicks paint on the globe.
+    painting: bool,
+    /// Key turning the view smoothly while it's held, with `--arrows hold`.
+    held: Option<Held>,
+    /// With `--tour`, the stops still to fly to until a key is pressed.
+    tour: Option<Tour>,
+    /// With `--lock-region`, the region the view center stays in until
+    /// unlocked.
+    region: Option<Bounds>,
+    /// With `--adaptive-quality`, the quality frames are rendered at.
+    budget: Option<Budget>,
+    redraw: bool,
+    /// Whether the terminal needs clearing before the next frame.
+    clear: bool,
+}
+
+impl<'a> Interactive<'a> {
+    /// Starts at the initial view, in a terminal of `term_size` cells,
+    /// with mouse events reported if `mouse`.
+    pub fn new(settings: &'a Settings, term_size: (u16, u16), mouse: bool) -> Interactive<'a> {
+        let mut globe = settings.globe();
+        let (angle, cam_z) = settings.initial_view();
+        globe.angle = angle;
+        let (bookmarks, error) = Bookmarks::load();
+        let mut app = Interactive {
+            settings,
+            globe,
+            lit: settings.dual.then(|| settings.globe()),
+            term_size,
+            canvas: Canvas::new(4, 8, None),
+            lit_canvas: Canvas::new(4, 8, None),
+            dual_frame: None,
+            anaglyph: None,
+            cam_zoom: settings.zoom,
+            cam_z,
+            mouse,
+            title: Title::default(),
+            layer: settings.layer.clone(),
+            drag: Drag::default(),
+            zoom: Zoom::default(),
+            show_help: false,
+            paused: false,
+            message: error.map(|e| (e, Instant::now())),
+            bookmarks,
+            saving_bookmark: false,
+            flight: None,
+            last_input: Instant::now(),
+            antipode: None,
+            measure: None,
+            painting: false,
+            held: None,
+            tour: settings.tour.clone().map(Tour::new),
+            region: settings.lock_region,
+            budget: settings.target_fps.map(Budget::new),
+            redraw: true,
+            clear: false,
+        };
+        app.resize(term_size);
+        app.keep_in_region();
+        app
+    }
+
+    /// Sizes the canvases for a terminal of `term_size` cells.
+    fn resize(&mut self, term_size: (u16, u16)) {
+        let settings = self.settings;
+        let pane = match settings.dual {
+            true => compare::pane_size(settings.frame_size(term_size)),
+            false => settings.frame_size(term_size),
+        };
+        self.term_size = term_size;
+        self.canvas = canvas_for(pane, settings);
+        self.lit_canvas = canvas_for(pane, settings);
+        if settings.anaglyph {
+            self.anaglyph = Some(Anaglyph::new(pane, settings));
+        }
+    }
+
+    /// Moves the view center back into the locked region if it left it.
+    fn keep_in_region(&mut self) {
+        let region = match self.region {
+            Some(region) => region,
+            None => return,
+        };
+        let (lat, lon) = view_position((self.globe.angle, self.cam_z));
+        let (lat, clamped) = region.clamp((lat, lon));
+        self.globe.angle -= (clamped - lon).to_radians();
+        self.cam_z = globe::tilt_for_latitude(lat);
+    }
+
+    /// Zooms by `delta` keeping `anchor` in place, within the zoom bounds
+    /// of the canvas, and says so when one of them stops it.
+    fn zoom_by(&mut self, delta: Float, anchor: Option<((usize, usize), (Float, Float))>) {
+        let bounds = Camera::zoom_bounds(&self.canvas, &self.globe.texture);
+        let bounds = (bounds.0 * self.globe.radius, bounds.1 * self.globe.radius);
+        if !self.zoom.by(self.cam_zoom, delta, anchor, bounds) {
+            let hint = match delta < 0. {
+                true => "max zoom",
+                false => "min zoom",
+            };
+            self.show_message(hint.to_string());
+        }
+    }
+
+    fn show_message(&mut self, text: String) {
+        self.message = Some((text, Instant::now()));
+    }
+
+    fn handle_key(&mut self, event: KeyEvent) -> app::Action {
+        let settings = self.settings;
+        let action = settings.bindings.action(event);
+        let number = match event.code {
+            KeyCode::Char(c @ '1'..='9') => Some(c as usize - '0' as usize),
+            _ => None,
+        };
+        // any key ends a tour where it is
+        if self.tour.take().is_some() {
+            self.flight = None;
+        }
+        // taking over the view ends a bookmark flight
+        if !matches!(action, Some(Action::Help) | Some(Action::Screenshot)) {
+            self.flight = None;
+        }
+        // the first press moves a step, repeats then turn the view smoothly
+        // in `update` until they stop
+        let turning = matches!(
+            action,
+            Some(Action::RotateLeft)
+                | Some(Action::RotateRight)
+                | Some(Action::TiltUp)
+                | Some(Action::TiltDown)
+        ) || action.and_then(Action::diagonal).is_some();
+        match (&mut self.held, action) {
+            (Some(held), Some(action)) if held.action == action && !held.released() => {
+                held.last = Instant::now();
+                held.repeating = true;
+                return app::Action::Continue;
+            }
+            _ if turning && settings.arrows == Arrows::Hold && !self.paused => {
+                self.held = action.map(|action| Held {
+                    action,
+                    last: Instant::now(),
+                    repeating: false,
+                });
+            }
+            _ => self.held = None,
+        }
+        match action {
+            _ if self.saving_bookmark => {
+                self.saving_bookmark = false;
+                let text = match number {
+                    Some(n) => {
+                        let view = current_view(&self.globe, self.cam_z, self.cam_zoom);
+                        match self.bookmarks.set(n, view) {
+                            Ok(()) => format!("saved bookmark {}", n),
+                            Err(e) => format!("saving bookmark failed: {}", e),
+                        }
+                    }
+                    None => "no bookmark saved".to_string(),
+                };
+                self.show_message(text);
+            }
+            Some(Action::Quit) => return app::Action::Quit,
+            Some(Action::Help) => self.show_help = !self.show_help,
+            Some(Action::Pause) => {
+                self.paused = !self.paused;
+                self.drag.stop();
+            }
+            Some(Action::Screenshot) => {
+                self.canvas.clear();
+                self.globe.render_on(&mut self.canvas);
+                settings.apply_effects(&mut self.canvas);
+                self.layer.draw(&self.globe, &mut self.canvas);
+                let text = match save_screenshot(&self.canvas) {
+                    Ok(path) => format!("saved {}", path.display()),
+                    Err(e) => format!("screenshot failed: {}", e),
+                };
+                self.show_message(text);
+            }
+            Some(Action::Bookmark) => {
+                self.saving_bookmark = true;
+                self.show_message("press 1-9 to save the view".to_string());
+            }
+            Some(Action::Measure) => {
+                self.measure = match self.measure {
+                    Some(_) => None,
+                    None => Some(Vec::new()),
+                };
+                self.painting = false;
+                self.drag.stop();
+            }
+            Some(Action::Paint) => {
+                self.painting = !self.painting;
+                self.measure = None;
+                self.drag.stop();
+                let text = match self.painting {
+                    true => "paint: click or drag on the globe",
+                    false => "stopped painting",
+                };
+                self.show_message(text.to_string());
+            }
+            Some(Action::Unlock) => {
+                let text = match self.region.take() {
+                    Some(_) => "unlocked the region",
+                    None => "no region to unlock",
+                };
+                self.show_message(text.to_string());
+            }
+            _ if self.paused => (),
+            Some(Action::Antipode) => self.jump_to_antipode(),
+            Some(Action::Face(continent)) => self.face(continent),
+            Some(Action::ZoomOut) => self.zoom_by(0.1, None),
+            Some(Action::ZoomIn) => self.zoom_by(-0.1, None),
+            Some(Action::TiltUp) if self.cam_z < MAX_TILT => self.cam_z += 0.1,
+            Some(Action::TiltDown) if self.cam_z > -MAX_TILT => self.cam_z -= 0.1,
+            Some(Action::RotateLeft) => self.globe.angle += PI / 30.,
+            Some(Action::RotateRight) => self.globe.angle -= PI / 30.,
+            Some(action) if action.diagonal().is_some() => {
+                let (turn, tilt) = action.diagonal().unwrap();
+                self.globe.angle += turn * PI / 30.;
+                self.cam_z = (self.cam_z + tilt * 0.1).clamp(-MAX_TILT, MAX_TILT);
+            }
+            Some(Action::Reset) => {
+                self.zoom = Zoom::default();
+                self.cam_zoom = settings.zoom;
+                let (angle, z) = settings.initial_view();
+                self.globe.angle = angle;
+                self.cam_z = z;
+            }
+            None if number.is_some() => {
+                if let Some(view) = number.and_then(|n| self.bookmarks.get(n)) {
+                    self.zoom = Zoom::default();
+                    self.drag.stop();
+                    let to = (
+                        -view.lon.to_radians(),
+                        globe::tilt_for_latitude(view.lat),
+                        view.zoom.max(1.),
+                    );
+                    let from = (self.globe.angle, self.cam_z, self.cam_zoom);
+                    self.flight = Some(Flight::new(from, to, Duration::from_millis(600)));
+                }
+            }
+            None if event.code == KeyCode::Enter => {
+                // focus on point
+                let (cx, cy) = (0., 0.);
+
+                self.cam_z = (cy * 2. - 1.) * MAX_TILT;
+                self.globe.angle = cx * angles::TAU + PI;
+            }
+            _ => (),
+        }
+        self.redraw = true;
+        app::Action::Continue
+    }
+
+    /// Flies to the point on the other side of the globe, or back from it.
+    fn jump_to_antipode(&mut self) {
+        self.zoom = Zoom::default();
+        self.drag.stop();
+        let here = (self.globe.angle, self.cam_z);
+        let (from, to) = match self.antipode {
+            Some((start, end)) if same_view(here, end) => (end, start),
+            _ => (here, (here.0 + PI, -here.1)),
+        };
+        self.antipode = Some((from, to));
+        let (from_lat, from_lon) = view_position(from);
+        let (to_lat, to_lon) = view_position(to);
+        self.show_message(format!(
+            "antipode of {} \u{2192} {}",
+            format_position(from_lat, from_lon),
+            format_position(to_lat, to_lon)
+        ));
+        self.flight = Some(Flight::new(
+            (here.0, here.1, self.cam_zoom),
+            (to.0, to.1, self.cam_zoom),
+            Duration::from_millis(600),
+        ));
+    }
+
+    /// Flies to the view of a continent from `globe::Presets`.
+    fn face(&mut self, continent: Continent) {
+        self.zoom = Zoom::default();
+        self.drag.stop();
+        let (lat, lon, zoom) = Presets::continent(continent);
+        let (near, far) = Camera::zoom_bounds(&self.canvas, &self.globe.texture);
+        let r = self.globe.radius;
+        let zoom = (zoom * r).clamp(near * r, far * r);
+        let to = (-lon.to_radians(), globe::tilt_for_latitude(lat), zoom);
+        let from = (self.globe.angle, self.cam_z, self.cam_zoom);
+        self.show_message(continent.name().to_string());
+        self.flight = Some(Flight::new(from, to, Duration::from_millis(600)));
+    }
+
+    fn handle_mouse(&mut self, event: MouseEvent) {
+        let settings = self.settings;
+        match event {
+            MouseEvent::Down(_, x, y, _) if self.measure.is_some() => {
+                let cell = pointer_cell(settings, &self.canvas, self.term_size, (x, y));
+                let pos = cell.and_then(|(x, y)| self.globe.unproject(&self.canvas, x, y));
+                match (pos, &mut self.measure) {
+                    (Some(pos), Some(points)) => {
+                        // a third click starts over
+                        if points.len() == 2 {
+                            points.clear();
+                        }
+                        points.push(pos);
+                    }
+                    // clicking next to the globe leaves measure mode
+                    _ => self.measure = None,
+                }
+            }
+            MouseEvent::Down(_, x, y, _) | MouseEvent::Drag(_, x, y, _) if self.painting => {
+                let cell = pointer_cell(settings, &self.canvas, self.term_size, (x, y));
+                if let Some((lat, lon)) =
+                    cell.and_then(|(x, y)| self.globe.unproject(&self.canvas, x, y))
+                {
+                    let c = *globe::PALETTE.last().unwrap();
+                    self.globe.paint(lat, lon, PAINT_RADIUS, c);
+                    if let Some(lit) = &mut self.lit {
+                        lit.paint(lat, lon, PAINT_RADIUS, c);
+                    }
+                }
+            }
+            MouseEvent::Down(..) => {
+                self.flight = None;
+                self.drag.press();
+            }
+            MouseEvent::Drag(_, x, y, _) => {
+                let diff = self.drag.moved(x, y);
+                drag_view(
+                    settings,
+                    &mut self.globe,
+                    &self.canvas,
+                    &mut self.cam_z,
+                    diff,
+                );
+            }
+            MouseEvent::Up(..) => self.drag.release(),
+            MouseEvent::ScrollUp(x, y, _) | MouseEvent::ScrollDown(x, y, _) => {
+                let delta = match event {
+                    MouseEvent::ScrollUp(..) => -0.1,
+                    _ => 0.1,
+                };
+                // keep the point under the pointer in place
+                let anchor =
+                    pointer_cell(settings, &self.canvas, self.term_size, (x, y)).and_then(|cell| {
+                        self.globe
+                            .unproject(&self.canvas, cell.0, cell.1)
+                            .map(|pos| (cell, pos))
+                    });
+                self.flight = None;
+                self.zoom_by(delta, anchor);
+            }
+        }
+    }
+
+    fn camera(&self, globe: &Globe) -> Camera {
+        Camera::new(self.cam_zoom, 0., self.cam_z + globe.wobble_offset())
+    }
+}
+
+impl Mode for Interactive<'_> {
+    fn handle_event(&mut self, event: Event) -> app::Action {
+        if !matches!(event, Event::Resize(..)) {
+            self.last_input = Instant::now();
+        }
+        match event {
+            Event::Key(event) if term::is_interrupt(&event) => return app::Action::Quit,
+            Event::Key(event) => return self.handle_key(event),
+            Event::Mouse(_) if self.paused => (),
+            Event::Mouse(event) => {
+                self.held = None;
+                self.tour = None;
+                self.handle_mouse(event);
+            }
+            Event::Resize(width, height) => {
+                self.resize((width, height));
+                self.clear = true;
+                self.redraw = true;
+            }
+        }
+        app::Action::Continue
+    }
+
+    fn handle_command(&mut self, command: Command) -> app::Action {
+        match command {
+            Command::Focus(lat, lon) => {
+                self.drag.stop();
+                self.globe.angle = -lon.to_radians();
+                self.cam_z = globe::tilt_for_latitude(lat);
+            }
+            Command::Zoom(value) => {
+                self.zoom = Zoom::default();
+                self.cam_zoom = value;
+            }
+            // there's no rotation to speed up
+            Command::Speed(_) => (),
+            Command::AddMarker(marker) => self.layer.markers.push(marker),
+            Command::ClearMarkers => self.layer.markers.clear(),
+            Command::Quit => return app::Action::Quit,
+        }
+        self.last_input = Instant::now();
+        self.redraw = true;
+        app::Action::Continue
+    }
+
+    fn update(&mut self, dt: Duration) {
+        let settings = self.settings;
+        self.redraw |= !self.paused;
+
+        // a tour waits while paused, like it does for its flights
+        let waiting = self.flight.is_some() || self.paused;
+        let stop = self.tour.as_mut().and_then(|tour| tour.update(dt, waiting));
+        if let Some(stop) = stop {
+            let from = (self.globe.angle, self.cam_z, self.cam_zoom);
+            let to = (
+                -stop.lon.to_radians(),
+                globe::tilt_for_latitude(stop.lat),
+                self.cam_zoom,
+            );
+            self.flight = Some(Flight::new(from, to, TOUR_FLIGHT));
+        }
+
+        if let Some(f) = &self.flight {
+            let (angle, tilt, distance) = f.value();
+            self.globe.angle = angle;
+            self.cam_z = tilt;
+            self.cam_zoom = distance;
+            if f.done() {
+                self.flight = None;
+                // land exactly where an antipode jump was headed
+                let landed = self
+                    .antipode
+                    .filter(|(_, end)| same_view((angle, tilt), *end));
+                if let Some((_, end)) = landed {
+                    self.globe.angle = end.0;
+                    self.cam_z = end.1;
+                }
+            }
+            self.redraw = true;
+        }
+
+        if let Some(value) = self.zoom.step() {
+            self.cam_zoom = value;
+            if let Some(((x, y), (lat, lon))) = self.zoom.anchor {
+                // nudge the view until the anchor is back under the pointer
+                for _ in 0..2 {
+                    self.globe.camera = self.camera(&self.globe);
+                    if let Some((lat_now, lon_now)) = self.globe.unproject(&self.canvas, x, y) {
+                        let lon_diff = (lon_now - lon + 180.).rem_euclid(360.) - 180.;
+                        self.globe.angle += lon_diff.to_radians();
+                        self.cam_z =
+                            (self.cam_z + (lat - lat_now).to_radians()).clamp(-MAX_TILT, MAX_TILT);
+                    }
+                }
+            }
+            self.redraw = true;
+        }
+
+        if self.held.as_ref().is_some_and(Held::released) {
+            self.held = None;
+        }
+        if let Some(held) = self.held.as_ref().filter(|held| held.repeating) {
+            let turn = settings.arrow_speed.to_radians() * dt.as_secs_f32();
+            match held.action {
+                Action::RotateLeft => self.globe.angle += turn,
+                Action::RotateRight => self.globe.angle -= turn,
+                Action::TiltUp => self.cam_z = (self.cam_z + turn).min(MAX_TILT),
+                Action::TiltDown => self.cam_z = (self.cam_z - turn).max(-MAX_TILT),
+                action => {
+                    if let Some((x, y)) = action.diagonal() {
+                        self.globe.angle += x * turn;
+                        self.cam_z = (self.cam_z + y * turn).clamp(-MAX_TILT, MAX_TILT);
+                    }
+                }
+            }
+        }
+
+        // keep spinning after a flick
+        if let Some(diff) = self.drag.step() {
+            drag_view(
+                settings,
+                &mut self.globe,
+                &self.canvas,
+                &mut self.cam_z,
+                diff,
+            );
+            self.redraw = true;
+        }
+
+        // spin like the screensaver until someone takes over, carrying on
+        // from wherever the spin left the view
+        let idle = settings
+            .idle_spin
+            .is_some_and(|after| self.last_input.elapsed() >= after);
+        if idle && !self.paused && self.flight.is_none() && self.tour.is_none() {
+            self.globe.angle -= PI / 50. * settings.speed * settings.fps * dt.as_secs_f32();
+        }
+
+        // drags, keys and spinning all stop at the edge of the region
+        self.keep_in_region();
+
+        if let Some((_, shown)) = &self.message {
+            if shown.elapsed() > Duration::from_secs(2) {
+                self.message = None;
+                self.redraw = true;
+            }
+        }
+    }
+
+    fn draw(&mut self, out: &mut impl Write, fps: Float) -> Option<&Canvas> {
+        // keep the frame still while paused so it can be selected and copied
+        if !self.redraw {
+            return None;
+        }
+        self.redraw = false;
+        let started = Instant::now();
+        let settings = self.settings;
+        if let Some(budget) = &self.budget {
+            self.globe.quality = budget.quality();
+            if let Some(lit) = &mut self.lit {
+                lit.quality = budget.quality();
+            }
+        }
+
+        // clip camera zoom
+        if self.cam_zoom < 1.0 {
+            self.cam_zoom = 1.0;
+        }
+        self.globe.camera = self.camera(&self.globe);
+        settings.update_globe(&mut self.globe);
+
+        let canvas = &mut self.canvas;
+        canvas.clear();
+        let mut render_stats = None;
+        match &mut self.anaglyph {
+            Some(anaglyph) => anaglyph.render(&mut self.globe, canvas),
+            None if settings.stats => {
+                render_stats = Some(self.globe.render_on_with_stats(canvas));
+            }
+            None => self.globe.render_on(canvas),
+        }
+        settings.apply_effects(canvas);
+        let globe = &self.globe;
+        self.layer.draw(globe, canvas);
+        if let Some((_, end)) = self.antipode {
+            let (lat, lon) = view_position(end);
+            if let Some((x, y)) = globe.project(canvas, lat, lon) {
+                canvas.draw_point(x, y, 'x');
+            }
+        }
+        if let Some(tour) = &self.tour {
+            let stop = tour.stop();
+            let mut layer = Layer::default();
+            layer.markers.push(markers::Marker {
+                lat: stop.lat,
+                lon: stop.lon,
+                label: Some(stop.name.clone()),
+                c: '*',
+            });
+            layer.draw(globe, canvas);
+        }
+        let measure_text = self.measure.as_deref().map(|points| {
+            let mut layer = Layer::default();
+            layer.lines.push(points.to_vec());
+            for &(lat, lon) in points {
+                layer.markers.push(markers::Marker {
+                    lat,
+                    lon,
+                    label: None,
+                    c: 'o',
+                });
+            }
+            layer.draw(globe, canvas);
+            match points {
+                [from, to] => {
+                    let km = globe::geo::distance_km(*from, *to);
+                    format!("{:.0} km, {:.0} nmi", km, km / 1.852)
+                }
+                [_] => "click the second point".to_string(),
+                _ => "measure: click two points".to_string(),
+            }
+        });
+        if let Some(lit) = &mut self.lit {
+            lit.angle = globe.angle;
+            lit.camera = Camera::new(self.cam_zoom, 0., self.cam_z + lit.wobble_offset());
+            settings.update_globe(lit);
+            lit.sun = Some(globe::subsolar_point(SystemTime::now()));
+            self.lit_canvas.clear();
+            lit.render_on(&mut self.lit_canvas);
+            settings.apply_effects(&mut self.lit_canvas);
+            self.layer.draw(lit, &mut self.lit_canvas);
+            self.dual_frame = Some(compare::side_by_side(
+                canvas,
+                &self.lit_canvas,
+                &settings.palette,
+            ));
+        }
+        let colors = settings.colors(globe, canvas);
+        let resolution = (globe.adaptive_resolution || self.budget.is_some())
+            .then(|| globe.render_resolution(canvas));
+        let frame = self.dual_frame.as_mut().unwrap_or(canvas);
+
+        if self.show_help {
+            overlay::draw_help(frame, &settings.bindings, self.mouse);
+        }
+        if let Some((text, _)) = &self.message {
+            overlay::draw_message(frame, text);
+        } else if let Some(text) = &measure_text {
+            overlay::draw_message(frame, text);
+        } else if self.paused {
+            overlay::draw_message(frame, "paused");
+        }
+        if settings.stats {
+            let quality = self.budget.as_ref().map(Budget::level);
+            overlay::draw_stats(frame, fps, resolution, quality, render_stats.as_ref());
+        }
+
+        app::print_frame(
+            out,
+            settings,
+            &mut self.title,
+            globe,
+            self.cam_zoom,
+            frame,
+            colors
+                .as_ref()
+                .or(self.anaglyph.as_ref().map(|a| &a.colors)),
+            self.term_size,
+            std::mem::take(&mut self.clear),
+            None,
+        );
+        if let Some(budget) = &mut self.budget {
+            budget.record(started.elapsed());
+        }
+        Some(frame)
+    }
+}
+
+/// Smooth zooming, optionally keeping a point of the globe under the
+/// pointer.
+#[derive(Default)]
+struct Zoom {
+    tween: Option<Tween>,
+    /// Canvas cell and the position (in degrees) that should stay there.
+    anchor: Option<((usize, usize), (Float, Float))>,
+}
+
+impl Zoom {
+    /// Animates the camera distance by `delta` from the current target,
+    /// stopping at the closest and farthest distance in `bounds`. Returns
+    /// whether it got all the way.
+    fn by(
+        &mut self,
+        current: Float,
+        delta: Float,
+        anchor: Option<((usize, usize), (Float, Float))>,
+        (near, far): (Float, Float),
+    ) -> bool {
+        let target = self.tween.as_ref().map_or(current, |t| t.target());
+        // a view already past a bound, e.g. from --zoom, isn't pulled back
+        let wanted = target + delta;
+        let target = wanted.clamp(near.min(target), far.max(target));
+        self.tween = Some(Tween::new(current, target, Duration::from_millis(150)));
+        self.anchor = anchor;
+        target == wanted
+    }
+
+    /// Camera distance for the current frame, if zooming.
+    fn step(&mut self) -> Option<Float> {
+        let tween = self.tween.as_ref()?;
+        let value = tween.value();
+        if tween.done() {
+            self.tween = None;
+        }
+        Some(value)
+    }
+}
+
+/// Moves the view by a mouse drag, in cells, so the surface near the
+/// center of `canvas` follows the pointer at any zoom, scaled by
+/// `settings.drag_sensitivity`. Horizontal movement only turns the globe and
+/// vertical movement only tilts the camera, except with `--classic-drag`.
+fn drag_view(
+    settings: &Settings,
+    globe: &mut Globe,
+    canvas: &Canvas,
+    cam_z: &mut Float,
+    (x_diff, y_diff): (Float, Float),
+) {
+    if settings.classic_drag {
+        // fixed steps, with vertical movement turning the globe as well
+        *cam_z = (*cam_z + y_diff * 0.1).clamp(-MAX_TILT, MAX_TILT);
+        globe.angle += (x_diff + y_diff) * PI / 30.;
+        return;
+    }
+    let sensitivity = settings.drag_sensitivity;
+    // radians per character width; rows are twice as tall
+    let radius = globe.limb_radius(canvas);
+    let step = match radius.is_finite() && radius > 0. {
+        true => sensitivity / radius,
+        false => 0.,
+    };
+    *cam_z = ease_tilt(*cam_z, y_diff * 2. * step);
+    globe.angle += x_diff * step;
+}
+
+/// Camera tilt after tilting by `delta`, slowing down over the last 0.3
+/// radians before `MAX_TILT` instead of stopping dead at it.
+fn ease_tilt(tilt: Float, delta: Float) -> Float {
+    const SOFT: Float = 0.3;
+    let mut delta = delta;
+    if delta * tilt > 0. {
+        let room = MAX_TILT - tilt.abs();
+        delta *= (room / SOFT).clamp(0., 1.);
+    }
+    (tilt + delta).clamp(-MAX_TILT, MAX_TILT)
+}
+
+/// Canvas cell under the mouse pointer at `pointer` in the terminal, if
+/// it's over the frame. Both panes of `--dual` map to the same cells.
+fn pointer_cell(
+    settings: &Settings,
+    canvas: &Canvas,
+    term_size: (u16, u16),
+    (x, y): (u16, u16),
+) -> Option<(usize, usize)> {
+    let (width, height) = overlay::grid_size(canvas);
+    let frame_width = width * (1 + settings.dual as usize);
+    let (left, top) = frame_origin_of(frame_width, canvas, term_size);
+    let (x, y) = (x.checked_sub(left)? as usize, y.checked_sub(top)? as usize);
+    if x >= frame_width || y >= height {
+        return None;
+    }
+    Some((x % width, y))
+}
+
+/// Position in degrees at the center of the view with the globe at `angle`
+/// and the camera at `tilt`, in radians.
+fn view_position((angle, tilt): (Float, Float)) -> (Float, Float) {
+    let lon = (180. - angle.to_degrees()).rem_euclid(360.) - 180.;
+    (globe::latitude_for_tilt(tilt), lon)
+}
+
+/// Whether two (angle, tilt) pairs show about the same view.
+fn same_view(a: (Float, Float), b: (Float, Float)) -> bool {
+    let turn = angles::shortest_arc(b.0, a.0);
+    turn.abs() < 1e-4 && (a.1 - b.1).abs() < 1e-4
+}
+
+/// View shown with the globe at its angle and the camera at `tilt` and
+/// `zoom`, with the longitude from -180 to 180.
+fn current_view(globe: &Globe, tilt: Float, zoom: Float) -> bookmarks::View {
+    let (lat, lon) = view_position((globe.angle, tilt));
+    bookmarks::View { lat, lon, zoom }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::settings;
+    use crossterm::event::{KeyModifiers, MouseButton};
+
+    fn key(app: &mut Interactive, code: KeyCode) -> app::Action {
+        app.handle_event(Event::Key(KeyEvent::from(code)))
+    }
+
+    /// Presses the mouse at the first of `points` and drags it through the
+    /// rest, giving how far the angle and tilt moved.
+    fn drag(app: &mut Interactive, points: &[(u16, u16)]) -> (Float, Float) {
+        let start = (app.globe.angle, app.cam_z);
+        let (x, y) = points[0];
+        let button = MouseButton::Left;
+        let mut events = vec![MouseEvent::Down(button, x, y, KeyModifiers::NONE)];
+        for &(x, y) in points {
+            events.push(MouseEvent::Drag(button, x, y, KeyModifiers::NONE));
+        }
+        for event in events {
+            assert_eq!(app.handle_event(Event::Mouse(event)), app::Action::Continue);
+        }
+        (app.globe.angle - start.0, app.cam_z - start.1)
+    }
+
+    /// Radians a drag of a character width turns the globe by.
+    fn step(app: &Interactive) -> Float {
+        app.settings.drag_sensitivity / app.globe.limb_radius(&app.canvas)
+    }
+
+    #[test]
+    fn horizontal_drags_only_turn() {
+        let settings = settings(&["-i"]);
+        let mut app = Interactive::new(&settings, (80, 24), true);
+        let (turn, tilt) = drag(&mut app, &[(40, 12), (43, 12), (46, 12)]);
+        assert!((turn - 6. * step(&app)).abs() < 1e-5, "turned {}", turn);
+        assert_eq!(tilt, 0.);
+        // back the other way
+        let (turn, _) = drag(&mut app, &[(46, 12), (36, 12)]);
+        assert!((turn + 10. * step(&app)).abs() < 1e-5, "turned {}", turn);
+    }
+
+    #[test]
+    fn vertical_drags_only_tilt() {
+        let settings = settings(&["-i"]);
+        let mut app = Interactive::new(&settings, (80, 24), true);
+        let (turn, tilt) = drag(&mut app, &[(40, 10), (40, 11), (40, 13)]);
+        assert_eq!(turn, 0.);
+        // rows are twice as tall as characters are wide
+        assert!((tilt - 6. * step(&app)).abs() < 1e-5, "tilted {}", tilt);
+        // and never past the pole however far it goes
+        drag(&mut app, &[(40, 0), (40, 23), (40, 0), (40, 23)]);
+        assert!(app.cam_z <= MAX_TILT);
+        assert_eq!(app.globe.angle, 0.);
+    }
+
+    #[test]
+    fn classic_drag() {
+        let settings = settings(&["-i", "--classic-drag"]);
+        let mut app = Interactive::new(&settings, (80, 24), true);
+        // fixed steps, with vertical movement turning the globe too
+        let (turn, tilt) = drag(&mut app, &[(40, 10), (40, 12)]);
+        assert!((turn - 2. * PI / 30.).abs() < 1e-5, "turned {}", turn);
+        assert!((tilt - 0.2).abs() < 1e-5, "tilted {}", tilt);
+    }
+
+    #[test]
+    fn keys() {
+        let settings = settings(&["-i", "--arrows", "step"]);
+        let mut app = Interactive::new(&settings, (80, 24), true);
+        assert_eq!(key(&mut app, KeyCode::Right), app::Action::Continue);
+        assert!((app.globe.angle + PI / 30.).abs() < 1e-5);
+        key(&mut app, KeyCode::Up);
+        assert!((app.cam_z - 0.1).abs() < 1e-5);
+        // nothing moves while paused
+        key(&mut app, KeyCode::Char(' '));
+        key(&mut app, KeyCode::Left);
+        key(&mut app, KeyCode::Down);
+        assert!((app.globe.angle + PI / 30.).abs() < 1e-5);
+        assert!((app.cam_z - 0.1).abs() < 1e-5);
+        assert_eq!(key(&mut app, KeyCode::Char('q')), app::Action::Quit);
+    }
+
+    #[test]
+    fn resize() {
+        let settings = settings(&["-i"]);
+        let mut app = Interactive::new(&settings, (80, 24), true);
+        let event = Event::Resize(120, 40);
+        assert_eq!(app.handle_event(event), app::Action::Continue);
+        assert_eq!(app.term_size, (120, 40));
+        assert_eq!(overlay::grid_size(&app.canvas), (80, 40));
+        assert!(app.clear);
+    }
+}
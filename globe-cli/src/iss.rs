@@ -0,0 +1,468 @@
+//! International Space Station tracking.
+//!
+//! Position is propagated locally from a two-line element set (TLE) using a
+//! circular orbit with J2 secular drift of the node and perigee. That's far
+//! from full SGP4 but good to within a few hundred kilometers for a fresh
+//! element set, which is plenty at ASCII resolution.
+//!
+//! With the `net` feature enabled the element set is fetched (and
+//! periodically refreshed) from a configurable URL. Without one, or until it
+//! arrives, the station is shown on an approximate orbit instead: a circular
+//! one at its usual inclination and altitude, which looks right but isn't
+//! where the station really is.
+//!
+//! `--iss` shows the station in the middle of the view, see `start`.
+
+use std::f64::consts::PI;
+use std::io::Write;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crossterm::event::Event;
+use globe::{Camera, Canvas, Float, Globe};
+
+use crate::app::{self, Action, Mode};
+use crate::control::Command;
+use crate::term::{self, Title};
+use crate::{status_canvas, ExitOn, Settings};
+
+#[cfg(feature = "net")]
+use crate::clock::{civil_from_days, days_from_civil};
+
+#[cfg(feature = "net")]
+use std::sync::mpsc::{channel, Receiver};
+#[cfg(feature = "net")]
+use std::time::Instant;
+
+/// Default source for fresh ISS elements.
+#[cfg(feature = "net")]
+pub const DEFAULT_URL: &str = "https://celestrak.org/NORAD/elements/gp.php?CATNR=25544&FORMAT=TLE";
+
+/// How far back the ground track reaches.
+const TRACK_LENGTH: Duration = Duration::from_secs(45 * 60);
+/// Time between ground track points.
+const TRACK_STEP: Duration = Duration::from_secs(30);
+/// How often fresh elements are requested.
+#[cfg(feature = "net")]
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+const EARTH_RADIUS_KM: f64 = 6378.137;
+const EARTH_MU: f64 = 398600.4418;
+const J2: f64 = 1.08262668e-3;
+
+/// Inclination of the station's orbit in degrees, which stays put.
+const ISS_INCLINATION: f64 = 51.64;
+/// Revolutions a day the station usually makes, about 420 km up.
+const ISS_MEAN_MOTION: f64 = 15.5;
+/// J2000, the epoch of the approximate orbit, as a julian date.
+const J2000: f64 = 2451545.;
+
+/// Orbital elements parsed from a two-line element set.
+pub struct Tle {
+    /// Epoch as a julian date.
+    epoch: f64,
+    inclination: f64,
+    raan: f64,
+    arg_perigee: f64,
+    mean_anomaly: f64,
+    /// Mean motion in radians per day.
+    mean_motion: f64,
+    /// Whether this is the approximate orbit rather than measured elements.
+    pub approximate: bool,
+}
+
+impl Tle {
+    #[cfg(feature = "net")]
+    /// Parses a TLE, with or without the leading name line.
+    pub fn parse(text: &str) -> Result<Tle, String> {
+        let lines: Vec<&str> = text
+            .lines()
+            .map(|l| l.trim_end())
+            .filter(|l| !l.is_empty())
+            .collect();
+        let line1 = lines
+            .iter()
+            .find(|l| l.starts_with("1 "))
+            .ok_or("missing TLE line 1")?;
+        let line2 = lines
+            .iter()
+            .find(|l| l.starts_with("2 "))
+            .ok_or("missing TLE line 2")?;
+        verify_checksum(line1)?;
+        verify_checksum(line2)?;
+
+        let year = field(line1, 18, 20)? as i64;
+        let year = if year < 57 { 2000 + year } else { 1900 + year };
+        let day = field(line1, 20, 32)?;
+
+        Ok(Tle {
+            epoch: julian_date_of_year(year) + day - 1.,
+            inclination: field(line2, 8, 16)?.to_radians(),
+            raan: field(line2, 17, 25)?.to_radians(),
+            arg_perigee: field(line2, 34, 42)?.to_radians(),
+            mean_anomaly: field(line2, 43, 51)?.to_radians(),
+            mean_motion: field(line2, 52, 63)? * 2. * PI,
+            approximate: false,
+        })
+    }
+
+    /// Circular orbit at the station's inclination and altitude, for when
+    /// there are no measured elements. Its ground track has the right shape
+    /// and speed but not the station's real position.
+    pub fn approximate() -> Tle {
+        Tle {
+            epoch: J2000,
+            inclination: ISS_INCLINATION.to_radians(),
+            raan: 0.,
+            arg_perigee: 0.,
+            mean_anomaly: 0.,
+            mean_motion: ISS_MEAN_MOTION * 2. * PI,
+            approximate: true,
+        }
+    }
+
+    /// Semi-major axis in kilometers.
+    fn semi_major_axis(&self) -> f64 {
+        let n = self.mean_motion / 86400.;
+        (EARTH_MU / (n * n)).cbrt()
+    }
+
+    /// Altitude above the equatorial radius in kilometers.
+    pub fn altitude_km(&self) -> f64 {
+        self.semi_major_axis() - EARTH_RADIUS_KM
+    }
+
+    /// Sub-satellite point (latitude, longitude in degrees) at `time`.
+    pub fn position_at(&self, time: SystemTime) -> (f64, f64) {
+        let jd = julian_date(time);
+        let dt = jd - self.epoch;
+
+        // secular J2 drift, which only means something from measured
+        // elements
+        let cos_i = self.inclination.cos();
+        let (raan, arg_perigee) = match self.approximate {
+            true => (self.raan, self.arg_perigee),
+            false => {
+                let p = self.semi_major_axis() / EARTH_RADIUS_KM;
+                let k = 1.5 * J2 * self.mean_motion / (p * p);
+                (
+                    self.raan - k * cos_i * dt,
+                    self.arg_perigee + 0.5 * k * (5. * cos_i * cos_i - 1.) * dt,
+                )
+            }
+        };
+
+        // argument of latitude on a circular orbit
+        let u = arg_perigee + self.mean_anomaly + self.mean_motion * dt;
+
+        let (sin_u, cos_u) = u.sin_cos();
+        let (sin_o, cos_o) = raan.sin_cos();
+        let x = cos_o * cos_u - sin_o * sin_u * cos_i;
+        let y = sin_o * cos_u + cos_o * sin_u * cos_i;
+        let z = sin_u * self.inclination.sin();
+
+        let lat = z.asin();
+        let lon = y.atan2(x) - gmst(jd);
+        (lat.to_degrees(), normalize_degrees(lon.to_degrees()))
+    }
+
+    #[cfg(feature = "net")]
+    /// Date of the epoch as `YYYY-MM-DD`.
+    pub fn epoch_date(&self) -> String {
+        let (y, m, d) = civil_from_days((self.epoch - 2440587.5).floor() as i64);
+        format!("{:04}-{:02}-{:02}", y, m, d)
+    }
+}
+
+/// Ground track leading up to `time`, oldest point first.
+pub fn ground_track(tle: &Tle, time: SystemTime) -> Vec<(f64, f64)> {
+    let steps = (TRACK_LENGTH.as_secs() / TRACK_STEP.as_secs()) as u32;
+    (1..=steps)
+        .rev()
+        .filter_map(|i| time.checked_sub(TRACK_STEP * i))
+        .map(|t| tle.position_at(t))
+        .collect()
+}
+
+/// Source of element sets for the tracker.
+pub struct Tracker {
+    pub tle: Tle,
+    /// Where the current elements came from, for the status line.
+    pub source: String,
+    /// Set when the last refresh failed.
+    pub warning: Option<String>,
+    #[cfg(feature = "net")]
+    url: Option<String>,
+    #[cfg(feature = "net")]
+    pending: Option<Receiver<Result<Tle, String>>>,
+    #[cfg(feature = "net")]
+    last_request: Option<Instant>,
+}
+
+impl Tracker {
+    /// Creates a tracker. Without a `url` only the approximate orbit is
+    /// used.
+    pub fn new(url: Option<String>) -> Self {
+        let tle = Tle::approximate();
+        let source = "approximate orbit, not the real position".to_string();
+        #[cfg(not(feature = "net"))]
+        let _ = url;
+        Tracker {
+            tle,
+            source,
+            warning: None,
+            #[cfg(feature = "net")]
+            url,
+            #[cfg(feature = "net")]
+            pending: None,
+            #[cfg(feature = "net")]
+            last_request: None,
+        }
+    }
+
+    /// Starts a background refresh when one is due and picks up the result
+    /// of a finished one. Never blocks.
+    pub fn update(&mut self) {
+        #[cfg(feature = "net")]
+        {
+            let url = match &self.url {
+                Some(url) => url.clone(),
+                None => return,
+            };
+            if let Some(rx) = &self.pending {
+                match rx.try_recv() {
+                    Ok(Ok(tle)) => {
+                        self.source = format!("TLE epoch {}", tle.epoch_date());
+                        self.tle = tle;
+                        self.warning = None;
+                        self.pending = None;
+                    }
+                    Ok(Err(e)) => {
+                        self.warning =
+                            Some(format!("refresh failed ({}), using last known TLE", e));
+                        self.pending = None;
+                    }
+                    Err(std::sync::mpsc::TryRecvError::Empty) => (),
+                    Err(std::sync::mpsc::TryRecvError::Disconnected) => self.pending = None,
+                }
+            }
+            let due = self
+                .last_request
+                .is_none_or(|t| t.elapsed() >= REFRESH_INTERVAL);
+            if self.pending.is_none() && due {
+                let (tx, rx) = channel();
+                std::thread::spawn(move || {
+                    let _ = tx.send(fetch(&url));
+                });
+                self.pending = Some(rx);
+                self.last_request = Some(Instant::now());
+            }
+        }
+    }
+}
+
+#[cfg(feature = "net")]
+fn fetch(url: &str) -> Result<Tle, String> {
+    let body = ureq::get(url)
+        .timeout(Duration::from_secs(10))
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_string()
+        .map_err(|e| e.to_string())?;
+    Tle::parse(&body)
+}
+
+/// Parses the columns `start..end` (zero based) of a TLE line.
+#[cfg(feature = "net")]
+fn field(line: &str, start: usize, end: usize) -> Result<f64, String> {
+    line.get(start..end)
+        .ok_or_else(|| format!("TLE line too short: {:?}", line))?
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid TLE field in columns {}-{}", start + 1, end))
+}
+
+#[cfg(feature = "net")]
+fn verify_checksum(line: &str) -> Result<(), String> {
+    let expected = line
+        .chars()
+        .nth(68)
+        .and_then(|c| c.to_digit(10))
+        .ok_or("TLE line missing checksum")?;
+    let sum: u32 = line
+        .chars()
+        .take(68)
+        .map(|c| match c {
+            '-' => 1,
+            c => c.to_digit(10).unwrap_or(0),
+        })
+        .sum();
+    if sum % 10 != expected {
+        return Err(format!("TLE checksum mismatch on line {}", &line[..1]));
+    }
+    Ok(())
+}
+
+fn julian_date(time: SystemTime) -> f64 {
+    let secs = match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs_f64(),
+        Err(e) => -e.duration().as_secs_f64(),
+    };
+    secs / 86400. + 2440587.5
+}
+
+/// Julian date of January 1st, 00:00 UTC.
+#[cfg(feature = "net")]
+fn julian_date_of_year(year: i64) -> f64 {
+    days_from_civil(year, 1, 1) as f64 + 2440587.5
+}
+
+/// Greenwich mean sidereal time in radians.
+fn gmst(jd: f64) -> f64 {
+    let deg = 280.46061837 + 360.98564736629 * (jd - 2451545.0);
+    deg.rem_euclid(360.).to_radians()
+}
+
+fn normalize_degrees(deg: f64) -> f64 {
+    (deg + 180.).rem_euclid(360.) - 180.
+}
+
+/// Shows the station until it's interrupted or a character key is pressed,
+/// refreshing its elements from `url` if given.
+pub fn start(url: Option<String>, settings: &Settings) {
+    app::run(settings, false, |term_size, _| {
+        Iss::new(settings, Tracker::new(url), term_size)
+    });
+}
+
+/// State of `--iss` between frames.
+pub struct Iss<'a> {
+    settings: &'a Settings,
+    globe: Globe,
+    tracker: Tracker,
+    term_size: (u16, u16),
+    canvas: Canvas,
+    cam_zoom: Float,
+    /// When the station was last placed, and where it was in degrees.
+    time: SystemTime,
+    position: (Float, Float),
+    title: Title,
+    /// Whether the terminal needs clearing before the next frame.
+    clear: bool,
+}
+
+impl<'a> Iss<'a> {
+    pub fn new(settings: &'a Settings, tracker: Tracker, term_size: (u16, u16)) -> Iss<'a> {
+        Iss {
+            settings,
+            globe: settings.globe(),
+            tracker,
+            term_size,
+            canvas: status_canvas(term_size, settings),
+            cam_zoom: settings.zoom,
+            time: SystemTime::now(),
+            position: (0., 0.),
+            title: Title::default(),
+            clear: false,
+        }
+    }
+
+    /// Where the station is and where its elements came from.
+    fn status(&self) -> String {
+        let (lat, lon) = self.position;
+        // whole degrees are all an approximate orbit is good for, if that
+        let precision = if self.tracker.tle.approximate { 0 } else { 1 };
+        let mut status = format!(
+            "ISS {:.*}\u{b0}{} {:.*}\u{b0}{}  alt {:.0} km  ({})",
+            precision,
+            lat.abs(),
+            if lat >= 0. { 'N' } else { 'S' },
+            precision,
+            lon.abs(),
+            if lon >= 0. { 'E' } else { 'W' },
+            self.tracker.tle.altitude_km(),
+            self.tracker.source,
+        );
+        if let Some(warning) = &self.tracker.warning {
+            status.push_str("  warning: ");
+            status.push_str(warning);
+        }
+        status
+    }
+}
+
+impl Mode for Iss<'_> {
+    fn handle_event(&mut self, event: Event) -> Action {
+        match event {
+            Event::Key(event) if term::is_interrupt(&event) => return Action::Quit,
+            _ if ExitOn::Char.exits(&event) => return Action::Quit,
+            Event::Resize(width, height) => {
+                self.term_size = (width, height);
+                self.canvas = status_canvas(self.term_size, self.settings);
+                // nothing overwrites what the old size left behind
+                self.clear = true;
+            }
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    fn handle_command(&mut self, command: Command) -> Action {
+        // the view stays on the station, so there's little to control
+        match command {
+            Command::Zoom(zoom) => self.cam_zoom = zoom,
+            Command::Quit => return Action::Quit,
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    fn update(&mut self, _dt: Duration) {
+        self.tracker.update();
+        self.time = SystemTime::now();
+        let (lat, lon) = self.tracker.tle.position_at(self.time);
+        self.position = (lat as Float, lon as Float);
+
+        // keep the station in the center of the view, easing after its
+        // position as it's only recomputed so often
+        self.globe.follow(self.position.0, self.position.1);
+        self.settings.update_globe(&mut self.globe);
+    }
+
+    fn draw(&mut self, out: &mut impl Write, _fps: Float) -> Option<&Canvas> {
+        let settings = self.settings;
+        let globe = &mut self.globe;
+        let (lat, lon) = self.position;
+        let (view_lat, _) = globe.follow.view.unwrap_or((lat, lon));
+        let cam_z = globe::tilt_for_latitude(view_lat);
+        globe.camera = Camera::new(self.cam_zoom, 0., cam_z + globe.wobble_offset());
+
+        let canvas = &mut self.canvas;
+        canvas.clear();
+        globe.render_on(canvas);
+        settings.apply_effects(canvas);
+        settings.layer.draw(globe, canvas);
+        for (lat, lon) in ground_track(&self.tracker.tle, self.time) {
+            if let Some((x, y)) = globe.project(canvas, lat as Float, lon as Float) {
+                canvas.draw_point(x, y, '+');
+            }
+        }
+        if let Some((x, y)) = globe.project(canvas, lat, lon) {
+            canvas.draw_point(x, y, '#');
+        }
+
+        let status = self.status();
+        app::print_frame(
+            out,
+            settings,
+            &mut self.title,
+            &self.globe,
+            self.cam_zoom,
+            &mut self.canvas,
+            None,
+            self.term_size,
+            std::mem::take(&mut self.clear),
+            Some(&status),
+        );
+        Some(&self.canvas)
+    }
+}
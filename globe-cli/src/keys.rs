@@ -0,0 +1,334 @@
+//! Configurable key bindings for interactive mode.
+
+use std::fmt;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use globe::{Continent, Float};
+
+/// Something the user can do with a key press.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    RotateLeft,
+    RotateRight,
+    TiltUp,
+    TiltDown,
+    /// Turns left and tilts up at once, and likewise for the other corners.
+    RotateUpLeft,
+    RotateUpRight,
+    RotateDownLeft,
+    RotateDownRight,
+    ZoomIn,
+    ZoomOut,
+    Quit,
+    Pause,
+    Reset,
+    Screenshot,
+    /// Saves the view under the number key pressed next.
+    Bookmark,
+    /// Turns to the other side of the globe, or back again.
+    Antipode,
+    /// Measures the distance between two clicked points.
+    Measure,
+    /// Lets the view leave the `--lock-region`.
+    Unlock,
+    /// Makes clicks and drags paint on the globe instead of turning it.
+    Paint,
+    /// Flies to a view of a continent, see `globe::Presets::continent`.
+    Face(Continent),
+    Help,
+}
+
+impl Action {
+    pub const ALL: [Action; 27] = [
+        Action::RotateLeft,
+        Action::RotateRight,
+        Action::TiltUp,
+        Action::TiltDown,
+        Action::RotateUpLeft,
+        Action::RotateUpRight,
+        Action::RotateDownLeft,
+        Action::RotateDownRight,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::Quit,
+        Action::Pause,
+        Action::Reset,
+        Action::Screenshot,
+        Action::Bookmark,
+        Action::Antipode,
+        Action::Measure,
+        Action::Unlock,
+        Action::Paint,
+        Action::Face(Continent::Africa),
+        Action::Face(Continent::Antarctica),
+        Action::Face(Continent::Asia),
+        Action::Face(Continent::Europe),
+        Action::Face(Continent::NorthAmerica),
+        Action::Face(Continent::Oceania),
+        Action::Face(Continent::SouthAmerica),
+        Action::Help,
+    ];
+
+    /// Name used in the configuration file and `--bind`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::RotateLeft => "rotate_left",
+            Action::RotateRight => "rotate_right",
+            Action::TiltUp => "tilt_up",
+            Action::TiltDown => "tilt_down",
+            Action::RotateUpLeft => "rotate_up_left",
+            Action::RotateUpRight => "rotate_up_right",
+            Action::RotateDownLeft => "rotate_down_left",
+            Action::RotateDownRight => "rotate_down_right",
+            Action::ZoomIn => "zoom_in",
+            Action::ZoomOut => "zoom_out",
+            Action::Quit => "quit",
+            Action::Pause => "pause",
+            Action::Reset => "reset",
+            Action::Screenshot => "screenshot",
+            Action::Bookmark => "bookmark",
+            Action::Antipode => "antipode",
+            Action::Measure => "measure",
+            Action::Unlock => "unlock",
+            Action::Paint => "paint",
+            Action::Face(continent) => match continent {
+                Continent::Africa => "face_africa",
+                Continent::Antarctica => "face_antarctica",
+                Continent::Asia => "face_asia",
+                Continent::Europe => "face_europe",
+                Continent::NorthAmerica => "face_north_america",
+                Continent::Oceania => "face_oceania",
+                Continent::SouthAmerica => "face_south_america",
+            },
+            Action::Help => "help",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.iter().copied().find(|a| a.name() == name)
+    }
+
+    /// Directions a diagonal action turns and tilts the view in, positive
+    /// being left and up.
+    pub fn diagonal(self) -> Option<(Float, Float)> {
+        match self {
+            Action::RotateUpLeft => Some((1., 1.)),
+            Action::RotateUpRight => Some((-1., 1.)),
+            Action::RotateDownLeft => Some((1., -1.)),
+            Action::RotateDownRight => Some((-1., -1.)),
+            _ => None,
+        }
+    }
+
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            // vi keys, with the diagonals shifted as `b` and `u` were taken
+            Action::RotateLeft => &["Left", "h"],
+            Action::RotateRight => &["Right", "l"],
+            Action::TiltUp => &["Up", "k"],
+            Action::TiltDown => &["Down", "j"],
+            // with Num Lock off the keypad's 7 and 1 send Home and End; its
+            // 9 and 3 send the zoom keys
+            Action::RotateUpLeft => &["Y", "Home"],
+            Action::RotateUpRight => &["U"],
+            Action::RotateDownLeft => &["B", "End"],
+            Action::RotateDownRight => &["N"],
+            Action::ZoomIn => &["PageDown"],
+            Action::ZoomOut => &["PageUp"],
+            Action::Quit => &["q", "Esc"],
+            Action::Pause => &["Space"],
+            Action::Reset => &["r"],
+            Action::Screenshot => &["s"],
+            Action::Bookmark => &["b"],
+            Action::Antipode => &["a"],
+            Action::Measure => &["m"],
+            Action::Unlock => &["u"],
+            Action::Paint => &["p"],
+            // function keys, as the number row goes to the bookmarks
+            Action::Face(continent) => match continent {
+                Continent::Africa => &["F1"],
+                Continent::Antarctica => &["F2"],
+                Continent::Asia => &["F3"],
+                Continent::Europe => &["F4"],
+                Continent::NorthAmerica => &["F5"],
+                Continent::Oceania => &["F6"],
+                Continent::SouthAmerica => &["F7"],
+            },
+            Action::Help => &["?"],
+        }
+    }
+}
+
+/// Key press with modifiers, e.g. `Ctrl+c` or `PageUp`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub fn parse(s: &str) -> Result<Key, String> {
+        let mut modifiers = KeyModifiers::empty();
+        let mut rest = s;
+        // a lone `+` is a key, not a separator
+        while let Some(i) = rest.find('+').filter(|&i| i > 0) {
+            modifiers |= match rest[..i].to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier in key `{}`", s)),
+            };
+            rest = &rest[i + 1..];
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdown" | "pgdn" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "insert" => KeyCode::Insert,
+                "delete" | "del" => KeyCode::Delete,
+                "space" => KeyCode::Char(' '),
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key `{}`", s)),
+                },
+            },
+        };
+        Ok(Key::new(code, modifiers))
+    }
+
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Key {
+        let modifiers = match code {
+            // the case of the character already tells whether shift was held
+            KeyCode::Char(_) => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        Key { code, modifiers }
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Key {
+        Key::new(event.code, event.modifiers)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDown"),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// Active key bindings.
+pub struct Bindings {
+    keys: Vec<(Action, Vec<Key>)>,
+}
+
+impl Bindings {
+    /// Builds the bindings from the configuration file and `--bind` flags.
+    ///
+    /// Every action mentioned replaces its default keys, with flags taking
+    /// precedence over the configuration file. Keys bound to more than one
+    /// action are an error.
+    pub fn new(config: &[(Action, Vec<Key>)], flags: &[&str]) -> Result<Bindings, String> {
+        let mut keys: Vec<(Action, Vec<Key>)> = Action::ALL
+            .iter()
+            .map(|&a| {
+                let keys = a.default_keys().iter().map(|k| Key::parse(k).unwrap());
+                (a, keys.collect())
+            })
+            .collect();
+        for (action, config_keys) in config {
+            keys.iter_mut().find(|(a, _)| a == action).unwrap().1 = config_keys.clone();
+        }
+
+        let mut overridden = Vec::new();
+        for flag in flags {
+            let (action, key) = parse_binding(flag)?;
+            let entry = &mut keys.iter_mut().find(|(a, _)| *a == action).unwrap().1;
+            if !overridden.contains(&action) {
+                entry.clear();
+                overridden.push(action);
+            }
+            entry.push(key);
+        }
+
+        let interrupt = Key::parse("Ctrl+c").unwrap();
+        for (i, (action, action_keys)) in keys.iter().enumerate() {
+            for key in action_keys {
+                if *key == interrupt {
+                    return Err(format!("`{}` always quits and can't be bound", key));
+                }
+                if let Some((other, _)) = keys[i + 1..].iter().find(|(_, k)| k.contains(key)) {
+                    return Err(format!(
+                        "key `{}` is bound to both `{}` and `{}`",
+                        key,
+                        action.name(),
+                        other.name()
+                    ));
+                }
+            }
+        }
+        Ok(Bindings { keys })
+    }
+
+    /// Action bound to a key press, if any.
+    pub fn action(&self, event: KeyEvent) -> Option<Action> {
+        let key = Key::from(event);
+        self.keys
+            .iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|(a, _)| *a)
+    }
+
+    /// Keys bound to `action`, for display.
+    pub fn keys(&self, action: Action) -> &[Key] {
+        &self.keys.iter().find(|(a, _)| *a == action).unwrap().1
+    }
+}
+
+/// Parses an `action=key` binding.
+fn parse_binding(s: &str) -> Result<(Action, Key), String> {
+    let (name, key) = s
+        .split_once('=')
+        .ok_or_else(|| format!("invalid binding `{}`, expected `action=key`", s))?;
+    let action = Action::from_name(name.trim()).ok_or_else(|| unknown_action(name.trim()))?;
+    Ok((action, Key::parse(key)?))
+}
+
+pub fn unknown_action(name: &str) -> String {
+    let names: Vec<&str> = Action::ALL.iter().map(|a| a.name()).collect();
+    format!(
+        "unknown action `{}`, expected one of: {}",
+        name,
+        names.join(", ")
+    )
+}
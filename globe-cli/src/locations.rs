@@ -0,0 +1,92 @@
+//! Places known by name, for `--flight` and the `locations` command.
+
+use globe::Float;
+
+/// A city with a large airport.
+pub struct Location {
+    /// IATA code of the airport.
+    pub code: &'static str,
+    pub name: &'static str,
+    /// Position of the airport in degrees.
+    pub lat: Float,
+    pub lon: Float,
+}
+
+const fn place(code: &'static str, name: &'static str, lat: Float, lon: Float) -> Location {
+    Location {
+        code,
+        name,
+        lat,
+        lon,
+    }
+}
+
+/// Known places, sorted by airport code.
+pub const LOCATIONS: &[Location] = &[
+    place("AKL", "Auckland", -37.01, 174.79),
+    place("AMS", "Amsterdam", 52.31, 4.76),
+    place("ANC", "Anchorage", 61.17, -149.99),
+    place("ATL", "Atlanta", 33.64, -84.43),
+    place("BKK", "Bangkok", 13.69, 100.75),
+    place("BOG", "Bogota", 4.70, -74.15),
+    place("BOM", "Mumbai", 19.09, 72.87),
+    place("BOS", "Boston", 42.36, -71.01),
+    place("CAI", "Cairo", 30.12, 31.41),
+    place("CDG", "Paris", 49.01, 2.55),
+    place("DEL", "Delhi", 28.57, 77.10),
+    place("DEN", "Denver", 39.86, -104.67),
+    place("DFW", "Dallas", 32.90, -97.04),
+    place("DOH", "Doha", 25.27, 51.61),
+    place("DXB", "Dubai", 25.25, 55.36),
+    place("EZE", "Buenos Aires", -34.82, -58.54),
+    place("FCO", "Rome", 41.80, 12.25),
+    place("FRA", "Frankfurt", 50.03, 8.56),
+    place("GRU", "Sao Paulo", -23.43, -46.47),
+    place("HKG", "Hong Kong", 22.31, 113.91),
+    place("HND", "Tokyo Haneda", 35.55, 139.78),
+    place("HNL", "Honolulu", 21.32, -157.92),
+    place("ICN", "Seoul", 37.46, 126.44),
+    place("IST", "Istanbul", 41.26, 28.74),
+    place("JFK", "New York", 40.64, -73.78),
+    place("JNB", "Johannesburg", -26.14, 28.24),
+    place("KEF", "Reykjavik", 63.99, -22.62),
+    place("LAX", "Los Angeles", 33.94, -118.41),
+    place("LHR", "London", 51.47, -0.45),
+    place("LIM", "Lima", -12.02, -77.11),
+    place("LOS", "Lagos", 6.58, 3.32),
+    place("MAD", "Madrid", 40.47, -3.56),
+    place("MEL", "Melbourne", -37.67, 144.84),
+    place("MEX", "Mexico City", 19.44, -99.07),
+    place("MIA", "Miami", 25.79, -80.29),
+    place("NBO", "Nairobi", -1.32, 36.93),
+    place("NRT", "Tokyo Narita", 35.77, 140.39),
+    place("ORD", "Chicago", 41.98, -87.90),
+    place("PEK", "Beijing", 40.08, 116.58),
+    place("PVG", "Shanghai", 31.14, 121.81),
+    place("SCL", "Santiago", -33.39, -70.79),
+    place("SEA", "Seattle", 47.45, -122.31),
+    place("SFO", "San Francisco", 37.62, -122.38),
+    place("SIN", "Singapore", 1.36, 103.99),
+    place("SVO", "Moscow", 55.97, 37.41),
+    place("SYD", "Sydney", -33.95, 151.18),
+    place("YVR", "Vancouver", 49.19, -123.18),
+    place("YYZ", "Toronto", 43.68, -79.63),
+];
+
+/// The place with an airport code, ignoring case.
+pub fn by_code(code: &str) -> Option<&'static Location> {
+    LOCATIONS
+        .iter()
+        .find(|l| l.code.eq_ignore_ascii_case(code.trim()))
+}
+
+/// Places whose name or code contains `query`, ignoring case.
+pub fn search(query: &str) -> Vec<&'static Location> {
+    let query = query.trim().to_lowercase();
+    LOCATIONS
+        .iter()
+        .filter(|l| {
+            l.name.to_lowercase().contains(&query) || l.code.to_lowercase().contains(&query)
+        })
+        .collect()
+}
@@ -5,260 +5,2787 @@
 //! **Mouse**: click and drag to rotate the globe. Use the mouse wheel to zoom in
 //! and out.
 //!
-//! **Keyboard**: use arrow keys to rotate, *PgUp* and *PgDown* to zoom.
+//! **Keyboard**: use arrow keys to rotate, *PgUp* and *PgDown* to zoom, *q* to
+//! quit and *?* to list all key bindings. Bindings can be changed with
+//! `--bind` or the `[keys]` section of the configuration file.
+//!
+//! **Bookmarks**: *b* followed by *1* to *9* saves the view, and the number
+//! alone flies back to it.
+//!
+//! **Antipode**: *a* turns to the opposite side of the globe, and again
+//! back.
+//!
+//! **Measuring**: *m* and two clicks give the distance between the clicked
+//! points.
+//!
+//! # ISS tracking
+//!
+//! `--iss` follows the International Space Station, drawing its recent ground
+//! track. Build with the `net` feature to fetch up-to-date orbital elements.
+
+mod anaglyph;
+mod animation;
+mod app;
+mod bench;
+mod bookmarks;
+mod budget;
+mod clock;
+mod compare;
+mod completions;
+mod config;
+mod control;
+mod countries;
+mod drag;
+mod events;
+mod flat;
+mod flight;
+mod flow;
+mod graphics;
+mod input;
+mod interactive;
+mod iss;
+mod json;
+mod keys;
+mod locations;
+mod markers;
+mod moon;
+mod notify;
+mod overlay;
+mod record;
+mod route;
+mod screensaver;
+mod session;
+mod sheet;
+mod term;
+mod tour;
+mod watch;
 
 use std::f32::consts::PI;
-use std::io::{stdout, Write};
-use std::time::Duration;
+use std::ffi::OsString;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, stdout, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use clap::{App, AppSettings, Arg};
+use clap::{App, AppSettings, Arg, ArgGroup, ArgMatches, ErrorKind};
 use crossterm::{
     cursor,
-    event::{poll, read, Event, KeyCode},
-    style::Print,
-    ExecutableCommand, QueueableCommand,
+    event::{Event, KeyCode},
+    style::{self, Print},
+    QueueableCommand,
 };
-use crossterm::{event::MouseEvent, terminal};
 
-use crossterm::terminal::ClearType;
-use globe::{Camera, Canvas, GlobeConfig, GlobeTemplate};
+use globe::prelude::*;
+use globe::{effects, Color, ColorMode, Dither, ElevationRamp, Theme};
+
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::graphics::{Output, Raster};
+use crate::json::Json;
+use crate::keys::Bindings;
+use crate::markers::Layer;
+use crate::notify::Notify;
+use crate::record::Recorder;
+use crate::session::Session;
+use crate::term::{Background, ColorChoice, ColorDepth};
+
+/// Smallest frame in cells a globe is drawn in; smaller terminals get a
+/// message instead.
+const MIN_FRAME: (u16, u16) = (8, 4);
+
+/// Lowest contrast between markers and terrain of a configured theme that
+/// doesn't get a warning, see `Color::contrast`.
+const MIN_CONTRAST: Float = 3.;
+
+/// `--print --size` below this many columns prints a `globe::thumbnail`.
+const THUMBNAIL_BELOW: u16 = 10;
+
+/// Width of a terminal cell over its height in most fonts.
+const DEFAULT_CELL_ASPECT: Float = 0.5;
+
+/// Range frame rates are clamped to: below it frames are minutes apart,
+/// above it faster than any terminal draws.
+const MIN_FPS: Float = 0.1;
+const MAX_FPS: Float = 1000.;
+
+/// Fastest `--event-speed` and longest `--event-fade` in frames. Together
+/// with the slowest frame rate they keep the fade time well within what a
+/// `Duration` holds.
+const MAX_EVENT_SPEED: Float = 1e9;
+const MAX_EVENT_FADE: u32 = 10_000;
+
+/// Degrees of longitude between the letters of `--banner`.
+const BANNER_SPACING: Float = 4.;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 pub const AUTHORS: &str = env!("CARGO_PKG_AUTHORS");
 
-fn main() {
+/// Options shared by all modes, merged from the command line, the
+/// configuration file and built-in defaults, in that order of precedence.
+struct Settings {
+    template: GlobeTemplate,
+    /// Contents of a `--texture` file, used instead of the template.
+    texture: Option<String>,
+    /// Texture pack from `--pack`, used instead of the template.
+    pack: Option<GlobeConfig>,
+    /// Screensaver rotation speed, relative to the default.
+    speed: Float,
+    /// Initial camera distance from the center of the globe.
+    zoom: Float,
+    /// Initial view center as latitude and longitude in degrees.
+    coords: (Float, Float),
+    fps: Float,
+    /// Shading characters, from darkest to brightest.
+    palette: Vec<char>,
+    bindings: Bindings,
+    /// Draw on the alternate screen so the terminal contents survive.
+    alt_screen: bool,
+    /// Fixed frame size in cells, instead of the terminal size.
+    width: Option<u16>,
+    height: Option<u16>,
+    output: Output,
+    clock: Option<Clock>,
+    /// Markers and lines from `--geojson` files.
+    layer: Layer,
+    timezones: bool,
+    /// Light the globe by the real sun, with city lights on the night side.
+    city_lights: bool,
+    /// Scale bar and longitude labels, from `scale`.
+    scale_bar: bool,
+    dither: Dither,
+    /// Render fewer characters while zoomed out, with `--adaptive-resolution`.
+    adaptive_resolution: bool,
+    /// Frame rate `--adaptive-quality` lowers the quality to hold.
+    target_fps: Option<Float>,
+    /// Region drawn brighter, from `--highlight-country`.
+    highlight: Option<globe::Highlight>,
+    /// Specular highlight from `--specular`.
+    lighting: globe::Lighting,
+    /// How often the screensaver turns back to the view center.
+    refocus: Option<Duration>,
+    /// Local hour whose time zone band is emphasized.
+    highlight_hour: Option<u32>,
+    /// Events from `--events`, replayed `event_speed` times faster than
+    /// they happened, each fading out over `event_fade` frames.
+    events: Vec<globe::Event>,
+    event_speed: Float,
+    event_fade: u32,
+    /// Stops of the route from `--route`, drawn numbered.
+    route: Option<Vec<(Float, Float)>>,
+    /// Field the particles of `--flow` or `--flow-demo` drift along.
+    flow: Option<globe::FlowField>,
+    /// When the program started, for replaying events.
+    start: Instant,
+    /// Route for `--flight`, flown in `flight_time` and followed by the
+    /// camera with `follow`.
+    flight: Option<flight::Route>,
+    flight_time: Duration,
+    follow: bool,
+    /// Draw a ring around the globe showing how far the flight got.
+    progress: bool,
+    /// How long the screensaver runs before exiting by itself.
+    duration: Option<Duration>,
+    exit_on: ExitOn,
+    /// What tells the terminal a run finished on its own.
+    notify: Notify,
+    /// How held arrow keys move the view in interactive mode.
+    arrows: Arrows,
+    /// Degrees per second held arrow keys turn the view by.
+    arrow_speed: Float,
+    /// Read commands from stdin while rendering.
+    control_stdin: bool,
+    /// Only draw screensaver frames once the rotation is visible.
+    low_power: bool,
+    /// Capture the mouse in modes that use it.
+    mouse: bool,
+    /// Idle time after which interactive mode starts spinning.
+    idle_spin: Option<Duration>,
+    /// How `--color` colors the globe, if it does.
+    color: Option<ColorMode>,
+    /// Colors the terminal shows, which colored frames are drawn with.
+    color_depth: ColorDepth,
+    /// Colors of `--color` on the Earth.
+    theme: Theme,
+    /// Contents of a `--heightmap` file and the colors of
+    /// `--elevation-ramp`, for `--color-mode elevation`.
+    heightmap: Option<String>,
+    elevation_ramp: Option<ElevationRamp>,
+    /// Stops of the `--tour` interactive mode starts with.
+    tour: Option<Vec<tour::Stop>>,
+    /// Columns of the `globe::thumbnail` printed instead of a frame, for
+    /// `--print` with a `--size` too small to render.
+    thumbnail: Option<usize>,
+    /// How long the terminal size has to stay the same before frames are
+    /// drawn at the new size.
+    resize_delay: Duration,
+    /// Show the view center in the window title.
+    title_coords: bool,
+    /// Show the measured frame rate on top of the globe.
+    stats: bool,
+    /// Multiplier for how far dragging turns the globe.
+    drag_sensitivity: Float,
+    /// Drag with the old fixed steps, vertical drags turning the globe too.
+    classic_drag: bool,
+    /// Region interactive mode keeps the view center in, until unlocked.
+    lock_region: Option<globe::Bounds>,
+    /// File to record frames to and its size limit in bytes.
+    record: Option<(PathBuf, Option<u64>)>,
+    /// Save the interactive view on exit, for `--resume`.
+    resume: bool,
+    /// Amplitude in radians and period of the camera tilt wobble.
+    wobble: Option<(Float, Duration)>,
+    /// Screensaver spin axis, in radians clockwise from vertical, and
+    /// whether it swings to and fro.
+    spin_axis: Float,
+    tumble: bool,
+    /// Whether the screensaver globe is small and bounces around.
+    bounce: bool,
+    /// Show the view a second time next to the first, lit by the sun.
+    dual: bool,
+    /// Whether to draw red and cyan stereo frames, with `--anaglyph`.
+    anaglyph: bool,
+    /// Names and templates of the globes shown side by side by `--compare`.
+    compare: Option<((String, GlobeTemplate), (String, GlobeTemplate))>,
+    /// Orbit of the screensaver moon from `--with-moon`.
+    moon: Option<moon::Orbit>,
+    /// Text written around the equator with `--banner`.
+    banner: Option<String>,
+    /// Post-processing from `--effect`, in the order given.
+    effects: Vec<Box<dyn Effect>>,
+    /// Width of a terminal cell over its height, from `--cell-aspect`.
+    cell_aspect: Float,
+}
+
+/// Input that ends the screensaver. Ctrl+C and termination signals always do.
+#[derive(Clone, Copy, PartialEq)]
+enum ExitOn {
+    /// Any key press or mouse event.
+    Any,
+    /// Character keys, but not arrows, function keys and the like.
+    Char,
+    /// Only the `q` key.
+    Q,
+}
+
+impl ExitOn {
+    fn parse(s: &str) -> Result<ExitOn, String> {
+        match s {
+            "any" => Ok(ExitOn::Any),
+            "char" => Ok(ExitOn::Char),
+            "q" => Ok(ExitOn::Q),
+            _ => Err(format!("invalid exit-on `{}`, expected any, char or q", s)),
+        }
+    }
+
+    fn exits(self, event: &Event) -> bool {
+        match (self, event) {
+            (_, Event::Resize(..)) => false,
+            (ExitOn::Any, _) => true,
+            (ExitOn::Char, Event::Key(key)) => matches!(key.code, KeyCode::Char(_)),
+            (ExitOn::Q, Event::Key(key)) => key.code == KeyCode::Char('q'),
+            _ => false,
+        }
+    }
+}
+
+/// How interactive mode reacts to arrow keys, and the other keys that turn
+/// or tilt the view, being held down.
+#[derive(Clone, Copy, PartialEq)]
+enum Arrows {
+    /// Turn smoothly for as long as the key repeats.
+    Hold,
+    /// Turn by a fixed step for every press and repeat.
+    Step,
+}
+
+impl Arrows {
+    fn parse(s: &str) -> Result<Arrows, String> {
+        match s {
+            "hold" => Ok(Arrows::Hold),
+            "step" => Ok(Arrows::Step),
+            _ => Err(format!("invalid arrows `{}`, expected hold or step", s)),
+        }
+    }
+}
+
+impl Settings {
+    fn from_args(matches: &ArgMatches, config: Config) -> Result<Settings, String> {
+        let template = pick(matches, "template", config.template, |s| Ok(s.to_string()))?;
+        let speed = pick(matches, "speed", config.speed, parse_number)?;
+        let zoom = pick(matches, "zoom", config.zoom, parse_number)?;
+        let coords = pick(matches, "coords", config.coords, config::parse_coords)?;
+        let fps = pick(matches, "fps", config.fps, parse_number)?;
+        let country = matches
+            .value_of("country")
+            .map(countries::find)
+            .transpose()?;
+        let coords = country.map(|c| (c.lat, c.lon)).or(coords);
+        // the side of the Earth in daylight, around where it's noon
+        let coords = match matches.is_present("sync-time") {
+            true => Some((0., globe::subsolar_point(SystemTime::now()).1)),
+            false => coords,
+        };
+        let highlight = country
+            .filter(|_| matches.is_present("highlight-country"))
+            .map(|c| c.highlight());
+        let lighting = match matches.value_of("specular") {
+            Some(s) => match s.parse::<Float>() {
+                // water shines more than land
+                Ok(n) if (0. ..=1.).contains(&n) => globe::Lighting {
+                    specular_strength: n / 2.,
+                    ocean_specular_strength: Some(n),
+                    ..Default::default()
+                },
+                _ => {
+                    return Err(format!(
+                        "invalid specular strength `{}`, expected 0 to 1",
+                        s
+                    ))
+                }
+            },
+            None => globe::Lighting::default(),
+        };
+        let refocus = matches
+            .value_of("refocus")
+            .map(parse_duration)
+            .transpose()?
+            .filter(|d| !d.is_zero());
+        let clock = matches
+            .value_of("clock")
+            .map(|zone| Clock::new(zone, matches.is_present("date")))
+            .transpose()?;
+        let highlight_hour = match matches.value_of("highlight-hour") {
+            Some(h) => match h.parse() {
+                Ok(h) if h < 24 => Some(h),
+                _ => return Err(format!("invalid hour `{}`, expected 0 to 23", h)),
+            },
+            None => None,
+        };
+        let mut layer = Layer::default();
+        for path in matches.values_of("geojson").into_iter().flatten() {
+            layer
+                .load_geojson(Path::new(path))
+                .map_err(|e| format!("{}: {}", path, e))?;
+        }
+        let cell_aspect = match matches.value_of("cell-aspect") {
+            Some(s) => match parse_number(s)? {
+                ratio if (0.1..=2.).contains(&ratio) => ratio,
+                _ => return Err(format!("invalid cell aspect `{}`, expected 0.1 to 2", s)),
+            },
+            None => DEFAULT_CELL_ASPECT,
+        };
+        let effects = matches
+            .values_of("effect")
+            .into_iter()
+            .flatten()
+            .map(parse_effect)
+            .collect::<Result<_, _>>()?;
+        let events = match matches.value_of("events") {
+            Some(path) => {
+                events::load_csv(Path::new(path)).map_err(|e| format!("{}: {}", path, e))?
+            }
+            None => Vec::new(),
+        };
+        let event_speed = match matches.value_of("event-speed") {
+            Some(s) => match s.trim_end_matches('x').parse::<Float>() {
+                Ok(n) if n > 0. && n <= MAX_EVENT_SPEED => n,
+                _ => {
+                    return Err(format!(
+                        "invalid event speed `{}`, expected e.g. 1000x, up to {}x",
+                        s, MAX_EVENT_SPEED
+                    ))
+                }
+            },
+            None => 3600.,
+        };
+        let event_fade = match matches.value_of("event-fade") {
+            Some(s) => match s.parse() {
+                Ok(n) if n > 0 && n <= MAX_EVENT_FADE => n,
+                _ => {
+                    return Err(format!(
+                        "invalid event fade `{}`, expected 1 to {} frames",
+                        s, MAX_EVENT_FADE
+                    ))
+                }
+            },
+            None => 30,
+        };
+        let route = match matches.value_of("route") {
+            Some(path) => {
+                Some(route::load_csv(Path::new(path)).map_err(|e| format!("{}: {}", path, e))?)
+            }
+            None => None,
+        };
+        let flow = match matches.value_of("flow") {
+            Some(path) => {
+                Some(flow::load(Path::new(path)).map_err(|e| format!("{}: {}", path, e))?)
+            }
+            None if matches.is_present("flow-demo") => Some(flow::demo()),
+            None => None,
+        };
+        let flight = matches
+            .value_of("flight")
+            .map(flight::Route::parse)
+            .transpose()?;
+        let flight_time = matches
+            .value_of("flight-time")
+            .map(parse_duration)
+            .transpose()?
+            .filter(|d| !d.is_zero())
+            .unwrap_or(Duration::from_secs(10));
+        let palette = pick(matches, "charset", config.charset, config::parse_charset)?;
+        let binds: Vec<&str> = matches.values_of("bind").into_iter().flatten().collect();
+        let bindings = Bindings::new(&config.keys, &binds)?;
+        let size = matches.value_of("size").map(parse_cells).transpose()?;
+        let width = matches.value_of("width").map(parse_cells).transpose()?;
+        let height = matches.value_of("height").map(parse_cells).transpose()?;
+        let width = width.or(size);
+        let height = height.or_else(|| size.map(|s| s.div_ceil(2)));
+        let thumbnail = size
+            .filter(|&s| s < THUMBNAIL_BELOW && matches.is_present("print"))
+            .map(usize::from);
+        let too_small =
+            width.is_some_and(|w| w < MIN_FRAME.0) || height.is_some_and(|h| h < MIN_FRAME.1);
+        if too_small && thumbnail.is_none() {
+            return Err(format!(
+                "frames need at least {}x{} cells",
+                MIN_FRAME.0, MIN_FRAME.1
+            ));
+        }
+        let duration = matches
+            .value_of("duration")
+            .map(parse_duration)
+            .transpose()?;
+        let exit_on = matches.value_of("exit-on").map(ExitOn::parse).transpose()?;
+        let notify = matches.value_of("notify").map(Notify::parse).transpose()?;
+        let arrows = pick(matches, "arrows", config.arrows, |s| Ok(s.to_string()))?;
+        let arrows = arrows.as_deref().map(Arrows::parse).transpose()?;
+        let arrow_speed = pick(matches, "arrow-speed", config.arrow_speed, parse_number)?;
+        let dither = match matches.value_of("dither") {
+            None | Some("none") => Dither::None,
+            Some("ordered") => Dither::Ordered,
+            Some("noise") => Dither::Noise,
+            Some(other) => {
+                return Err(format!(
+                    "unknown dithering `{}`, expected none, ordered or noise",
+                    other
+                ))
+            }
+        };
+        let record = match matches.value_of("record") {
+            Some(path) => {
+                let max_size = matches
+                    .value_of("record-max-size")
+                    .map(parse_size)
+                    .transpose()?;
+                Some((PathBuf::from(path), max_size))
+            }
+            None => None,
+        };
+        let idle_spin = matches
+            .value_of("idle-spin")
+            .map(parse_duration)
+            .transpose()?
+            .filter(|d| !d.is_zero());
+        let tour = match matches.value_of("tour-file") {
+            Some(path) => {
+                Some(tour::load_csv(Path::new(path)).map_err(|e| format!("{}: {}", path, e))?)
+            }
+            None => matches.is_present("tour").then(tour::landmarks),
+        };
+        let resize_delay = matches
+            .value_of("resize-delay")
+            .map(parse_duration)
+            .transpose()?
+            .unwrap_or(Duration::from_millis(100));
+        let wobble = match matches.value_of("wobble") {
+            Some(s) => {
+                let degrees = s
+                    .parse::<Float>()
+                    .ok()
+                    .filter(|d| (0. ..=90.).contains(d))
+                    .ok_or_else(|| format!("invalid wobble `{}`, expected 0 to 90 degrees", s))?;
+                let period = matches
+                    .value_of("wobble-period")
+                    .map(parse_duration)
+                    .transpose()?
+                    .unwrap_or(Duration::from_secs(20));
+                if period.is_zero() {
+                    return Err("the wobble period can't be zero".to_string());
+                }
+                Some((degrees.to_radians(), period)).filter(|_| degrees > 0.)
+            }
+            None => None,
+        };
+        let drag_sensitivity = match matches.value_of("drag-sensitivity") {
+            Some(s) => s
+                .parse::<Float>()
+                .ok()
+                .filter(|n| n.is_finite() && *n > 0.)
+                .ok_or_else(|| format!("invalid drag sensitivity `{}`, expected e.g. 1.5", s))?,
+            None => 1.,
+        };
+        let spin_axis = match matches.value_of("spin-axis") {
+            Some(s) => s
+                .parse::<Float>()
+                .ok()
+                .filter(|d| d.is_finite())
+                .ok_or_else(|| format!("invalid spin axis `{}`, expected degrees", s))?,
+            None => 0.,
+        };
+        let moon = if matches.is_present("with-moon") {
+            let number = |name| {
+                matches
+                    .value_of(name)
+                    .map(|s| {
+                        s.parse::<Float>()
+                            .map_err(|_| format!("invalid number `{}` for --{}", s, name))
+                    })
+                    .transpose()
+            };
+            let period = matches
+                .value_of("moon-period")
+                .map(parse_duration)
+                .transpose()?
+                .unwrap_or(Duration::from_secs(30));
+            if period.is_zero() {
+                return Err("the moon period can't be zero".to_string());
+            }
+            Some(moon::Orbit {
+                distance: number("moon-distance")?,
+                period,
+                inclination: number("moon-inclination")?.unwrap_or(10.).to_radians(),
+            })
+        } else {
+            None
+        };
+
+        let template = template
+            .as_deref()
+            .map_or(Ok(GlobeTemplate::Earth), parse_template)?;
+        let texture = match (
+            matches.value_of("texture"),
+            matches.value_of("texture-geojson"),
+        ) {
+            (Some(path), _) => {
+                Some(fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?)
+            }
+            (None, Some(path)) => Some(geojson_texture(path)?),
+            (None, None) => None,
+        };
+        let pack = matches
+            .value_of("pack")
+            .map(|dir| GlobeConfig::load_pack(Path::new(dir)).map_err(|e| e.to_string()))
+            .transpose()?;
+        // the configuration file can't ask for what the flags rule out
+        let flag_or = |name: &str, conflicts: &[&str], config: Option<bool>| {
+            matches.is_present(name)
+                || config == Some(true) && !conflicts.iter().any(|&c| matches.is_present(c))
+        };
+        let conflicts = ["sixel", "kitty", "anaglyph", "dual", "bounce"];
+        let choice = match matches.value_of("color").map(ColorChoice::parse) {
+            Some(choice) => Some(choice?),
+            None if conflicts.iter().any(|&c| matches.is_present(c)) => None,
+            None => config.color,
+        };
+        let color = choice.is_some_and(|choice| choice.colors(term::no_color()));
+        let color_depth = match matches.value_of("color-depth") {
+            Some(depth) => ColorDepth::parse(depth)?,
+            // asked for, colors are shown even where the terminal says none
+            None if choice == Some(ColorChoice::Always) => {
+                term::color_depth().max(ColorDepth::Ansi16)
+            }
+            None => term::color_depth(),
+        };
+        let city_lights = flag_or("city-lights", &["dual"], config.night);
+        let heightmap = matches
+            .value_of("heightmap")
+            .map(|path| fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e)))
+            .transpose()?;
+        let color_mode = match matches.value_of("color-mode") {
+            None => None,
+            Some("stylized") => Some(ColorMode::Stylized),
+            Some("shade") => Some(ColorMode::Shade),
+            Some("elevation") => {
+                if heightmap.is_none() && pack.as_ref().and_then(GlobeConfig::heightmap).is_none() {
+                    return Err(
+                        "elevation colors need a --heightmap or a --pack with a height texture"
+                            .to_string(),
+                    );
+                }
+                Some(ColorMode::Elevation)
+            }
+            Some(other) => {
+                return Err(format!(
+                    "unknown color mode `{}`, expected stylized, shade or elevation",
+                    other
+                ))
+            }
+        };
+        let elevation_ramp = matches
+            .value_of("elevation-ramp")
+            .map(parse_elevation_ramp)
+            .transpose()?;
+        // only the Earth's own textures have land and ocean to color
+        let color = color.then(|| {
+            color_mode.unwrap_or(match (matches.value_of("texture"), &pack, template) {
+                (None, None, GlobeTemplate::Earth) => ColorMode::Stylized,
+                _ => ColorMode::Shade,
+            })
+        });
+        let theme = match matches.value_of("theme") {
+            Some(name) => config::parse_theme(name)?,
+            None => {
+                // themes of the configuration file may be hard to read
+                let theme = config.theme.unwrap_or_default();
+                let contrast = theme.contrast_report().marker_terrain();
+                if config.theme.is_some() && color.is_some() && contrast < MIN_CONTRAST {
+                    eprintln!(
+                        "warning: markers of the configured theme have a contrast of only {:.1} \
+                         against the terrain, {} or more reads well",
+                        contrast, MIN_CONTRAST
+                    );
+                }
+                theme
+            }
+        };
+        let compare = match matches.value_of("compare") {
+            Some(s) => {
+                let (left, right) = s.split_once(':').ok_or_else(|| {
+                    format!(
+                        "invalid comparison `{}`, expected two templates like earth:moon",
+                        s
+                    )
+                })?;
+                let pane = |name: &str| parse_template(name).map(|t| (name.to_string(), t));
+                Some((pane(left)?, pane(right)?))
+            }
+            None => None,
+        };
+        let zoom = zoom.unwrap_or(2.);
+        if zoom < 1. {
+            return Err("zoom must be at least 1".to_string());
+        }
+        let fps = fps.unwrap_or(10.);
+        if fps <= 0. {
+            return Err("fps must be positive".to_string());
+        }
+        let fps = fps.clamp(MIN_FPS, MAX_FPS);
+        let target_fps = match matches.value_of("target-fps") {
+            Some(s) => parse_number(s)?,
+            None => fps,
+        };
+        if target_fps <= 0. {
+            return Err("target fps must be positive".to_string());
+        }
+        let target_fps = target_fps.clamp(MIN_FPS, MAX_FPS);
+        Ok(Settings {
+            template,
+            texture,
+            speed: speed.unwrap_or(1.),
+            zoom,
+            coords: coords.unwrap_or((0., 0.)),
+            fps,
+            palette: palette
+                .or_else(|| theme.palette.map(<[char]>::to_vec))
+                .or_else(|| {
+                    pack.as_ref()
+                        .and_then(GlobeConfig::palette)
+                        .map(<[char]>::to_vec)
+                })
+                .unwrap_or_else(|| globe::PALETTE.to_vec()),
+            pack,
+            bindings,
+            alt_screen: !matches.is_present("no-altscreen"),
+            // a globe is twice as wide as it is tall in cells
+            width,
+            height,
+            output: Output::Text,
+            clock,
+            layer,
+            timezones: matches.is_present("timezones"),
+            highlight,
+            lighting,
+            refocus,
+            highlight_hour,
+            events,
+            event_speed,
+            event_fade,
+            route,
+            flow,
+            start: Instant::now(),
+            flight,
+            flight_time,
+            follow: matches.is_present("follow"),
+            progress: !matches.is_present("no-progress"),
+            duration,
+            exit_on: exit_on.unwrap_or(ExitOn::Any),
+            notify: notify.unwrap_or(Notify::None),
+            arrows: arrows.unwrap_or(Arrows::Hold),
+            arrow_speed: arrow_speed.unwrap_or(90.),
+            control_stdin: matches.is_present("control-stdin"),
+            low_power: matches.is_present("low-power"),
+            mouse: !matches.is_present("no-mouse"),
+            idle_spin,
+            color,
+            color_depth,
+            theme,
+            heightmap,
+            elevation_ramp,
+            tour,
+            thumbnail,
+            resize_delay,
+            title_coords: matches.is_present("title-coords"),
+            stats: matches.is_present("stats"),
+            drag_sensitivity,
+            classic_drag: matches.is_present("classic-drag"),
+            lock_region: matches
+                .value_of("lock-region")
+                .map(parse_region)
+                .transpose()?,
+            record,
+            resume: matches.is_present("resume"),
+            city_lights,
+            scale_bar: matches.is_present("scale"),
+            dither,
+            adaptive_resolution: matches.is_present("adaptive-resolution"),
+            target_fps: matches.is_present("adaptive-quality").then_some(target_fps),
+            wobble,
+            compare,
+            dual: matches.is_present("dual"),
+            anaglyph: matches.is_present("anaglyph"),
+            spin_axis: spin_axis.to_radians(),
+            tumble: matches.is_present("tumble"),
+            bounce: matches.is_present("bounce"),
+            moon,
+            banner: matches.value_of("banner").map(str::to_string),
+            effects,
+            cell_aspect,
+        })
+    }
+
+    /// Runs the `--effect` passes over a rendered frame.
+    fn apply_effects(&self, canvas: &mut Canvas) {
+        for effect in &self.effects {
+            canvas.apply(effect.as_ref());
+        }
+    }
+
+    /// With `--color`, colors for the cells of a frame `globe` was just
+    /// rendered on.
+    fn colors(&self, globe: &Globe, canvas: &Canvas) -> Option<anaglyph::Colors> {
+        self.color
+            .map(|mode| globe.cell_colors(canvas, mode, &self.theme))
+    }
+
+    /// Time to wait for input between frames.
+    fn frame_time(&self) -> Duration {
+        Duration::from_secs_f32(1. / self.fps)
+    }
+
+    /// Builds the globe to render.
+    fn globe(&self) -> Globe {
+        match (&self.texture, &self.pack) {
+            (Some(texture), _) => self.globe_with(GlobeConfig::new().with_texture(texture)),
+            (None, Some(pack)) => self.globe_with(pack.clone()),
+            (None, None) => self.globe_of(self.template),
+        }
+    }
+
+    /// Globe of another template, with everything else as set.
+    fn globe_of(&self, template: GlobeTemplate) -> Globe {
+        self.globe_with(GlobeConfig::new().use_template(template))
+    }
+
+    /// Globe built from `config` with everything else as set.
+    fn globe_with(&self, mut config: GlobeConfig) -> Globe {
+        if self.timezones {
+            config = config.with_timezone_bands(None);
+        }
+        if self.city_lights {
+            config = config.with_night_texture(GlobeTemplate::EarthNight);
+        }
+        if let Some(heightmap) = &self.heightmap {
+            config = config.with_heightmap(heightmap);
+        }
+        if let Some(ramp) = &self.elevation_ramp {
+            config = config.with_elevation_ramp(ramp.clone());
+        }
+        let mut globe = config
+            .with_theme(self.theme)
+            .dithering(self.dither)
+            .adaptive_resolution(self.adaptive_resolution)
+            .with_scale_bar(self.scale_bar)
+            .build();
+        globe.highlight = self.highlight;
+        globe.lighting = self.lighting;
+        if let Some(text) = &self.banner {
+            let width = (text.chars().count().max(1) - 1) as Float * BANNER_SPACING;
+            globe.add_surface_text(0., self.coords.1 - width / 2., text, BANNER_SPACING);
+        }
+        if let Some((amplitude, period)) = self.wobble {
+            globe.set_wobble(amplitude, period);
+        }
+        let fade = self.frame_time() * self.event_fade;
+        globe.set_events(self.events.clone(), fade.mul_f32(self.event_speed));
+        if let Some(stops) = &self.route {
+            let style = globe::RouteStyle {
+                numbered: true,
+                ..Default::default()
+            };
+            globe.add_route(stops, style);
+        }
+        if let Some(field) = &self.flow {
+            globe.set_flow_field(field.clone(), flow::PARTICLES, flow::LIFETIME);
+        }
+        self.update_globe(&mut globe);
+        globe
+    }
+
+    /// Current tilt of the screensaver spin axis in radians. With `tumble`
+    /// it swings up to 45 degrees either way, once a minute.
+    fn spin_roll(&self) -> Float {
+        if !self.tumble {
+            return self.spin_axis;
+        }
+        let phase = self.start.elapsed().as_secs_f32() / 60.;
+        self.spin_axis + (PI / 4.) * (phase.fract() * 2. * PI).sin()
+    }
+
+    /// Updates the parts of the globe that depend on the current time.
+    fn update_globe(&self, globe: &mut Globe) {
+        globe.dither_seed = globe.dither_seed.wrapping_add(1);
+        globe.advance(self.start.elapsed().saturating_sub(globe.elapsed()));
+        // night textures, from --city-lights or a pack, need the sun
+        if globe.texture_night.is_some() {
+            globe.sun = Some(globe::subsolar_point(SystemTime::now()));
+        }
+        if let (Some(bands), Some(hour)) = (&mut globe.timezone_bands, self.highlight_hour) {
+            let secs = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            let utc_hour = (secs / 3600 % 24) as i32;
+            // offset of the band where it's `hour` o'clock, from -11 to 12
+            let offset = (hour as i32 - utc_hour + 11).rem_euclid(24) - 11;
+            bands.highlight = Some(offset);
+        }
+
+        // replay the events in a loop, starting over once the last one faded
+        if let Some(last) = globe.events.iter().map(|e| e.time).max() {
+            let period = (last + globe.event_fade).as_secs_f64();
+            let elapsed = self.start.elapsed().as_secs_f64() * self.event_speed as f64;
+            let time = Duration::from_secs_f64(elapsed % period);
+            globe.event_time = time;
+        }
+        globe.wobble_time = self.start.elapsed();
+    }
+
+    /// Size of the frame in cells, falling back to the terminal size.
+    fn frame_size(&self, term_size: (u16, u16)) -> (u16, u16) {
+        (
+            self.width.unwrap_or(term_size.0),
+            self.height.unwrap_or(term_size.1),
+        )
+    }
+
+    /// Globe angle and camera tilt showing `coords` in the center.
+    fn initial_view(&self) -> (Float, Float) {
+        let (lat, lon) = self.coords;
+        (-lon.to_radians(), globe::tilt_for_latitude(lat))
+    }
+}
+
+/// Command line arguments with a mode subcommand like `screensaver`
+/// replaced by its flag, so they're parsed and checked the same way. An
+/// unknown subcommand is an error suggesting the closest known one.
+fn mode_args(app: &App) -> Result<Vec<OsString>, String> {
+    const MODES: [(&str, &str); 4] = [
+        ("interactive", "-i"),
+        ("screensaver", "-s"),
+        ("print", "--print"),
+        ("bench", "--bench"),
+    ];
+    let mut args: Vec<OsString> = std::env::args_os().collect();
+    let first = match args.get(1).and_then(|a| a.to_str()) {
+        Some(first) if !first.starts_with('-') => first.to_string(),
+        _ => return Ok(args),
+    };
+    if let Some((_, flag)) = MODES.iter().find(|(name, _)| *name == first) {
+        args[1] = flag.into();
+        return Ok(args);
+    }
+    let names: Vec<&str> = app.get_subcommands().map(|c| c.get_name()).collect();
+    if names.contains(&first.as_str()) {
+        return Ok(args);
+    }
+    let mut e = format!("unknown command `{}`", first);
+    let closest = names.iter().min_by_key(|name| edit_distance(name, &first));
+    if let Some(name) = closest.filter(|name| edit_distance(name, &first) <= 3) {
+        write!(e, ", did you mean `{}`?", name).unwrap();
+    }
+    Err(e)
+}
+
+/// Number of single character insertions, deletions and substitutions
+/// turning `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitute = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Value of a command line option, falling back to the configuration file.
+fn pick<T>(
+    matches: &ArgMatches,
+    name: &str,
+    config: Option<T>,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<Option<T>, String> {
+    match matches.value_of(name) {
+        Some(value) => parse(value).map(Some),
+        None => Ok(config),
+    }
+}
+
+fn parse_template(s: &str) -> Result<GlobeTemplate, String> {
+    GlobeTemplate::ALL
+        .iter()
+        .copied()
+        .find(|t| t.name() == s)
+        .ok_or_else(|| {
+            format!(
+                "unknown template `{}`, expected earth, earth-night or moon",
+                s
+            )
+        })
+}
+
+/// What this build supports, for `--capabilities`: the features of the
+/// binary and the library, and what `--template` and `--theme` take. Keys
+/// are only ever added, so scripts can rely on the ones there.
+fn capabilities() -> Json {
+    let library = globe::capabilities();
+    let names =
+        |names: &[&str]| Json::Array(names.iter().map(|&n| Json::Str(n.to_string())).collect());
+    let mut features = vec!["image-export", "sixel", "kitty", "cities", "countries"];
+    if cfg!(feature = "net") {
+        features.push("net");
+    }
+    features.extend(&library.features);
+    Json::Object(vec![
+        ("version".to_string(), Json::Str(VERSION.to_string())),
+        (
+            "library_version".to_string(),
+            Json::Str(library.version.to_string()),
+        ),
+        ("features".to_string(), names(&features)),
+        ("templates".to_string(), names(&library.templates)),
+        ("themes".to_string(), names(&library.themes)),
+    ])
+}
+
+/// Rasterizes the polygons of a `--texture-geojson` file into a texture of
+/// the same size as the built-in ones, land bright on a dim ocean.
+fn geojson_texture(path: &str) -> Result<String, String> {
+    let file = fs::File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+    let texture = globe::texture_from_geojson(io::BufReader::new(file), 200, 80, '@', ':')
+        .map_err(|e| format!("{}: {}", path, e))?;
+    let rows: Vec<String> = texture.iter().map(|row| row.iter().collect()).collect();
+    Ok(rows.join("\n"))
+}
+
+/// Parses a `--lock-region` like `30,-130:55,-60`, the south west and north
+/// east corners.
+fn parse_region(s: &str) -> Result<globe::Bounds, String> {
+    let (min, max) = s.split_once(':').ok_or_else(|| {
+        format!(
+            "invalid region `{}`, expected corners like 30,-130:55,-60",
+            s
+        )
+    })?;
+    let (min, max) = (config::parse_coords(min)?, config::parse_coords(max)?);
+    if min.0 > max.0 {
+        return Err(format!(
+            "invalid region `{}`, the first corner must be the southern one",
+            s
+        ));
+    }
+    Ok(globe::Bounds { min, max })
+}
+
+/// Parses an `--elevation-ramp` like `0:#32a041,3000:#f5f5f5`, heights in
+/// meters and their colors.
+fn parse_elevation_ramp(s: &str) -> Result<ElevationRamp, String> {
+    let stop = |stop: &str| -> Option<(Float, Color)> {
+        let (height, color) = stop.split_once(':')?;
+        let height = parse_number(height.trim()).ok()?;
+        Some((height, config::parse_color(color.trim())?))
+    };
+    let stops = s.split(',').map(stop).collect::<Option<_>>();
+    stops.and_then(ElevationRamp::new).ok_or_else(|| {
+        format!(
+            "invalid elevation ramp `{}`, expected heights in meters and colors like \
+             0:#32a041,3000:#f5f5f5",
+            s
+        )
+    })
+}
+
+fn parse_effect(s: &str) -> Result<Box<dyn Effect>, String> {
+    match s {
+        "scanlines" => Ok(Box::new(effects::Scanlines)),
+        "vignette" => Ok(Box::new(effects::Vignette::default())),
+        "glow" => Ok(Box::new(effects::Glow)),
+        other => Err(format!(
+            "unknown effect `{}`, expected scanlines, vignette or glow",
+            other
+        )),
+    }
+}
+
+/// Parses a number, rejecting NaN and infinities, which no option takes.
+fn parse_number(s: &str) -> Result<Float, String> {
+    s.parse::<Float>()
+        .ok()
+        .filter(|n| n.is_finite())
+        .ok_or_else(|| format!("invalid number `{}`", s))
+}
+
+/// Parses a size in terminal cells.
+fn parse_cells(s: &str) -> Result<u16, String> {
+    match s.parse() {
+        Ok(n) if (1..=4096).contains(&n) => Ok(n),
+        _ => Err(format!("invalid size `{}`, expected 1 to 4096 cells", s)),
+    }
+}
+
+/// Parses a size in bytes with an optional K, M or G suffix for binary
+/// kilobytes, megabytes and gigabytes, e.g. `100M`.
+fn parse_size(s: &str) -> Result<u64, String> {
+    let (number, unit) = match s.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((i, _)) => s.split_at(i),
+        None => (s, ""),
+    };
+    let unit: u64 = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        _ => return Err(format!("invalid size `{}`, expected e.g. 100M", s)),
+    };
+    match number.parse::<u64>() {
+        Ok(n) if n > 0 => n
+            .checked_mul(unit)
+            .ok_or_else(|| format!("size `{}` is too large", s)),
+        _ => Err(format!("invalid size `{}`, expected e.g. 100M", s)),
+    }
+}
+
+/// Parses a duration such as `90s`, `10m` or `1h30m`. Plain numbers are
+/// seconds.
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration `{}`, expected e.g. 30s, 10m or 1h30m", s);
+    if let Ok(secs) = s.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+    let mut total = Duration::default();
+    let mut rest = s;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let n: u64 = rest[..digits].parse().map_err(|_| invalid())?;
+        rest = &rest[digits..];
+        let unit = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        total += match &rest[..unit] {
+            "ms" => Duration::from_millis(n),
+            "s" => Duration::from_secs(n),
+            "m" | "min" => Duration::from_secs(n * 60),
+            "h" => Duration::from_secs(n * 3600),
+            "d" => Duration::from_secs(n * 86400),
+            _ => return Err(invalid()),
+        };
+        rest = &rest[unit..];
+    }
+    Ok(total)
+}
+
+/// The command line interface, with `control_help` describing
+/// `--control-stdin`.
+fn cli(control_help: &str) -> App<'_> {
     let app = App::new("globe-cli")
         .version(VERSION)
         .author(AUTHORS)
         .setting(AppSettings::ArgRequiredElseHelp)
         .about("Render an ASCII globe in your terminal.")
-        .arg(Arg::new("interactive").short('i'))
-        .arg(Arg::new("screensaver").short('s'));
-    let matches = app.get_matches();
-    if matches.is_present("interactive") {
-        start_interactive();
-    } else if matches.is_present("screensaver") {
-        start_screensaver();
-    }
+        .arg(
+            Arg::new("interactive")
+                .short('i')
+                .help("Pan and zoom the globe with the mouse or keyboard"),
+        )
+        .arg(
+            Arg::new("screensaver")
+                .short('s')
+                .help("Let the globe spin until a key is pressed or the mouse is used"),
+        )
+        .arg(
+            Arg::new("arrows")
+                .long("arrows")
+                .takes_value(true)
+                .value_name("MODE")
+                .requires("interactive")
+                .help(
+                    "How held arrow keys move the view: hold to turn smoothly, step for a step \
+                     per repeat [default: hold]",
+                ),
+        )
+        .arg(
+            Arg::new("arrow-speed")
+                .long("arrow-speed")
+                .takes_value(true)
+                .value_name("DEGREES")
+                .requires("interactive")
+                .help("Degrees per second held arrow keys turn the view by [default: 90]"),
+        )
+        .arg(
+            Arg::new("idle-spin")
+                .long("idle-spin")
+                .takes_value(true)
+                .value_name("TIME")
+                .requires("interactive")
+                .help("Start spinning after TIME without input in interactive mode [default: 0, never]"),
+        )
+        .arg(
+            Arg::new("tour")
+                .long("tour")
+                .requires("interactive")
+                .help("Fly from landmark to landmark until a key is pressed"),
+        )
+        .arg(
+            Arg::new("tour-file")
+                .long("tour-file")
+                .takes_value(true)
+                .value_name("FILE")
+                .requires("tour")
+                .help("Tour the places in a CSV file of name, latitude, longitude and dwell time"),
+        )
+        .arg(
+            Arg::new("resize-delay")
+                .long("resize-delay")
+                .takes_value(true)
+                .value_name("TIME")
+                .requires("live")
+                .help(
+                    "Wait until the terminal size stayed the same for TIME, e.g. 250ms, before \
+                     drawing at a new size [default: 100ms, 0 draws every size]",
+                ),
+        )
+        .arg(
+            Arg::new("resume")
+                .long("resume")
+                .requires("interactive")
+                .help("Start interactive mode where the last --resume session was left, and save \
+                       the view on exit"),
+        )
+        .arg(
+            Arg::new("duration")
+                .long("duration")
+                .takes_value(true)
+                .value_name("TIME")
+                .requires("screensaver")
+                .help("Exit the screensaver by itself after TIME, e.g. 30s, 10m or 1h30m"),
+        )
+        .arg(
+            Arg::new("low-power")
+                .long("low-power")
+                .requires("screensaver")
+                .help("Only draw a new screensaver frame once the globe turned by a character"),
+        )
+        .arg(
+            Arg::new("exit-on")
+                .long("exit-on")
+                .takes_value(true)
+                .value_name("INPUT")
+                .requires("screensaver")
+                .help(
+                    "What ends the screensaver: any (key or mouse event), char \
+                     (character keys) or q [default: any]",
+                ),
+        )
+        .arg(
+            Arg::new("notify")
+                .long("notify")
+                .takes_value(true)
+                .value_name("KIND")
+                .help(
+                    "How to tell that the screensaver ran out its --duration or an export, \
+                     --print or --bench finished: bell, title or none [default: none]",
+                ),
+        )
+        .arg(
+            Arg::new("wobble")
+                .long("wobble")
+                .takes_value(true)
+                .value_name("DEGREES")
+                .help("Swing the camera tilt up and down by up to DEGREES"),
+        )
+        .arg(
+            Arg::new("wobble-period")
+                .long("wobble-period")
+                .takes_value(true)
+                .value_name("TIME")
+                .requires("wobble")
+                .help("How long a swing of --wobble takes [default: 20s]"),
+        )
+        .arg(
+            Arg::new("spin-axis")
+                .long("spin-axis")
+                .takes_value(true)
+                .value_name("DEGREES")
+                .allow_hyphen_values(true)
+                .requires("screensaver")
+                .help("Tilt the screensaver spin axis clockwise from vertical"),
+        )
+        .arg(
+            Arg::new("tumble")
+                .long("tumble")
+                .requires("screensaver")
+                .help("Slowly swing the screensaver spin axis from side to side"),
+        )
+        .arg(
+            Arg::new("bounce")
+                .long("bounce")
+                .requires("screensaver")
+                .conflicts_with_all(&["sixel", "kitty", "anaglyph"])
+                .help("Shrink the screensaver globe and bounce it off the terminal edges"),
+        )
+        .arg(
+            Arg::new("banner")
+                .long("banner")
+                .takes_value(true)
+                .value_name("TEXT")
+                .conflicts_with_all(&["sixel", "kitty"])
+                .help("Write TEXT around the equator, centered on the initial view"),
+        )
+        .arg(
+            Arg::new("cell-aspect")
+                .long("cell-aspect")
+                .takes_value(true)
+                .value_name("RATIO")
+                .conflicts_with_all(&["sixel", "kitty"])
+                .help(
+                    "Width of a terminal cell over its height, for fonts where the globe \
+                     isn't round [default: 0.5]",
+                ),
+        )
+        .arg(
+            Arg::new("color")
+                .long("color")
+                .takes_value(true)
+                .value_name("WHEN")
+                .min_values(0)
+                .require_equals(true)
+                .default_missing_value("auto")
+                .requires("live")
+                .conflicts_with_all(&["sixel", "kitty", "anaglyph", "dual", "bounce"])
+                .help(
+                    "Color the globe: ocean, land and ice by latitude on the Earth, shades of \
+                     gray otherwise. WHEN is always, never or auto, which leaves colors out \
+                     if NO_COLOR is set [default: auto]",
+                ),
+        )
+        .arg(
+            Arg::new("color-depth")
+                .long("color-depth")
+                .takes_value(true)
+                .value_name("DEPTH")
+                .requires("live")
+                .help(
+                    "Colors the terminal shows: truecolor, 256, 16 or mono for none \
+                     [default: from COLORTERM and TERM]",
+                ),
+        )
+        .arg(
+            Arg::new("color-mode")
+                .long("color-mode")
+                .takes_value(true)
+                .value_name("MODE")
+                .requires("color")
+                .help(
+                    "What --color shows: stylized land, ocean and ice, shade for shades of \
+                     gray, or elevation for the heights of a --heightmap from blue sea \
+                     through green and brown to white peaks [default: stylized on the \
+                     Earth, shade otherwise]",
+                ),
+        )
+        .arg(
+            Arg::new("heightmap")
+                .long("heightmap")
+                .takes_value(true)
+                .value_name("PATH")
+                .help(
+                    "Heights for --color-mode elevation: an equirectangular map like \
+                     --texture, where each digit is that many thousands of meters above sea \
+                     level and anything else is sea",
+                ),
+        )
+        .arg(
+            Arg::new("elevation-ramp")
+                .long("elevation-ramp")
+                .takes_value(true)
+                .value_name("STOPS")
+                .allow_hyphen_values(true)
+                .requires("color-mode")
+                .help(
+                    "Colors of --color-mode elevation at heights in meters, blended in \
+                     between, e.g. -6000:#0f2d7d,0:#328741,4500:#f5f5f5",
+                ),
+        )
+        .arg(
+            Arg::new("theme")
+                .long("theme")
+                .takes_value(true)
+                .value_name("NAME")
+                .help(
+                    "Colors of --color and shading characters: default, classic, matrix, \
+                     amber, mono, natural, or cb-warm or cb-cool, which tell land from ocean \
+                     without red and green for color blindness [default: the [theme] of the \
+                     configuration file, or default]",
+                ),
+        )
+        .arg(
+            Arg::new("effect")
+                .long("effect")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .value_name("NAME")
+                .conflicts_with_all(&["sixel", "kitty"])
+                .help("Post-process frames with scanlines, vignette or glow; can be repeated"),
+        )
+        .arg(
+            Arg::new("with-moon")
+                .long("with-moon")
+                .requires("screensaver")
+                .conflicts_with_all(&["sixel", "kitty"])
+                .help("Have a moon orbit the globe in the screensaver"),
+        )
+        .arg(
+            Arg::new("moon-period")
+                .long("moon-period")
+                .takes_value(true)
+                .value_name("TIME")
+                .requires("with-moon")
+                .help("How long an orbit of the moon takes [default: 30s]"),
+        )
+        .arg(
+            Arg::new("moon-distance")
+                .long("moon-distance")
+                .takes_value(true)
+                .value_name("CELLS")
+                .requires("with-moon")
+                .help("Radius of the moon's orbit in cells [default: fits the frame]"),
+        )
+        .arg(
+            Arg::new("moon-inclination")
+                .long("moon-inclination")
+                .takes_value(true)
+                .value_name("DEGREES")
+                .allow_hyphen_values(true)
+                .requires("with-moon")
+                .help("Tilt of the moon's orbit towards the viewer [default: 10]"),
+        )
+        .arg(
+            Arg::new("iss")
+                .long("iss")
+                .help("Follow the International Space Station"),
+        )
+        .arg(
+            Arg::new("compare")
+                .long("compare")
+                .takes_value(true)
+                .value_name("A:B")
+                .conflicts_with_all(&["iss", "flight", "interactive", "print", "bench"])
+                .conflicts_with_all(&["sixel", "kitty"])
+                .help("Show two templates side by side, e.g. earth:moon"),
+        )
+        .arg(
+            Arg::new("dual")
+                .long("dual")
+                .requires("interactive")
+                .conflicts_with_all(&["city-lights", "sixel", "kitty"])
+                .help("Show the view twice side by side, the right one lit by the sun as it is now"),
+        )
+        .arg(
+            Arg::new("anaglyph")
+                .long("anaglyph")
+                .requires("live")
+                .conflicts_with_all(&["dual", "sixel", "kitty"])
+                .help("Draw the globe in red and cyan 3D, for red/cyan glasses"),
+        )
+        .arg(
+            Arg::new("flight")
+                .long("flight")
+                .takes_value(true)
+                .value_name("ROUTE")
+                .allow_hyphen_values(true)
+                .conflicts_with_all(&["iss", "interactive", "print", "bench"])
+                .help("Fly a plane between two airports like JFK-LHR, or LAT,LON:LAT,LON"),
+        )
+        .arg(
+            Arg::new("flight-time")
+                .long("flight-time")
+                .takes_value(true)
+                .value_name("TIME")
+                .requires("flight")
+                .help("How long a flight takes, e.g. 30s [default: 10s]"),
+        )
+        .arg(
+            Arg::new("follow")
+                .long("follow")
+                .requires("flight")
+                .help("Keep the plane in the center of the view"),
+        )
+        .arg(
+            Arg::new("no-progress")
+                .long("no-progress")
+                .requires("flight")
+                .help("Don't draw the ring around the globe showing the flight's progress"),
+        )
+        .arg(Arg::new("offline").long("offline").help(
+            "Only use the bundled ISS orbital elements (always on without the `net` feature)",
+        ))
+        .arg(
+            Arg::new("print")
+                .long("print")
+                .help("Print a single frame to stdout and exit, works without a terminal"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .takes_value(true)
+                .value_name("FORMAT")
+                .requires("headless")
+                .help(
+                    "Output of --print: text, or json for one object per frame and line with \
+                     `rows` (array of strings), `width` and `height` in characters, the view \
+                     center `tilt` (latitude) and `angle` (longitude) in degrees, camera \
+                     `zoom`, `frame` number and Unix `timestamp` [default: text]. With \
+                     --bench, json gives an array with `size`, `frames`, `fps`, `p50_ms` and \
+                     `p99_ms` per canvas size",
+                ),
+        )
+        .arg(
+            Arg::new("frames")
+                .long("frames")
+                .takes_value(true)
+                .value_name("N")
+                .requires("headless")
+                .help(
+                    "Print N frames of a turning globe, render N per size with --bench, or \
+                     put N on the sprite sheet [default: 1, 500 with --bench]",
+                ),
+        )
+        .arg(
+            Arg::new("bench")
+                .long("bench")
+                .conflicts_with("print")
+                .help(
+                    "Time rendering at a few canvas sizes without drawing, and print the results",
+                ),
+        )
+        .arg(
+            Arg::new("export-sheet")
+                .long("export-sheet")
+                .takes_value(true)
+                .value_name("PATH")
+                .conflicts_with_all(&["format", "sixel", "kitty"])
+                .help(
+                    "Render a full turn of the globe as a grid of --frames images --size \
+                     pixels square, and write it to PATH as a PNG sprite sheet with its layout \
+                     in a .json file next to it [default: 36 frames of 64 pixels]",
+                ),
+        )
+        .arg(
+            Arg::new("columns")
+                .long("columns")
+                .takes_value(true)
+                .value_name("N")
+                .requires("export-sheet")
+                .help("Frames per row of the sprite sheet [default: as square as it gets]"),
+        )
+        .arg(
+            Arg::new("glyphs")
+                .long("glyphs")
+                .requires("export-sheet")
+                .help("Draw sprite sheet frames as characters in an 8x8 pixel font"),
+        )
+        .arg(
+            Arg::new("glyph-scale")
+                .long("glyph-scale")
+                .takes_value(true)
+                .value_name("N")
+                .requires("glyphs")
+                .help("Pixels across each pixel of a --glyphs character [default: 1]"),
+        )
+        .group(ArgGroup::new("headless").args(&["print", "bench", "export-sheet"]))
+        .group(
+            ArgGroup::new("live")
+                .args(&["interactive", "screensaver"])
+                .multiple(true),
+        )
+        .arg(
+            Arg::new("record")
+                .long("record")
+                .takes_value(true)
+                .value_name("PATH")
+                .requires("live")
+                .help("Record the frames to PATH as an asciicast for `asciinema play`"),
+        )
+        .arg(
+            Arg::new("record-max-size")
+                .long("record-max-size")
+                .takes_value(true)
+                .value_name("SIZE")
+                .requires("record")
+                .help("Stop recording once the file would grow past SIZE, e.g. 100M"),
+        )
+        .arg(
+            Arg::new("watch-file")
+                .long("watch-file")
+                .takes_value(true)
+                .value_name("PATH")
+                .conflicts_with_all(&["interactive", "screensaver", "headless"])
+                .conflicts_with_all(&["iss", "flight", "compare"])
+                .help(
+                    "Keep rewriting PATH with a frame of the turning globe, for status bars; \
+                     works without a terminal",
+                ),
+        )
+        .arg(
+            Arg::new("interval")
+                .long("interval")
+                .takes_value(true)
+                .value_name("TIME")
+                .requires("watch-file")
+                .help("How often --watch-file is rewritten [default: 2s]"),
+        )
+        .arg(
+            Arg::new("template")
+                .long("template")
+                .takes_value(true)
+                .value_name("NAME")
+                .help("Built-in globe to display: earth, earth-night or moon [default: earth]"),
+        )
+        .arg(
+            Arg::new("texture")
+                .long("texture")
+                .takes_value(true)
+                .value_name("PATH")
+                .conflicts_with("template")
+                .help(
+                    "Display a texture from a file instead: an equirectangular map as rows of \
+                     shading characters, from the poles at the top and bottom",
+                ),
+        )
+        .arg(
+            Arg::new("texture-geojson")
+                .long("texture-geojson")
+                .takes_value(true)
+                .value_name("FILE")
+                .conflicts_with_all(&["template", "texture"])
+                .help(
+                    "Display the Polygon and MultiPolygon features of a GeoJSON file as land on \
+                     an empty ocean",
+                ),
+        )
+        .arg(
+            Arg::new("pack")
+                .long("pack")
+                .takes_value(true)
+                .value_name("DIR")
+                .conflicts_with_all(&["template", "texture", "texture-geojson"])
+                .help("Display a texture pack: a directory of textures described by its pack.toml"),
+        )
+        .arg(
+            Arg::new("flat")
+                .long("flat")
+                .conflicts_with_all(&["interactive", "screensaver", "bench", "frames", "format"])
+                .conflicts_with_all(&["iss", "flight", "compare", "watch-file", "sixel", "kitty"])
+                .help("Show the texture as a flat map, e.g. to check a --texture"),
+        )
+        .arg(
+            Arg::new("graticule")
+                .long("graticule")
+                .requires("flat")
+                .help("Draw meridians and parallels every 30\u{b0} on the --flat map"),
+        )
+        .arg(
+            Arg::new("speed")
+                .long("speed")
+                .takes_value(true)
+                .value_name("N")
+                .help("Screensaver rotation speed multiplier [default: 1]"),
+        )
+        .arg(
+            Arg::new("zoom")
+                .long("zoom")
+                .takes_value(true)
+                .value_name("N")
+                .help("Camera distance from the globe center, at least 1 [default: 2]"),
+        )
+        .arg(
+            Arg::new("coords")
+                .long("coords")
+                .takes_value(true)
+                .value_name("LAT,LON")
+                .allow_hyphen_values(true)
+                .help("Initial view center in degrees [default: 0,0]"),
+        )
+        .arg(
+            Arg::new("country")
+                .long("country")
+                .takes_value(true)
+                .value_name("CODE")
+                .conflicts_with("coords")
+                .help("Center the view on a country, by ISO code like JP or by name"),
+        )
+        .arg(
+            Arg::new("sync-time")
+                .long("sync-time")
+                .conflicts_with_all(&["coords", "country"])
+                .help("Center the view on the longitude where it's noon right now"),
+        )
+        .arg(
+            Arg::new("highlight-country")
+                .long("highlight-country")
+                .requires("country")
+                .help("Shade the rough extent of the --country brighter"),
+        )
+        .arg(
+            Arg::new("specular")
+                .long("specular")
+                .takes_value(true)
+                .value_name("STRENGTH")
+                .help(
+                    "Add a glossy highlight where the light reflects towards you, from 0 to 1, \
+                     half as strong on land",
+                ),
+        )
+        .arg(
+            Arg::new("refocus")
+                .long("refocus")
+                .takes_value(true)
+                .value_name("TIME")
+                .requires_all(&["country", "screensaver"])
+                .help("Turn the screensaver back to the --country every TIME"),
+        )
+        .arg(
+            Arg::new("fps")
+                .long("fps")
+                .takes_value(true)
+                .value_name("N")
+                .help("Frames per second, kept between 0.1 and 1000 [default: 10]"),
+        )
+        .arg(
+            Arg::new("size")
+                .long("size")
+                .takes_value(true)
+                .value_name("N")
+                .conflicts_with_all(&["width", "height"])
+                .help("Draw a globe N characters across instead of filling the terminal"),
+        )
+        .arg(
+            Arg::new("width")
+                .long("width")
+                .takes_value(true)
+                .value_name("N")
+                .help("Frame width in characters [default: terminal width]"),
+        )
+        .arg(
+            Arg::new("height")
+                .long("height")
+                .takes_value(true)
+                .value_name("N")
+                .help("Frame height in characters [default: terminal height]"),
+        )
+        .arg(
+            Arg::new("clock")
+                .long("clock")
+                .takes_value(true)
+                .value_name("ZONE")
+                .help(
+                    "Show the time in the top right corner: utc, local or a zone like Europe/Paris",
+                ),
+        )
+        .arg(
+            Arg::new("date")
+                .long("date")
+                .requires("clock")
+                .help("Show the date next to the clock"),
+        )
+        .arg(
+            Arg::new("geojson")
+                .long("geojson")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .value_name("FILE")
+                .help("Draw the points and lines of a GeoJSON file on the globe"),
+        )
+        .arg(
+            Arg::new("events")
+                .long("events")
+                .takes_value(true)
+                .value_name("FILE")
+                .help(
+                    "Replay events such as earthquakes from a CSV file with time, latitude, \
+                     longitude and magnitude columns",
+                ),
+        )
+        .arg(
+            Arg::new("event-speed")
+                .long("event-speed")
+                .takes_value(true)
+                .value_name("N")
+                .requires("events")
+                .help("How many times faster than real time events are replayed [default: 3600x]"),
+        )
+        .arg(
+            Arg::new("event-fade")
+                .long("event-fade")
+                .takes_value(true)
+                .value_name("FRAMES")
+                .requires("events")
+                .help("Number of frames events take to fade out [default: 30]"),
+        )
+        .arg(
+            Arg::new("route")
+                .long("route")
+                .takes_value(true)
+                .value_name("FILE")
+                .help(
+                    "Draw a route along great circles through the numbered stops of a CSV file \
+                     with latitude and longitude columns",
+                ),
+        )
+        .arg(
+            Arg::new("flow")
+                .long("flow")
+                .takes_value(true)
+                .value_name("FILE")
+                .conflicts_with("flow-demo")
+                .help(
+                    "Draw particles drifting over the ocean along a grid of wind or current \
+                     vectors, a row per line with eastward,northward pairs",
+                ),
+        )
+        .arg(
+            Arg::new("flow-demo")
+                .long("flow-demo")
+                .help("Draw particles drifting along idealized trade winds"),
+        )
+        .arg(
+            Arg::new("city-lights")
+                .long("city-lights")
+                .help("Light the globe by the sun as it is now, with city lights at night"),
+        )
+        .arg(
+            Arg::new("scale")
+                .long("scale")
+                .help("Draw a scale bar and label the longitudes where the equator meets the limb"),
+        )
+        .arg(
+            Arg::new("assume-dark")
+                .long("assume-dark")
+                .conflicts_with("assume-light")
+                .help("Shade for a dark terminal background without asking the terminal"),
+        )
+        .arg(
+            Arg::new("assume-light")
+                .long("assume-light")
+                .help("Shade for a light terminal background without asking the terminal"),
+        )
+        .arg(
+            Arg::new("dither")
+                .long("dither")
+                .takes_value(true)
+                .value_name("MODE")
+                .help(
+                    "Mix shading characters to smooth out banding: none, ordered (a fixed \
+                     pattern) or noise (moving grain) [default: none]",
+                ),
+        )
+        .arg(
+            Arg::new("adaptive-resolution")
+                .long("adaptive-resolution")
+                .help(
+                    "Render the globe in larger blocks while it's small on screen, which is \
+                     faster",
+                ),
+        )
+        .arg(
+            Arg::new("adaptive-quality")
+                .long("adaptive-quality")
+                .requires("live")
+                .help(
+                    "Lower the render quality while frames take too long for --target-fps, \
+                     first dropping --dither, then rendering larger blocks, and raise it \
+                     again once they're fast enough",
+                ),
+        )
+        .arg(
+            Arg::new("target-fps")
+                .long("target-fps")
+                .takes_value(true)
+                .value_name("N")
+                .requires("adaptive-quality")
+                .help("Frame rate --adaptive-quality holds [default: --fps]"),
+        )
+        .arg(
+            Arg::new("timezones")
+                .long("timezones")
+                .help("Shade time zones as alternating 15\u{b0} bands"),
+        )
+        .arg(
+            Arg::new("highlight-hour")
+                .long("highlight-hour")
+                .takes_value(true)
+                .value_name("HOUR")
+                .requires("timezones")
+                .help("Emphasize the time zone where it's currently HOUR o'clock"),
+        )
+        .arg(
+            Arg::new("charset")
+                .long("charset")
+                .takes_value(true)
+                .value_name("NAME")
+                .help(
+                    "Shading characters: ascii, unicode, blocks, braille or \
+                     custom:<chars> from dark to bright [default: ascii]",
+                ),
+        )
+        .arg(
+            Arg::new("bind")
+                .long("bind")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .value_name("ACTION=KEY")
+                .help("Bind a key in interactive mode, e.g. `--bind zoom_in=+`"),
+        )
+        .arg(
+            Arg::new("control-stdin")
+                .long("control-stdin")
+                .conflicts_with_all(&["iss", "print"])
+                .help(control_help),
+        )
+        .arg(
+            Arg::new("no-mouse")
+                .long("no-mouse")
+                .help("Don't capture the mouse, leaving it to the terminal"),
+        )
+        .arg(
+            Arg::new("drag-sensitivity")
+                .long("drag-sensitivity")
+                .takes_value(true)
+                .value_name("N")
+                .requires("interactive")
+                .help("How far dragging turns the globe; at 1 the surface follows the pointer [default: 1]"),
+        )
+        .arg(
+            Arg::new("classic-drag")
+                .long("classic-drag")
+                .requires("interactive")
+                .conflicts_with("drag-sensitivity")
+                .help("Drag like older versions: fixed steps, and vertical drags also turn the globe"),
+        )
+        .arg(
+            Arg::new("lock-region")
+                .long("lock-region")
+                .takes_value(true)
+                .value_name("LAT,LON:LAT,LON")
+                .allow_hyphen_values(true)
+                .requires("interactive")
+                .help(
+                    "Keep the view center between a south west and a north east corner, until \
+                     unlocked with the unlock key",
+                ),
+        )
+        .arg(
+            Arg::new("stats")
+                .long("stats")
+                .requires("live")
+                .help(
+                    "Show the measured frame rate in the top left corner, the render \
+                     resolution with --adaptive-resolution and the quality level with \
+                     --adaptive-quality, and below them the rays, texture samples and time \
+                     the last render took",
+                ),
+        )
+        .arg(
+            Arg::new("title-coords")
+                .long("title-coords")
+                .conflicts_with("headless")
+                .help("Show the coordinates of the view center in the window title"),
+        )
+        .arg(
+            Arg::new("no-altscreen")
+                .long("no-altscreen")
+                .help("Draw on the main screen and keep the last frame after exiting"),
+        )
+        .arg(
+            Arg::new("sixel")
+                .long("sixel")
+                .help("Draw the globe as sixel images instead of characters"),
+        )
+        .arg(
+            Arg::new("kitty")
+                .long("kitty")
+                .conflicts_with("sixel")
+                .help("Draw the globe with the kitty graphics protocol instead of characters"),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .requires("sixel")
+                .help("Use sixel images even if the terminal doesn't report support"),
+        )
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .takes_value(true)
+                .value_name("FILE")
+                .help("Read the configuration from FILE instead of the default location"),
+        )
+        .arg(
+            Arg::new("no-config")
+                .long("no-config")
+                .conflicts_with("config")
+                .help("Don't read the configuration file"),
+        )
+        .arg(
+            Arg::new("capabilities")
+                .long("capabilities")
+                .help(
+                    "Print what this build supports as JSON: its version, features, templates \
+                     and themes",
+                ),
+        );
+    let app = app
+        .subcommand(App::new("interactive").about("Same as -i"))
+        .subcommand(App::new("screensaver").about("Same as -s"))
+        .subcommand(App::new("print").about("Same as --print"))
+        .subcommand(App::new("bench").about("Same as --bench"))
+        .subcommand(
+            App::new("locations")
+                .about("List known places and their coordinates, for --coords and --flight")
+                .arg(Arg::new("query").help("Only list places whose name or code contains QUERY")),
+        )
+        .subcommand(
+            App::new("completions")
+                .about("Print a shell completion script")
+                .arg(
+                    Arg::new("shell")
+                        .required(true)
+                        .possible_values(completions::SHELLS),
+                ),
+        );
+    #[cfg(feature = "net")]
+    let app = app.arg(
+        Arg::new("iss-url")
+            .long("iss-url")
+            .takes_value(true)
+            .value_name("URL")
+            .help("Where to fetch ISS orbital elements (TLE) from"),
+    );
+    app
 }
 
-fn start_screensaver() {
-    terminal::enable_raw_mode().unwrap();
+fn main() {
+    let control_help = format!(
+        "Read commands from stdin while rendering, one per line: {}",
+        control::USAGE
+    );
+    let mut app = cli(&control_help);
+    let args = match mode_args(&app) {
+        Ok(args) => args,
+        Err(e) => app.error(ErrorKind::UnrecognizedSubcommand, e).exit(),
+    };
+    let matches = app
+        .try_get_matches_from_mut(args)
+        .unwrap_or_else(|e| e.exit());
+    if matches.is_present("capabilities") {
+        println!("{}", capabilities());
+        return;
+    }
+    match matches.subcommand() {
+        Some(("locations", sub)) => {
+            let places = locations::search(sub.value_of("query").unwrap_or(""));
+            for place in &places {
+                println!(
+                    "{}  {:<14} {},{}",
+                    place.code, place.name, place.lat, place.lon
+                );
+            }
+            std::process::exit(if places.is_empty() { 1 } else { 0 });
+        }
+        Some(("completions", sub)) => {
+            let shell = sub.value_of("shell").unwrap();
+            print!("{}", completions::generate(&app, shell).unwrap());
+            return;
+        }
+        _ => {}
+    }
 
-    let mut stdout = stdout();
-    stdout.execute(cursor::Hide).unwrap();
-    stdout.execute(cursor::DisableBlinking).unwrap();
-
-    let mut globe = GlobeConfig::new()
-        .use_template(GlobeTemplate::Earth)
-        .build();
-    let mut term_size = terminal::size().unwrap();
-    let mut canvas = if term_size.0 > term_size.1 {
-        Canvas::new(term_size.1 * 8, term_size.1 * 8, None)
+    let mut settings = match Settings::from_args(&matches, load_config(&matches)) {
+        Ok(settings) => settings,
+        Err(e) => app.error(ErrorKind::InvalidValue, e).exit(),
+    };
+    if settings.resume {
+        resume_session(&mut settings, &matches);
+    }
+
+    // headless output may not even go to a terminal
+    let live = !matches.is_present("headless") && !matches.is_present("watch-file");
+    let background = if matches.is_present("assume-light") {
+        Background::Light
+    } else if matches.is_present("assume-dark") || !live {
+        Background::Dark
     } else {
-        Canvas::new(term_size.0 * 4, term_size.0 * 4, None)
+        term::background().unwrap_or(Background::Dark)
     };
+    if background == Background::Light {
+        settings.palette = light_palette(&settings.palette);
+    }
 
-    let cam_zoom = 2.;
-    let cam_xy = 0.;
-    let cam_z = 0.;
-    globe.camera = Camera::new(cam_zoom, cam_xy, cam_z);
-
-    loop {
-        if poll(Duration::from_millis(100)).unwrap() {
-            match read().unwrap() {
-                Event::Key(event) => {
-                    // pressing any char key exists the program
-                    if let KeyCode::Char(_) = event.code {
-                        break;
-                    }
-                }
-                Event::Resize(width, height) => {
-                    term_size = (width, height);
-                    canvas = if width > height {
-                        Canvas::new(height * 8, height * 8, None)
-                    } else {
-                        Canvas::new(width * 4, width * 4, None)
-                    };
-                }
-                Event::Mouse(_) => {}
+    if matches.is_present("flat") {
+        let graticule = matches.is_present("graticule");
+        match matches.is_present("print") {
+            true => flat::print(&settings, graticule),
+            false => flat::show(&settings, graticule),
+        }
+    } else if let Some(path) = matches.value_of("export-sheet") {
+        export_sheet(&mut app, &matches, &settings, path);
+    } else if matches.is_present("print") || matches.is_present("bench") {
+        print_or_bench(&mut app, &matches, &settings);
+    } else if let Some(path) = matches.value_of("watch-file") {
+        watch_file(&mut app, &matches, &settings, path);
+    } else {
+        start_live(&mut app, &matches, settings);
+    }
+}
+
+/// The configuration file `--config` names, or the one at the default
+/// location, exiting if it can't be read. Empty with `--no-config`.
+fn load_config(matches: &ArgMatches) -> Config {
+    if matches.is_present("no-config") {
+        return Config::default();
+    }
+    let path = matches
+        .value_of("config")
+        .map(PathBuf::from)
+        .or_else(Config::default_path);
+    match path.map(|p| (Config::load(&p), p)) {
+        Some((Ok(config), _)) => config,
+        Some((Err(e), path)) => {
+            eprintln!("error: {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+        None => Config::default(),
+    }
+}
+
+/// Takes the template and view from the session saved by `--resume`, where
+/// the command line doesn't give them.
+fn resume_session(settings: &mut Settings, matches: &ArgMatches) {
+    let session = Session::load().and_then(|session| {
+        session
+            .map(|s| Ok((parse_template(&s.template)?, s.view)))
+            .transpose()
+    });
+    match session {
+        Ok(Some((template, view))) => {
+            if !matches.is_present("template") {
+                settings.template = template;
+            }
+            if !matches.is_present("coords") {
+                settings.coords = (view.lat, view.lon);
+            }
+            if !matches.is_present("zoom") {
+                settings.zoom = view.zoom.max(1.);
             }
         }
+        Ok(None) => (),
+        Err(e) => eprintln!("note: ignoring the saved session, {}", e),
+    }
+}
 
-        // make the globe spin
-        globe.angle -= PI / 50.;
+/// Writes the sprite sheet of `--export-sheet` to `path`.
+fn export_sheet(app: &mut App, matches: &ArgMatches, settings: &Settings, path: &str) {
+    let number = |name: &str| match matches.value_of(name).map(str::parse) {
+        None => Ok(None),
+        Some(Ok(n)) if n > 0 => Ok(Some(n)),
+        Some(_) => Err(format!("{} must be a positive number", name)),
+    };
+    let sheet = number("frames").and_then(|frames| {
+        let size = number("size")?.unwrap_or(64);
+        let glyphs = match matches.is_present("glyphs") {
+            true => Some(number("glyph-scale")?.unwrap_or(1)),
+            false => None,
+        };
+        if let Some(scale) = glyphs.filter(|scale| size < globe::GLYPH_SIZE * scale) {
+            return Err(format!(
+                "size must be at least {} pixels for glyphs at scale {}",
+                globe::GLYPH_SIZE * scale,
+                scale
+            ));
+        }
+        let columns = number("columns")?;
+        Ok(sheet::Sheet::new(
+            frames.unwrap_or(36),
+            size,
+            columns,
+            glyphs,
+        ))
+    });
+    let sheet = match sheet {
+        Ok(sheet) => sheet,
+        Err(e) => app.error(ErrorKind::InvalidValue, e).exit(),
+    };
+    let start = Instant::now();
+    match sheet::export(settings, &sheet, Path::new(path)) {
+        Ok(layout) => println!("wrote {} and {}", path, layout.display()),
+        Err(e) => {
+            eprintln!("error: {}: {}", path, e);
+            std::process::exit(1);
+        }
+    }
+    eprintln!(
+        "exported {} frames to {} in {:.1}s",
+        sheet.frames,
+        path,
+        start.elapsed().as_secs_f32()
+    );
+    settings.notify.finished("globe: export finished");
+}
 
-        globe.camera = Camera::new(cam_zoom, cam_xy, cam_z);
-        canvas.clear();
+/// Prints frames with `--print`, or times them with `--bench`.
+fn print_or_bench(app: &mut App, matches: &ArgMatches, settings: &Settings) {
+    let json = match matches.value_of("format") {
+        None | Some("text") => false,
+        Some("json") => true,
+        Some(other) => {
+            let e = format!("unknown format `{}`, expected text or json", other);
+            app.error(ErrorKind::InvalidValue, e).exit()
+        }
+    };
+    let frames = match matches.value_of("frames").map(str::parse) {
+        None if matches.is_present("bench") => 500,
+        None => 1,
+        Some(Ok(n)) if n > 0 => n,
+        Some(_) => {
+            let e = "frames must be a positive number";
+            app.error(ErrorKind::InvalidValue, e).exit()
+        }
+    };
+    if matches.is_present("bench") {
+        let stats = bench::run(settings, frames);
+        match json {
+            true => println!("{}", bench::json(&stats)),
+            false => print!("{}", bench::table(&stats)),
+        }
+        settings.notify.finished("globe: benchmark finished");
+    } else {
+        print_frames(settings, frames, json);
+        settings.notify.finished("globe: printing finished");
+    }
+}
 
-        // render globe on the canvas
-        globe.render_on(&mut canvas);
+/// Keeps writing frames to the file at `path` for `--watch-file`, every
+/// `--interval`.
+fn watch_file(app: &mut App, matches: &ArgMatches, settings: &Settings, path: &str) {
+    let interval = match matches.value_of("interval").map(parse_duration) {
+        None => Duration::from_secs(2),
+        Some(Ok(interval)) if !interval.is_zero() => interval,
+        Some(Ok(_)) => {
+            let e = "the interval can't be zero";
+            app.error(ErrorKind::InvalidValue, e).exit()
+        }
+        Some(Err(e)) => app.error(ErrorKind::InvalidValue, e).exit(),
+    };
+    if let Err(e) = watch::run(settings, Path::new(path), interval) {
+        eprintln!("error: {}: {}", path, e);
+        std::process::exit(1);
+    }
+    exit_if_terminated();
+}
 
-        // print canvas to terminal
-        let (size_x, size_y) = canvas.get_size();
-        for i in 0..size_y / 8 {
-            stdout
-                .queue(terminal::Clear(terminal::ClearType::CurrentLine))
-                .unwrap();
-            for j in 0..size_x / 4 {
-                stdout.queue(Print(canvas.matrix[i][j])).unwrap();
-            }
-            stdout.queue(cursor::MoveDown(1)).unwrap();
-            stdout.queue(cursor::MoveLeft((size_x / 4) as u16)).unwrap();
-            stdout.flush().unwrap();
+/// Runs the mode shown frame after frame in the terminal, picking the
+/// graphics output it asks for.
+fn start_live(app: &mut App, matches: &ArgMatches, mut settings: Settings) {
+    // a frame that doesn't fit would scroll the terminal
+    let term_size = term::size();
+    let (width, height) = settings.frame_size(term_size);
+    if width > term_size.0 || height > term_size.1 {
+        let e = format!(
+            "a {}x{} frame doesn't fit in the {}x{} terminal",
+            width, height, term_size.0, term_size.1
+        );
+        app.error(ErrorKind::InvalidValue, e).exit();
+    }
+
+    if matches.is_present("sixel") {
+        if !matches.is_present("force") && !graphics::sixel_supported() {
+            let e = "the terminal doesn't report sixel support, use --force to try anyway";
+            app.error(ErrorKind::InvalidValue, e).exit();
         }
+        // common cell size for terminals that don't report it
+        let cell = term::cell_size().unwrap_or((10, 20));
+        settings.output = Output::Sixel { cell };
+    }
+    // shown after exiting, the alternate screen would hide it
+    let mut warning = None;
+    if matches.is_present("kitty") {
+        if graphics::kitty_supported() {
+            let cell = term::cell_size().unwrap_or((10, 20));
+            settings.output = Output::Kitty { cell };
+        } else {
+            warning = Some("the terminal doesn't support kitty graphics, used characters instead");
+        }
+    }
 
-        if term_size.0 / 2 > term_size.1 {
-            // center the cursor on the x axis
-            stdout
-                .execute(crossterm::cursor::MoveTo(
-                    (size_x / 8) as u16 - ((size_x / 8) / 4) as u16,
-                    0,
-                ))
-                .unwrap();
+    if matches.is_present("iss") {
+        #[cfg(feature = "net")]
+        let url = if matches.is_present("offline") {
+            None
+        } else {
+            Some(
+                matches
+                    .value_of("iss-url")
+                    .unwrap_or(iss::DEFAULT_URL)
+                    .to_string(),
+            )
+        };
+        #[cfg(not(feature = "net"))]
+        let url = None;
+        iss::start(url, &settings);
+    } else if let Some((left, right)) = &settings.compare {
+        compare::start(left, right, &settings);
+    } else if let Some(route) = &settings.flight {
+        flight::start(route, &settings);
+    } else if matches.is_present("interactive") {
+        interactive::start(&settings);
+    } else if matches.is_present("screensaver") {
+        screensaver::start(&settings);
+    }
+    if let Some(warning) = warning {
+        eprintln!("warning: {}", warning);
+    }
+    exit_if_terminated();
+}
+
+/// Exits with the conventional code if a termination signal ended the mode
+/// that ran, once it has cleaned up.
+fn exit_if_terminated() {
+    if let Some(code) = term::exit_code() {
+        std::process::exit(code);
+    }
+}
+
+/// Shading characters for a light background, where dense characters look
+/// dark: the palette reversed, without blanks so nothing on the globe
+/// disappears into the background.
+fn light_palette(palette: &[char]) -> Vec<char> {
+    let light: Vec<char> = palette
+        .iter()
+        .rev()
+        .copied()
+        .filter(|&c| !c.is_whitespace() && c != '\u{2800}')
+        .collect();
+    match light.is_empty() {
+        true => palette.to_vec(),
+        false => light,
+    }
+}
+
+/// Opens the `--record` file, if any, exiting if it can't be created.
+fn start_recording(settings: &Settings) -> Option<Recorder> {
+    let (path, max_size) = settings.record.as_ref()?;
+    match Recorder::create(path, *max_size) {
+        Ok(recorder) => Some(recorder),
+        Err(e) => {
+            eprintln!("error: {}: {}", path.display(), e);
+            std::process::exit(1);
         }
     }
+}
 
-    stdout.execute(cursor::Show).unwrap();
-    stdout.execute(cursor::EnableBlinking).unwrap();
-    stdout.execute(terminal::Clear(ClearType::All)).unwrap();
+/// Finishes the recording, reporting how it went on stderr. Call this after
+/// restoring the terminal.
+fn stop_recording(settings: &Settings, recorder: Option<Recorder>) {
+    let (recorder, (path, _)) = match (recorder, &settings.record) {
+        (Some(recorder), Some(record)) => (recorder, record),
+        _ => return,
+    };
+    let (frames, time) = recorder.recorded();
+    let summary = format!(
+        "recorded {} frames to {} in {:.1}s",
+        frames,
+        path.display(),
+        time.as_secs_f32()
+    );
+    match recorder.close() {
+        Ok(false) => eprintln!("{}", summary),
+        Ok(true) => eprintln!(
+            "{}\nwarning: {}: stopped recording at the size limit",
+            summary,
+            path.display()
+        ),
+        Err(e) => eprintln!("error: recording to {} failed: {}", path.display(), e),
+    }
+}
 
-    terminal::disable_raw_mode().unwrap();
+/// Visible part of the canvas as text, without trailing spaces.
+fn canvas_text(canvas: &Canvas) -> String {
+    let (width, height) = overlay::grid_size(canvas);
+    let mut text = String::new();
+    for row in canvas.matrix.iter().take(height) {
+        let line: String = row.iter().take(width).collect();
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text
 }
 
-fn start_interactive() {
-    terminal::enable_raw_mode().unwrap();
+/// Saves the visible part of the canvas to a text file in the working
+/// directory.
+fn save_screenshot(canvas: &Canvas) -> std::io::Result<PathBuf> {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let path = PathBuf::from(format!("globe-{}.txt", secs));
+    std::fs::write(&path, canvas_text(canvas))?;
+    Ok(path)
+}
 
-    let mut stdout = stdout();
-    stdout.execute(cursor::Hide).unwrap();
-    stdout.execute(cursor::DisableBlinking).unwrap();
-    stdout
-        .execute(crossterm::event::EnableMouseCapture)
-        .unwrap();
-
-    let mut globe = GlobeConfig::new()
-        .use_template(GlobeTemplate::Earth)
-        .build();
-    let mut term_size = terminal::size().unwrap();
-    let mut canvas = if term_size.0 > term_size.1 {
-        Canvas::new(term_size.1 * 8, term_size.1 * 8, None)
-    } else {
-        Canvas::new(term_size.0 * 4, term_size.0 * 4, None)
+/// Prints the initial view, then `frames - 1` more turning like the
+/// screensaver, as text separated by blank lines or as one JSON object per
+/// line. When stdout isn't a terminal the frame defaults to 40x40 cells, so
+/// piped output doesn't depend on where it ran. Text frames too small to
+/// render are printed as thumbnails of the Earth.
+fn print_frames(settings: &Settings, frames: u32, json: bool) {
+    let term_size = match stdout().is_terminal() {
+        true => term::size(),
+        false => term::DEFAULT_SIZE,
     };
+    let mut canvas = canvas_for(settings.frame_size(term_size), settings);
+    let mut globe = settings.globe();
+    let (angle, cam_z) = settings.initial_view();
+    globe.angle = angle;
 
-    let mut cam_zoom = 2.;
-    let cam_xy = 0.;
-    let mut cam_z = 0.;
-    globe.camera = Camera::new(cam_zoom, cam_xy, cam_z);
-
-    let mut last_drag_pos = None;
-
-    loop {
-        if poll(Duration::from_millis(100)).unwrap() {
-            match read().unwrap() {
-                Event::Key(event) => match event.code {
-                    KeyCode::Char(_) => break,
-                    KeyCode::PageUp => cam_zoom += 0.1,
-                    KeyCode::PageDown => cam_zoom -= 0.1,
-                    KeyCode::Up => {
-                        if cam_z < 1.5 {
-                            cam_z += 0.1;
-                        }
-                    }
-                    KeyCode::Down => {
-                        if cam_z > -1.5 {
-                            cam_z -= 0.1;
-                        }
-                    }
-                    KeyCode::Left => globe.angle += PI / 30.,
-                    KeyCode::Right => globe.angle -= PI / 30.,
-                    KeyCode::Enter => {
-                        // focus on point
-                        let (cx, cy) = (0., 0.);
-
-                        cam_z = cy * 3. - 1.5;
-                        globe.angle = cx * (PI * 2.) + PI;
+    let mut stdout = stdout();
+    for frame in 0..frames {
+        if frame > 0 {
+            globe.angle -= PI / 50. * settings.speed;
+            globe.event_time += settings.frame_time().mul_f32(settings.event_speed);
+            globe.wobble_time += settings.frame_time();
+        }
+        if let Some(cols) = settings.thumbnail.filter(|_| !json) {
+            let rows = globe::thumbnail(cols, globe.facing_longitude_deg());
+            let text: String = rows.iter().map(|r| format!("{}\n", r.trim_end())).collect();
+            let separator = if frame > 0 { "\n" } else { "" };
+            if write!(stdout, "{}{}", separator, text).is_err() {
+                return;
+            }
+            continue;
+        }
+        globe.camera = Camera::new(settings.zoom, 0., cam_z + globe.wobble_offset());
+        canvas.clear();
+        globe.render_on(&mut canvas);
+        settings.apply_effects(&mut canvas);
+        settings.layer.draw(&globe, &mut canvas);
+        let result = if json {
+            writeln!(
+                stdout,
+                "{}",
+                frame_json(&canvas, &globe, (settings.zoom, cam_z), frame)
+            )
+        } else if frame > 0 {
+            write!(stdout, "\n{}", canvas_text(&canvas))
+        } else {
+            write!(stdout, "{}", canvas_text(&canvas))
+        };
+        // stop quietly when piped into something like `head`
+        if result.is_err() {
+            return;
+        }
+    }
+}
+
+/// Frame as a JSON object, see the `--format` help for the fields.
+/// `camera` is the camera distance and tilt.
+fn frame_json(canvas: &Canvas, globe: &Globe, camera: (Float, Float), frame: u32) -> Json {
+    let (zoom, cam_z) = camera;
+    let (width, height) = overlay::grid_size(canvas);
+    let rows = canvas.matrix[..height]
+        .iter()
+        .map(|row| Json::Str(row[..width].iter().collect()))
+        .collect();
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0., |d| d.as_secs_f64());
+    let (lat, lon) = (
+        globe::latitude_for_tilt(cam_z),
+        globe.facing_longitude_deg(),
+    );
+    let number = |n: Float| Json::Num(n as f64);
+    Json::Object(vec![
+        ("frame".to_string(), Json::Num(frame as f64)),
+        ("timestamp".to_string(), Json::Num(timestamp)),
+        ("angle".to_string(), number(lon)),
+        ("tilt".to_string(), number(lat)),
+        ("zoom".to_string(), number(zoom)),
+        ("width".to_string(), Json::Num(width as f64)),
+        ("height".to_string(), Json::Num(height as f64)),
+        ("rows".to_string(), Json::Array(rows)),
+    ])
+}
+
+/// Creates the largest canvas fitting the terminal, with the palette and
+/// cell aspect ratio of the settings. Cells are about twice as tall as they
+/// are wide, so a square canvas spans twice as many columns as rows.
+fn canvas_for(term_size: (u16, u16), settings: &Settings) -> Canvas {
+    // too small a canvas has nothing to render; the terminal clips this one
+    // and draw_frame shows a message instead
+    let (cols, rows) = (term_size.0.max(MIN_FRAME.0), term_size.1.max(MIN_FRAME.1));
+    Canvas::fit_terminal_chars(cols, rows)
+        .with_palette(&settings.palette)
+        .with_cell_aspect(settings.cell_aspect)
+}
+
+/// Like `canvas_for`, leaving the bottom row of the frame for a status line.
+fn status_canvas(term_size: (u16, u16), settings: &Settings) -> Canvas {
+    let (width, height) = settings.frame_size(term_size);
+    canvas_for((width, height.saturating_sub(1)), settings)
+}
+
+/// Terminal cell the top left corner of the canvas is drawn at, centering
+/// it in an area of `size` cells.
+fn frame_origin(canvas: &Canvas, size: (u16, u16)) -> (u16, u16) {
+    let (width, _) = overlay::grid_size(canvas);
+    frame_origin_of(width, canvas, size)
+}
+
+/// Like `frame_origin`, for a frame `width` cells wide made of canvases like
+/// `canvas` side by side.
+fn frame_origin_of(width: usize, canvas: &Canvas, size: (u16, u16)) -> (u16, u16) {
+    let (_, height) = overlay::grid_size(canvas);
+    (
+        size.0.saturating_sub(width as u16) / 2,
+        size.1.saturating_sub(height as u16) / 2,
+    )
+}
+
+/// Prints the canvas centered in the terminal, optionally followed by a
+/// status line on the bottom row. Graphics outputs render the globe again as
+/// an image covering the same cells, without the text drawn on the canvas.
+///
+/// The whole frame is assembled before being written, so it reaches the
+/// terminal in a single flush. Text frames only print the cells that
+/// changed since the last frame printed from the same canvas, see
+/// `Canvas::take_dirty_cells`; nothing is cleared, to avoid flicker.
+fn draw_frame(
+    out: &mut impl Write,
+    settings: &Settings,
+    globe: &Globe,
+    canvas: &mut Canvas,
+    term_size: (u16, u16),
+    status: Option<&str>,
+) {
+    draw_colored_frame(out, settings, globe, canvas, None, term_size, status);
+}
+
+/// Like `draw_frame`, with the text frame's characters in `colors`.
+/// Colored frames are printed whole.
+fn draw_colored_frame(
+    out: &mut impl Write,
+    settings: &Settings,
+    globe: &Globe,
+    canvas: &mut Canvas,
+    colors: Option<&anaglyph::Colors>,
+    term_size: (u16, u16),
+    status: Option<&str>,
+) {
+    let rows = term_size.1.saturating_sub(status.is_some() as u16);
+    if term_size.0 < MIN_FRAME.0 || rows < MIN_FRAME.1 {
+        draw_too_small(out, term_size);
+        // the message covers the frame
+        canvas.mark_dirty();
+        return;
+    }
+    if term::legacy_console() && settings.output == Output::Text {
+        draw_legacy_frame(out, settings, canvas, term_size, status);
+        return;
+    }
+
+    let (width, height) = overlay::grid_size(canvas);
+    let (left, top) = frame_origin(canvas, (term_size.0, rows));
+
+    // clip to the terminal if it was resized below a fixed frame size
+    let width = width.min((term_size.0 - left) as usize);
+    let height = height.min(rows as usize);
+
+    let mut frame = Vec::new();
+    match settings.output {
+        Output::Text if colors.is_none() || settings.color_depth == ColorDepth::Mono => {
+            queue_changes(&mut frame, canvas, (left, top), (width, height));
+        }
+        Output::Text => {
+            for (i, row) in canvas.matrix.iter().take(height).enumerate() {
+                frame.queue(cursor::MoveTo(left, top + i as u16)).unwrap();
+                let colors = match colors.and_then(|colors| colors.get(i)) {
+                    Some(colors) => colors,
+                    None => {
+                        let row: String = row[..width].iter().collect();
+                        frame.queue(Print(row)).unwrap();
+                        continue;
                     }
-                    _ => (),
-                },
-                Event::Mouse(event) => match event {
-                    MouseEvent::Drag(_, x, y, _) => {
-                        if let Some(last) = last_drag_pos {
-                            let (x_last, y_last) = last;
-                            let x_diff = x as globe::Float - x_last as globe::Float;
-                            let y_diff = y as globe::Float - y_last as globe::Float;
-
-                            if y_diff > 0. && cam_z < 1.5 {
-                                cam_z += 0.1;
-                            } else if y_diff < 0. && cam_z > -1.5 {
-                                cam_z -= 0.1;
+                };
+                // only switch colors where a character shows it
+                let mut current = None;
+                for (x, &c) in row[..width].iter().enumerate() {
+                    let color = colors.get(x).copied().flatten();
+                    if color != current && c != ' ' {
+                        current = color;
+                        match color {
+                            Some(color) => queue_color(&mut frame, settings.color_depth, color),
+                            None => {
+                                frame
+                                    .queue(style::SetForegroundColor(style::Color::Reset))
+                                    .unwrap();
                             }
-                            globe.angle += x_diff * PI / 30.;
-                            globe.angle += y_diff * PI / 30.;
                         }
-                        last_drag_pos = Some((x, y))
                     }
-                    MouseEvent::ScrollUp(..) => cam_zoom -= 0.1,
-                    MouseEvent::ScrollDown(..) => cam_zoom += 0.1,
-                    _ => last_drag_pos = None,
-                },
-                Event::Resize(width, height) => {
-                    term_size = (width, height);
-                    canvas = if width > height {
-                        Canvas::new(height * 8, height * 8, None)
-                    } else {
-                        Canvas::new(width * 4, width * 4, None)
-                    };
+                    frame.queue(Print(c)).unwrap();
                 }
+                frame.queue(style::ResetColor).unwrap();
             }
         }
-
-        // clip camera zoom
-        if cam_zoom < 1.0 {
-            cam_zoom = 1.0;
+        Output::Sixel { cell } | Output::Kitty { cell } => {
+            // square pixels keep the globe round
+            let side = (width * cell.0 as usize).min(height * cell.1 as usize);
+            // a terminal reporting no cell size has nothing to draw in
+            if let Some(raster) = Raster::render(globe, side, side) {
+                let image = match settings.output {
+                    Output::Sixel { .. } => graphics::sixel(&raster),
+                    _ => graphics::kitty(&raster),
+                };
+                frame.queue(cursor::MoveTo(left, top)).unwrap();
+                frame.queue(Print(image)).unwrap();
+            }
         }
+    }
+    queue_overlays(&mut frame, settings, term_size, status);
+    // what was printed whole is up to date as well
+    canvas.take_dirty_cells();
 
-        globe.camera = Camera::new(cam_zoom, cam_xy, cam_z);
-
-        canvas.clear();
+    out.write_all(&frame).unwrap();
+    out.flush().unwrap();
+}
 
-        // render globe on the canvas
-        globe.render_on(&mut canvas);
+/// Queues switching to the closest color to `color` that a terminal
+/// showing `depth` colors has. Crossterm sets even the 16 standard colors
+/// as entries of the 256-color palette, so their own codes are written
+/// instead.
+fn queue_color(frame: &mut Vec<u8>, depth: ColorDepth, color: Color) {
+    let color = match depth {
+        ColorDepth::TrueColor => style::Color::Rgb {
+            r: color.r,
+            g: color.g,
+            b: color.b,
+        },
+        ColorDepth::Ansi256 => style::Color::AnsiValue(color.to_ansi256()),
+        ColorDepth::Ansi16 | ColorDepth::Mono => {
+            let code = match color.to_ansi16() {
+                index @ 0..=7 => 30 + index,
+                index => 90 + index - 8,
+            };
+            write!(frame, "\x1b[{}m", code).unwrap();
+            return;
+        }
+    };
+    frame.queue(style::SetForegroundColor(color)).unwrap();
+}
 
-        // print canvas to terminal
-        let (size_x, size_y) = canvas.get_size();
-        for i in 0..size_y / 8 {
-            stdout
-                .queue(terminal::Clear(terminal::ClearType::CurrentLine))
-                .unwrap();
-            for j in 0..size_x / 4 {
-                stdout.queue(Print(canvas.matrix[i][j])).unwrap();
-            }
-            // stdout.execute(cursor::MoveToNextLine(1));
-            stdout.queue(cursor::MoveDown(1)).unwrap();
-            stdout.queue(cursor::MoveLeft((size_x / 4) as u16)).unwrap();
-            stdout.flush().unwrap();
-        }
-
-        if term_size.0 / 2 > term_size.1 {
-            // center the cursor on the x axis
-            stdout
-                .execute(crossterm::cursor::MoveTo(
-                    (size_x / 8) as u16 - ((size_x / 8) / 4) as u16,
-                    // (term_size.0 / 2) - (term_size.0 / 4) as u16,
-                    // term_size.0 / 2,
-                    0,
-                ))
+/// Queues the characters of `canvas` that changed since it was last
+/// printed, for the frame of `size` cells at `origin`, moving the cursor
+/// only to where a run of changed cells along a row starts.
+fn queue_changes(
+    frame: &mut Vec<u8>,
+    canvas: &mut Canvas,
+    (left, top): (u16, u16),
+    (width, height): (usize, usize),
+) {
+    let mut run = String::new();
+    // cell right after the run
+    let mut end = None;
+    for (x, y) in canvas.take_dirty_cells() {
+        // clipped to the terminal
+        if x >= width || y >= height {
+            continue;
+        }
+        if end != Some((x, y)) {
+            frame.queue(Print(&run)).unwrap();
+            run.clear();
+            frame
+                .queue(cursor::MoveTo(left + x as u16, top + y as u16))
                 .unwrap();
         }
+        run.push(canvas.matrix[y][x]);
+        end = Some((x + 1, y));
+    }
+    frame.queue(Print(run)).unwrap();
+}
+
+/// Fills the terminal with a message saying it's too small for a frame,
+/// clipped to what fits. Drawing frames resumes once the terminal grows.
+fn draw_too_small(out: &mut impl Write, (width, height): (u16, u16)) {
+    let text = format!(
+        "terminal too small (need at least {}x{})",
+        MIN_FRAME.0, MIN_FRAME.1
+    );
+    let text: String = text.chars().take(width as usize).collect();
+    let mut frame = Vec::new();
+    for y in 0..height {
+        let row = if y == height / 2 { text.as_str() } else { "" };
+        // the bottom right cell would scroll legacy consoles
+        let w =
+            (width as usize).saturating_sub((y + 1 == height && term::legacy_console()) as usize);
+        frame.queue(cursor::MoveTo(0, y)).unwrap();
+        frame.queue(Print(format!("{:^w$}", row, w = w))).unwrap();
+    }
+    out.write_all(&frame).unwrap();
+    out.flush().unwrap();
+}
+
+/// Draws a text frame on a legacy Windows console, where nothing can be
+/// buffered. The frame is written from the top left corner as a single
+/// block of full-width rows, relying on line wrapping instead of cursor
+/// movements between rows.
+fn draw_legacy_frame(
+    out: &mut impl Write,
+    settings: &Settings,
+    canvas: &Canvas,
+    term_size: (u16, u16),
+    status: Option<&str>,
+) {
+    let (width, height) = overlay::grid_size(canvas);
+    let rows = term_size.1.saturating_sub(status.is_some() as u16);
+    let (left, top) = frame_origin(canvas, (term_size.0, rows));
+    let term_width = term_size.0 as usize;
+
+    let mut text = String::new();
+    for y in 0..rows as usize {
+        let row = y
+            .checked_sub(top as usize)
+            .filter(|&i| i < height)
+            .map(|i| &canvas.matrix[i][..width]);
+        let row: String = std::iter::repeat_n(' ', left as usize)
+            .chain(row.into_iter().flatten().copied())
+            .chain(std::iter::repeat(' '))
+            .take(term_width)
+            .collect();
+        text.push_str(&row);
+    }
+    if status.is_none() {
+        // writing the bottom right cell would scroll the console
+        text.pop();
+    }
+    out.queue(cursor::MoveTo(0, 0)).unwrap();
+    out.queue(Print(text)).unwrap();
+    queue_overlays(out, settings, term_size, status);
+    out.flush().unwrap();
+}
+
+/// Queues the clock and the status line on the bottom row.
+fn queue_overlays(
+    out: &mut impl Write,
+    settings: &Settings,
+    term_size: (u16, u16),
+    status: Option<&str>,
+) {
+    if let Some(clock) = &settings.clock {
+        clock.queue(out, term_size.0);
+    }
+
+    if let Some(status) = status {
+        // pad to the full width to overwrite the previous status, except
+        // for the last cell on legacy consoles, which would scroll
+        let width = (term_size.0 as usize).saturating_sub(term::legacy_console() as usize);
+        let status: String = status.chars().take(width).collect();
+        out.queue(cursor::MoveTo(0, term_size.1.saturating_sub(1)))
+            .unwrap();
+        out.queue(Print(format!("{:<w$}", status, w = width)))
+            .unwrap();
     }
+}
+
+/// Position in degrees like `40.4°N 3.7°W`.
+fn format_position(lat: Float, lon: Float) -> String {
+    format!(
+        "{:.1}\u{b0}{} {:.1}\u{b0}{}",
+        lat.abs(),
+        if lat >= 0. { 'N' } else { 'S' },
+        lon.abs(),
+        if lon >= 0. { 'E' } else { 'W' },
+    )
+}
 
-    stdout.execute(cursor::Show).unwrap();
-    stdout.execute(cursor::EnableBlinking).unwrap();
-    stdout
-        .execute(crossterm::event::DisableMouseCapture)
-        .unwrap();
-    stdout.execute(terminal::Clear(ClearType::All)).unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Settings from `args`, after the program name, with an empty
+    /// configuration.
+    pub fn settings(args: &[&str]) -> Settings {
+        let args = std::iter::once("globe").chain(args.iter().copied());
+        let matches = cli("").try_get_matches_from(args).unwrap();
+        Settings::from_args(&matches, Config::default()).unwrap()
+    }
 
-    terminal::disable_raw_mode().unwrap();
+    #[test]
+    fn frame_json_parses_back() {
+        let mut globe = GlobeConfig::new()
+            .use_template(GlobeTemplate::Earth)
+            .build();
+        globe.angle = -(90 as Float).to_radians();
+        let mut canvas = Canvas::new(96, 96, None);
+        globe.render_on(&mut canvas);
+
+        let text = frame_json(&canvas, &globe, (2., 0.5), 3).to_string();
+        assert!(!text.contains('\n'), "not a single line: {}", text);
+        let json = Json::parse(&text).unwrap();
+        let number = |key| json.get(key).and_then(Json::as_f64).unwrap();
+        assert_eq!((number("width"), number("height")), (24., 12.));
+        assert_eq!(number("frame"), 3.);
+        assert_eq!(number("zoom"), 2.);
+        assert!((number("angle") - 90.).abs() < 1e-3);
+        assert!((number("tilt") - globe::latitude_for_tilt(0.5) as f64).abs() < 1e-3);
+        let rows = json.get("rows").and_then(Json::as_array).unwrap();
+        assert_eq!(rows.len(), 12);
+        for (row, cells) in rows.iter().zip(&canvas.matrix) {
+            let expected: String = cells[..24].iter().collect();
+            assert_eq!(row.as_str(), Some(expected.as_str()));
+        }
+    }
 }
@@ -0,0 +1,262 @@
+//! Points and lines drawn on the globe, loaded from GeoJSON files.
+
+use std::fs;
+use std::path::Path;
+
+use globe::{Canvas, Float, Globe};
+
+use crate::json::Json;
+use crate::overlay;
+
+/// Character for points without a `char` property.
+const DEFAULT_POINT: char = '*';
+/// Character lines are drawn with.
+const LINE: char = '~';
+/// Spacing of the points lines are drawn with, in degrees.
+const LINE_STEP: Float = 0.5;
+
+/// Labelled point on the globe.
+#[derive(Clone)]
+pub struct Marker {
+    pub lat: Float,
+    pub lon: Float,
+    pub label: Option<String>,
+    pub c: char,
+}
+
+/// Markers and lines to draw on top of the globe.
+#[derive(Clone, Default)]
+pub struct Layer {
+    pub markers: Vec<Marker>,
+    /// Polylines as latitude and longitude pairs in degrees.
+    pub lines: Vec<Vec<(Float, Float)>>,
+}
+
+impl Layer {
+    /// Adds the features of a GeoJSON file.
+    pub fn load_geojson(&mut self, path: &Path) -> Result<(), String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let json = Json::parse(&text)?;
+        self.add_geojson(&json)
+    }
+
+    /// Adds the features of a GeoJSON `FeatureCollection`, a single
+    /// `Feature` or a bare geometry. Points become markers labelled with
+    /// `properties.name`, everything else is drawn as lines.
+    pub fn add_geojson(&mut self, json: &Json) -> Result<(), String> {
+        match json.get("type").and_then(Json::as_str) {
+            Some("FeatureCollection") => {
+                let features = json
+                    .get("features")
+                    .and_then(Json::as_array)
+                    .ok_or("`features` must be an array")?;
+                for (i, feature) in features.iter().enumerate() {
+                    self.add_feature(feature)
+                        .map_err(|e| format!("feature {}: {}", i, e))?;
+                }
+                Ok(())
+            }
+            Some("Feature") => self.add_feature(json),
+            _ => self.add_geometry(json, None, DEFAULT_POINT),
+        }
+    }
+
+    fn add_feature(&mut self, feature: &Json) -> Result<(), String> {
+        let properties = feature.get("properties");
+        let property = |key| properties.and_then(|p| p.get(key));
+        let label = property("name").and_then(Json::as_str).map(str::to_string);
+        let c = match property("char").and_then(Json::as_str) {
+            Some(s) => {
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => c,
+                    _ => return Err("`char` must be a single character".to_string()),
+                }
+            }
+            None => DEFAULT_POINT,
+        };
+        match feature.get("geometry") {
+            Some(Json::Null) | None => Ok(()),
+            Some(geometry) => self.add_geometry(geometry, label, c),
+        }
+    }
+
+    fn add_geometry(
+        &mut self,
+        geometry: &Json,
+        label: Option<String>,
+        c: char,
+    ) -> Result<(), String> {
+        let kind = geometry
+            .get("type")
+            .and_then(Json::as_str)
+            .ok_or("geometry without a `type`")?;
+        if kind == "GeometryCollection" {
+            let geometries = geometry
+                .get("geometries")
+                .and_then(Json::as_array)
+                .ok_or("`geometries` must be an array")?;
+            for g in geometries {
+                self.add_geometry(g, label.clone(), c)?;
+            }
+            return Ok(());
+        }
+
+        let coords = geometry
+            .get("coordinates")
+            .ok_or("geometry without `coordinates`")?;
+        match kind {
+            "Point" => {
+                let (lat, lon) = position(coords)?;
+                self.markers.push(Marker { lat, lon, label, c });
+            }
+            "MultiPoint" => {
+                for p in array(coords)? {
+                    let (lat, lon) = position(p)?;
+                    let label = label.clone();
+                    self.markers.push(Marker { lat, lon, label, c });
+                }
+            }
+            "LineString" => self.lines.push(line(coords)?),
+            "MultiLineString" | "Polygon" => {
+                for l in array(coords)? {
+                    self.lines.push(line(l)?);
+                }
+            }
+            "MultiPolygon" => {
+                for polygon in array(coords)? {
+                    for l in array(polygon)? {
+                        self.lines.push(line(l)?);
+                    }
+                }
+            }
+            other => return Err(format!("unsupported geometry type `{}`", other)),
+        }
+        Ok(())
+    }
+
+    /// Draws the lines, then the markers and their labels on top.
+    pub fn draw(&self, globe: &Globe, canvas: &mut Canvas) {
+        let project = |canvas: &Canvas, lat, lon| globe.project(canvas, lat, lon);
+        self.draw_lines(canvas, project);
+        // skip the markers on the far side in one go
+        let positions: Vec<(Float, Float)> = self.markers.iter().map(|m| (m.lat, m.lon)).collect();
+        let visible = globe.cull_visible(&positions);
+        let markers = self.markers.iter().zip(visible).filter(|&(_, v)| v);
+        draw_markers(markers.map(|(m, _)| m), canvas, project);
+    }
+
+    /// Draws the layer at the cells `project` gives for positions in
+    /// degrees, like `globe::project_flat` for a flat map.
+    pub fn draw_with(
+        &self,
+        canvas: &mut Canvas,
+        project: impl Fn(&Canvas, Float, Float) -> Option<(usize, usize)>,
+    ) {
+        self.draw_lines(canvas, &project);
+        draw_markers(self.markers.iter(), canvas, project);
+    }
+
+    fn draw_lines(
+        &self,
+        canvas: &mut Canvas,
+        project: impl Fn(&Canvas, Float, Float) -> Option<(usize, usize)>,
+    ) {
+        for line in &self.lines {
+            for pair in line.windows(2) {
+                let distance = globe::angular_distance(pair[0], pair[1]);
+                let steps = (distance / LINE_STEP).ceil().max(1.) as usize;
+                for i in 0..=steps {
+                    let (lat, lon) =
+                        globe::great_circle(pair[0], pair[1], i as Float / steps as Float);
+                    if let Some((x, y)) = project(canvas, lat, lon) {
+                        canvas.draw_point(x, y, LINE);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn draw_markers<'a>(
+    markers: impl Iterator<Item = &'a Marker>,
+    canvas: &mut Canvas,
+    project: impl Fn(&Canvas, Float, Float) -> Option<(usize, usize)>,
+) {
+    for marker in markers {
+        if let Some((x, y)) = project(canvas, marker.lat, marker.lon) {
+            canvas.draw_point(x, y, marker.c);
+            if let Some(label) = &marker.label {
+                overlay::draw_text(canvas, x + 2, y, label);
+            }
+        }
+    }
+}
+
+fn array(json: &Json) -> Result<&[Json], String> {
+    json.as_array()
+        .ok_or_else(|| "`coordinates` must be an array".to_string())
+}
+
+/// GeoJSON position, `[longitude, latitude]` in degrees.
+fn position(json: &Json) -> Result<(Float, Float), String> {
+    let coords = array(json)?;
+    let lon = coords.first().and_then(Json::as_f64);
+    let lat = coords.get(1).and_then(Json::as_f64);
+    match (lat, lon) {
+        (Some(lat), Some(lon)) if lat.abs() <= 90. && lon.abs() <= 180. => {
+            Ok((lat as Float, lon as Float))
+        }
+        _ => Err("positions must be [longitude, latitude] in degrees".to_string()),
+    }
+}
+
+fn line(json: &Json) -> Result<Vec<(Float, Float)>, String> {
+    array(json)?.iter().map(position).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_file() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("data/example.geojson");
+        let mut layer = Layer::default();
+        layer.load_geojson(&path).unwrap();
+        let labels: Vec<_> = layer.markers.iter().map(|m| m.label.as_deref()).collect();
+        assert_eq!(
+            labels,
+            [
+                Some("London"),
+                Some("New York"),
+                Some("Nairobi"),
+                Some("Tokyo"),
+                Some("Sydney")
+            ]
+        );
+        let nairobi = &layer.markers[2];
+        assert_eq!((nairobi.lat, nairobi.lon, nairobi.c), (-1.29, 36.82, 'o'));
+        assert!(layer
+            .markers
+            .iter()
+            .all(|m| m.c == 'o' || m.c == DEFAULT_POINT));
+        assert_eq!(layer.lines, [vec![(51.51, -0.13), (40.71, -74.01)]]);
+    }
+
+    #[test]
+    fn names_the_bad_feature() {
+        let json = Json::parse(
+            r#"{"type": "FeatureCollection", "features": [
+                {"type": "Feature", "geometry": {"type": "Point", "coordinates": [0, 0]}},
+                {"type": "Feature", "geometry": {"type": "Point", "coordinates": [0, 91]}}
+            ]}"#,
+        )
+        .unwrap();
+        let e = Layer::default().add_geojson(&json).unwrap_err();
+        assert_eq!(
+            e,
+            "feature 1: positions must be [longitude, latitude] in degrees"
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! A moon orbiting the globe in screensaver mode, for `--with-moon`.
+
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use globe::{Camera, Canvas, Float, Globe, GlobeConfig, GlobeTemplate};
+
+use crate::overlay;
+
+/// Circular orbit around the center of the canvas.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Orbit {
+    /// Orbit radius in cells across; cells are about twice as tall as wide,
+    /// so the orbit is half as many cells tall.
+    pub distance: Option<Float>,
+    /// Time for a full orbit.
+    pub period: Duration,
+    /// Tilt of the orbit towards the viewer in radians. At 0 the moon moves
+    /// along a horizontal line, passing straight in front of the globe.
+    pub inclination: Float,
+}
+
+/// The moon and the canvas it's rendered on before being copied into the
+/// frame.
+pub struct Moon {
+    orbit: Orbit,
+    globe: Globe,
+    canvas: Canvas,
+}
+
+impl Moon {
+    /// Creates a moon about a quarter the size of a frame rendered on `canvas`.
+    pub fn new(orbit: Orbit, canvas: &Canvas, palette: &[char]) -> Moon {
+        Moon {
+            orbit,
+            globe: GlobeConfig::new().use_template(GlobeTemplate::Moon).build(),
+            canvas: moon_canvas(canvas, palette),
+        }
+    }
+
+    /// Sizes the moon for a new frame canvas.
+    pub fn resize(&mut self, canvas: &Canvas, palette: &[char]) {
+        self.canvas = moon_canvas(canvas, palette);
+    }
+
+    /// Draws the moon where it is `elapsed` into its orbit around `globe`,
+    /// rendered with the same `zoom`. Behind the globe it only shows around
+    /// it; in front it covers it.
+    pub fn draw(&mut self, globe: &Globe, canvas: &mut Canvas, zoom: Float, elapsed: Duration) {
+        let phase = elapsed.as_secs_f32() / self.orbit.period.as_secs_f32();
+        let phase = phase.fract() * 2. * PI;
+
+        let (width, height) = overlay::grid_size(canvas);
+        let (moon_width, moon_height) = overlay::grid_size(&self.canvas);
+        let distance = self
+            .orbit
+            .distance
+            .unwrap_or((width - moon_width) as Float / 2.);
+        // seen from above the orbit, the moon goes round anticlockwise with
+        // the viewer at the bottom
+        let x = distance * phase.cos();
+        let towards = distance * phase.sin();
+        let y = towards * self.orbit.inclination.sin() * canvas.cell_aspect();
+        let in_front = towards * self.orbit.inclination.cos() > 0.;
+
+        // always the same side facing the globe
+        self.globe.angle = -PI / 2. - phase;
+        self.globe.camera = Camera::new(zoom, 0., 0.);
+        self.canvas.clear();
+        self.globe.render_on(&mut self.canvas);
+
+        let left = (width as Float / 2. + x).round() as isize - moon_width as isize / 2;
+        let top = (height as Float / 2. + y).round() as isize - moon_height as isize / 2;
+        for my in 0..moon_height {
+            for mx in 0..moon_width {
+                if self.globe.unproject(&self.canvas, mx, my).is_none() {
+                    continue;
+                }
+                let (cx, cy) = (left + mx as isize, top + my as isize);
+                if cx < 0 || cy < 0 || cx as usize >= width || cy as usize >= height {
+                    continue;
+                }
+                let (cx, cy) = (cx as usize, cy as usize);
+                if in_front || globe.unproject(canvas, cx, cy).is_none() {
+                    canvas.draw_point(cx, cy, self.canvas.matrix[my][mx]);
+                }
+            }
+        }
+    }
+}
+
+/// Canvas for a moon in frames rendered on `canvas`: a square of whole
+/// cells, like the frame's, drawn for the same cells.
+fn moon_canvas(canvas: &Canvas, palette: &[char]) -> Canvas {
+    let (width, _) = overlay::grid_size(canvas);
+    let cols = (width / 4).max(2) as u16;
+    Canvas::fit_terminal_chars(cols, cols)
+        .with_palette(palette)
+        .with_cell_aspect(canvas.cell_aspect())
+}
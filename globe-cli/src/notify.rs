@@ -0,0 +1,51 @@
+//! Telling the terminal a long run finished, for `--notify`.
+
+use std::io::{stderr, IsTerminal, Write};
+use std::time::Duration;
+
+use crate::term;
+
+/// How long `Notify::Title` shows its message.
+const TITLE_TIME: Duration = Duration::from_secs(3);
+
+/// What happens when a run finishes on its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Notify {
+    None,
+    /// Rings the terminal bell.
+    Bell,
+    /// Shows a message in the window title for a few seconds.
+    Title,
+}
+
+impl Notify {
+    pub fn parse(s: &str) -> Result<Notify, String> {
+        match s {
+            "none" => Ok(Notify::None),
+            "bell" => Ok(Notify::Bell),
+            "title" => Ok(Notify::Title),
+            _ => Err(format!(
+                "invalid notify `{}`, expected bell, title or none",
+                s
+            )),
+        }
+    }
+
+    /// Tells the terminal that a run finished, with `message` for the
+    /// title. Written to stderr so it reaches the terminal even when stdout
+    /// carries frames, and skipped when stderr isn't a terminal either.
+    pub fn finished(self, message: &str) {
+        if !stderr().is_terminal() {
+            return;
+        }
+        match self {
+            Notify::None => {}
+            Notify::Bell => {
+                let mut stderr = stderr();
+                let _ = stderr.write_all(b"\x07");
+                let _ = stderr.flush();
+            }
+            Notify::Title => term::flash_title(message, TITLE_TIME),
+        }
+    }
+}
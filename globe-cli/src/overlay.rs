@@ -0,0 +1,108 @@
+//! Text drawn on top of the rendered globe.
+
+use globe::{Canvas, Float, QualitySettings, RenderStats};
+
+use crate::keys::{Action, Bindings};
+
+/// Size of the canvas in terminal cells.
+pub fn grid_size(canvas: &Canvas) -> (usize, usize) {
+    let (x, y) = canvas.get_size();
+    (x / 4, y / 8)
+}
+
+/// Writes `text` starting at the given cell, clipped to the canvas.
+pub fn draw_text(canvas: &mut Canvas, x: usize, y: usize, text: &str) {
+    let (width, height) = grid_size(canvas);
+    if y >= height {
+        return;
+    }
+    for (i, c) in text.chars().enumerate().take(width.saturating_sub(x)) {
+        canvas.draw_point(x + i, y, c);
+    }
+}
+
+/// Writes `text` centered on the bottom row.
+pub fn draw_message(canvas: &mut Canvas, text: &str) {
+    let (width, height) = grid_size(canvas);
+    let len = text.chars().count();
+    draw_text(
+        canvas,
+        width.saturating_sub(len) / 2,
+        height.saturating_sub(1),
+        text,
+    );
+}
+
+/// Draws frame statistics in the top left corner, for `--stats`: the
+/// frame rate, the render resolution in characters and the quality level
+/// out of the highest one, each if given, and below them what the
+/// render took if counted.
+pub fn draw_stats(
+    canvas: &mut Canvas,
+    fps: Float,
+    resolution: Option<(usize, usize)>,
+    quality: Option<usize>,
+    render: Option<&RenderStats>,
+) {
+    let mut text = format!("{:.1} fps", fps);
+    if let Some((width, height)) = resolution {
+        text += &format!(", {}x{}", width, height);
+    }
+    if let Some(level) = quality {
+        text += &format!(", quality {}/{}", level, QualitySettings::LEVELS - 1);
+    }
+    draw_text(canvas, 0, 0, &text);
+    if let Some(render) = render {
+        let mut text = format!(
+            "{} hit, {} background, {} samples, {} culled",
+            render.hit_cells, render.background_cells, render.samples, render.culled
+        );
+        if let Some(time) = render.time {
+            text += &format!(", {:.1} ms", time.as_secs_f64() * 1000.);
+        }
+        draw_text(canvas, 0, 1, &text);
+    }
+}
+
+/// Draws a framed box with `lines` in the middle of the canvas.
+pub fn draw_box(canvas: &mut Canvas, lines: &[String]) {
+    let (width, height) = grid_size(canvas);
+    let inner = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let (box_w, box_h) = (inner + 4, lines.len() + 2);
+    let x = width.saturating_sub(box_w) / 2;
+    let y = height.saturating_sub(box_h) / 2;
+
+    let border = format!("+{}+", "-".repeat(inner + 2));
+    draw_text(canvas, x, y, &border);
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(
+            canvas,
+            x,
+            y + 1 + i,
+            &format!("| {:<w$} |", line, w = inner),
+        );
+    }
+    draw_text(canvas, x, y + box_h - 1, &border);
+}
+
+/// Draws the list of active key bindings, and the mouse controls if the
+/// mouse is captured.
+pub fn draw_help(canvas: &mut Canvas, bindings: &Bindings, mouse: bool) {
+    let mut lines: Vec<String> = Action::ALL
+        .iter()
+        .map(|&action| {
+            let keys: Vec<String> = bindings
+                .keys(action)
+                .iter()
+                .map(|k| k.to_string())
+                .collect();
+            format!("{:<20}{}", action.name(), keys.join(" "))
+        })
+        .collect();
+    lines.push(String::new());
+    lines.push("1-9: go to bookmark".to_string());
+    if mouse {
+        lines.push("mouse: drag to rotate, wheel to zoom".to_string());
+    }
+    draw_box(canvas, &lines);
+}
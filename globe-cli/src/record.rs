@@ -0,0 +1,119 @@
+//! Recording frames to a file as they're drawn, for `--record`.
+//!
+//! Recordings are asciicast v2 files, one JSON line per frame, which
+//! `asciinema play` can replay.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use globe::{Canvas, FrameSink};
+
+use crate::json::Json;
+use crate::overlay;
+
+/// Bytes held in memory before they're written to the file.
+const BUFFER: usize = 64 * 1024;
+
+/// Frames streamed to a file, up to an optional size limit.
+pub struct Recorder {
+    file: BufWriter<File>,
+    start: Instant,
+    /// Bytes written so far, header included.
+    written: u64,
+    /// Frames written so far.
+    frames: usize,
+    max_size: Option<u64>,
+    /// Whether a frame was dropped because of the size limit; later ones are
+    /// dropped too.
+    full: bool,
+    /// First write error, after which nothing more is written.
+    error: Option<io::Error>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, max_size: Option<u64>) -> io::Result<Recorder> {
+        Ok(Recorder {
+            file: BufWriter::with_capacity(BUFFER, File::create(path)?),
+            start: Instant::now(),
+            written: 0,
+            frames: 0,
+            max_size,
+            full: false,
+            error: None,
+        })
+    }
+
+    /// Adds a frame, keeping a failure for `close` instead of returning it.
+    pub fn record(&mut self, canvas: &Canvas) {
+        if self.error.is_none() {
+            self.error = self.write_frame(canvas).err();
+        }
+    }
+
+    /// Frames recorded so far, and for how long.
+    pub fn recorded(&self) -> (usize, Duration) {
+        (self.frames, self.start.elapsed())
+    }
+
+    /// Finishes the file. Gives whether the size limit cut the recording
+    /// short, or the first error writing it.
+    pub fn close(mut self) -> io::Result<bool> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        self.finish()?;
+        Ok(self.full)
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<bool> {
+        let len = line.len() as u64 + 1;
+        if self.max_size.is_some_and(|max| self.written + len > max) {
+            self.full = true;
+            return Ok(false);
+        }
+        writeln!(self.file, "{}", line)?;
+        self.written += len;
+        Ok(true)
+    }
+}
+
+impl FrameSink for Recorder {
+    fn write_frame(&mut self, canvas: &Canvas) -> io::Result<()> {
+        if self.full {
+            return Ok(());
+        }
+        let (width, height) = overlay::grid_size(canvas);
+        if self.written == 0 {
+            let header = Json::Object(vec![
+                ("version".to_string(), Json::Num(2.)),
+                ("width".to_string(), Json::Num(width as f64)),
+                ("height".to_string(), Json::Num(height as f64)),
+            ]);
+            if !self.write_line(&header.to_string())? {
+                return Ok(());
+            }
+        }
+        // each frame redraws the screen from the top left corner
+        let rows: Vec<String> = canvas.matrix[..height]
+            .iter()
+            .map(|row| row[..width].iter().collect())
+            .collect();
+        let event = Json::Array(vec![
+            Json::Num(self.start.elapsed().as_secs_f64()),
+            Json::Str("o".to_string()),
+            Json::Str(format!("\x1b[H{}", rows.join("\r\n"))),
+        ]);
+        if self.write_line(&event.to_string())? {
+            self.frames += 1;
+        }
+        Ok(())
+    }
+
+    /// Flushes the buffer and waits for the file to reach the disk.
+    fn finish(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        self.file.get_ref().sync_all()
+    }
+}
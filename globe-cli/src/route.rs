@@ -0,0 +1,77 @@
+//! Routes through several stops for `--route`, loaded from CSV files.
+
+use std::fs;
+use std::path::Path;
+
+use globe::Float;
+
+use crate::events;
+
+/// Reads the stops of a route, in order, from a CSV file with a latitude
+/// and longitude per line. A header row naming the columns (`latitude` and
+/// `longitude`, or `lat` and `lon`) allows other columns and any order;
+/// without one they're expected to be the first two.
+pub fn load_csv(path: &Path) -> Result<Vec<(Float, Float)>, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    let mut columns = [0, 1];
+    let mut rows = Vec::new();
+    if let Some((i, first)) = lines.next() {
+        let names = events::fields(first);
+        match header_columns(&names) {
+            Some(found) => columns = found,
+            None if names
+                .iter()
+                .take(2)
+                .any(|n| n.trim().parse::<Float>().is_err()) =>
+            {
+                return Err("line 1: header needs `latitude` and `longitude` columns".to_string())
+            }
+            None => rows.push((i, names)),
+        }
+    }
+    rows.extend(lines.map(|(i, line)| (i, events::fields(line))));
+
+    let mut stops = Vec::new();
+    for (i, row) in rows {
+        let field = |column: usize| row.get(column).map(|s| s.trim()).unwrap_or("");
+        let number = |column: usize, what: &str| {
+            field(columns[column]).parse::<Float>().map_err(|_| {
+                format!(
+                    "line {}: invalid {} `{}`",
+                    i + 1,
+                    what,
+                    field(columns[column])
+                )
+            })
+        };
+        let lat = number(0, "latitude")?;
+        let lon = number(1, "longitude")?;
+        if lat.abs() > 90. || lon.abs() > 180. {
+            return Err(format!("line {}: position out of range", i + 1));
+        }
+        stops.push((lat, lon));
+    }
+    if stops.len() < 2 {
+        return Err("a route needs at least two stops".to_string());
+    }
+    Ok(stops)
+}
+
+/// Positions of the latitude and longitude columns in a header row, if it
+/// is one.
+fn header_columns(names: &[String]) -> Option<[usize; 2]> {
+    let find = |candidates: &[&str]| {
+        names
+            .iter()
+            .position(|n| candidates.contains(&n.trim().to_ascii_lowercase().as_str()))
+    };
+    Some([
+        find(&["latitude", "lat"])?,
+        find(&["longitude", "lon", "lng"])?,
+    ])
+}
@@ -0,0 +1,465 @@
+//! The spinning globe of screensaver mode.
+
+use std::f32::consts::PI;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use crossterm::event::Event;
+use globe::{Camera, Canvas, Float, Globe};
+
+use crate::anaglyph::Anaglyph;
+use crate::animation::{Flight, Pacer, Ramp};
+use crate::app::{self, Action, Mode};
+use crate::budget::Budget;
+use crate::control::Command;
+use crate::markers::Layer;
+use crate::moon::Moon;
+use crate::term::{self, Title};
+use crate::{canvas_for, overlay, ExitOn, Settings, MIN_FRAME};
+
+/// Runs the screensaver until it's interrupted, exited or out of time,
+/// telling the terminal with `--notify` when it ran out of time.
+pub fn start(settings: &Settings) {
+    // mouse events can only end the screensaver if they're reported
+    let mouse = settings.mouse && settings.exit_on == ExitOn::Any;
+    let screensaver = app::run(settings, mouse, |term_size, _| {
+        Screensaver::new(settings, term_size)
+    });
+    if screensaver.ran_out() && !term::terminated() {
+        settings.notify.finished("globe: screensaver finished");
+    }
+}
+
+/// State of the screensaver between frames.
+pub struct Screensaver<'a> {
+    settings: &'a Settings,
+    globe: Globe,
+    term_size: (u16, u16),
+    canvas: Canvas,
+    /// With `--anaglyph`, the renders for each eye.
+    anaglyph: Option<Anaglyph>,
+    cam_zoom: Float,
+    cam_z: Float,
+    speed: Float,
+    title: Title,
+    layer: Layer,
+    moon: Option<Moon>,
+    end: Option<Instant>,
+    /// With `--low-power`, rotation not drawn yet.
+    turned: Float,
+    redraw: bool,
+    /// Whether the terminal needs clearing before the next frame.
+    clear: bool,
+    /// With `--refocus`, the flight back to the view center and when the
+    /// last one ended.
+    flight: Option<Flight>,
+    refocused: Instant,
+    /// Eases into the spin, and out of it before exiting.
+    ramp: Ramp,
+    /// With `--bounce`, where the globe is in the frame.
+    bounce: Option<Bounce>,
+    /// With `--adaptive-quality`, the quality frames are rendered at.
+    budget: Option<Budget>,
+}
+
+/// Speed of a bouncing globe in cells per second, across and down. Cells are
+/// about twice as tall as they're wide, so this is a diagonal on screen.
+const BOUNCE_SPEED: (Float, Float) = (12., 6.);
+
+/// A globe canvas drifting around a frame and bouncing off its edges.
+struct Bounce {
+    /// The whole frame, with the globe canvas copied in.
+    frame: Canvas,
+    /// Top left corner of the globe in the frame, in cells.
+    position: (Float, Float),
+    velocity: (Float, Float),
+}
+
+impl Bounce {
+    /// Starts in the middle of a frame like `frame`, heading down and right.
+    fn new(frame: Canvas, globe: &Canvas) -> Bounce {
+        let mut bounce = Bounce {
+            frame,
+            position: (0., 0.),
+            velocity: BOUNCE_SPEED,
+        };
+        let (x, y) = bounce.room(globe);
+        bounce.position = (x / 2., y / 2.);
+        bounce
+    }
+
+    /// How far the globe can get from the top left corner on each axis.
+    fn room(&self, globe: &Canvas) -> (Float, Float) {
+        let (width, height) = overlay::grid_size(&self.frame);
+        let (globe_width, globe_height) = overlay::grid_size(globe);
+        (
+            width.saturating_sub(globe_width) as Float,
+            height.saturating_sub(globe_height) as Float,
+        )
+    }
+
+    /// Moves the globe on by `dt` seconds, turning back at the edges.
+    fn step(&mut self, globe: &Canvas, dt: Float) {
+        let room = self.room(globe);
+        for (position, velocity, room) in [
+            (&mut self.position.0, &mut self.velocity.0, room.0),
+            (&mut self.position.1, &mut self.velocity.1, room.1),
+        ] {
+            *position += *velocity * dt;
+            if *position < 0. {
+                *position = -*position;
+                *velocity = velocity.abs();
+            } else if *position > room {
+                *position = 2. * room - *position;
+                *velocity = -velocity.abs();
+            }
+            *position = position.clamp(0., room);
+        }
+    }
+
+    /// Switches to a new frame after a resize, keeping the globe inside.
+    fn resize(&mut self, frame: Canvas, globe: &Canvas) {
+        self.frame = frame;
+        let room = self.room(globe);
+        self.position = (
+            self.position.0.clamp(0., room.0),
+            self.position.1.clamp(0., room.1),
+        );
+    }
+
+    /// The frame with only `globe` in it, at the current position. Every
+    /// cell is written again, so the ones the globe left end up blank.
+    fn compose(&mut self, globe: &Canvas) -> &mut Canvas {
+        self.frame.clear();
+        let (left, top) = (
+            self.position.0.round() as usize,
+            self.position.1.round() as usize,
+        );
+        let (width, height) = overlay::grid_size(globe);
+        for (y, row) in globe.matrix[..height].iter().enumerate() {
+            self.frame.matrix[top + y][left..left + width].copy_from_slice(&row[..width]);
+        }
+        &mut self.frame
+    }
+}
+
+/// Canvas covering the whole frame in a terminal of `term_size` cells,
+/// rather than the largest square like `canvas_for`.
+fn bounce_frame(settings: &Settings, term_size: (u16, u16)) -> Canvas {
+    let (width, height) = settings.frame_size(term_size);
+    Canvas::new(
+        width.max(MIN_FRAME.0) * 4,
+        height.max(MIN_FRAME.1) * 8,
+        None,
+    )
+}
+
+/// Size of the globe canvas in a terminal of `term_size` cells, a third of
+/// the frame's height with `--bounce`.
+fn globe_size(settings: &Settings, term_size: (u16, u16)) -> (u16, u16) {
+    let (width, height) = settings.frame_size(term_size);
+    match settings.bounce {
+        true => (width, height / 3),
+        false => (width, height),
+    }
+}
+
+impl<'a> Screensaver<'a> {
+    /// Starts at the initial view, in a terminal of `term_size` cells.
+    pub fn new(settings: &'a Settings, term_size: (u16, u16)) -> Screensaver<'a> {
+        let mut globe = settings.globe();
+        let canvas = canvas_for(globe_size(settings, term_size), settings);
+        let (angle, cam_z) = settings.initial_view();
+        globe.angle = angle;
+        let moon = settings
+            .moon
+            .map(|orbit| Moon::new(orbit, &canvas, &settings.palette));
+        Screensaver {
+            settings,
+            globe,
+            term_size,
+            anaglyph: settings
+                .anaglyph
+                .then(|| Anaglyph::new(settings.frame_size(term_size), settings)),
+            cam_zoom: settings.zoom,
+            cam_z,
+            speed: settings.speed,
+            title: Title::default(),
+            layer: settings.layer.clone(),
+            moon,
+            end: settings.duration.map(|d| Instant::now() + d),
+            turned: 0.,
+            redraw: true,
+            clear: false,
+            flight: None,
+            refocused: Instant::now(),
+            ramp: Ramp::default(),
+            bounce: settings
+                .bounce
+                .then(|| Bounce::new(bounce_frame(settings, term_size), &canvas)),
+            budget: settings.target_fps.map(Budget::new),
+            canvas,
+        }
+    }
+
+    /// Radians per second the globe turns at.
+    fn turn_rate(&self) -> Float {
+        PI / 50. * self.speed * self.ramp.factor() * self.settings.fps
+    }
+
+    /// With `--low-power`, the rotation that moves the view by a character,
+    /// if it can be waited for.
+    fn cell(&self) -> Option<Float> {
+        cell_angle(&self.globe, &self.canvas, self.cam_z)
+            .filter(|_| self.settings.low_power && self.flight.is_none() && !self.ramp.easing())
+            // a bouncing globe moves all the time
+            .filter(|_| self.bounce.is_none())
+    }
+
+    /// Whether the `--duration` is up.
+    fn ran_out(&self) -> bool {
+        self.end.is_some_and(|end| Instant::now() >= end)
+    }
+}
+
+impl Mode for Screensaver<'_> {
+    fn handle_event(&mut self, event: Event) -> Action {
+        let exits = self.settings.exit_on.exits(&event);
+        match event {
+            Event::Key(event) if term::is_interrupt(&event) => return Action::Quit,
+            // a second press skips slowing down
+            _ if exits && self.ramp.stopping() => return Action::Quit,
+            _ if exits => self.ramp.stop(),
+            Event::Resize(width, height) => {
+                self.term_size = (width, height);
+                let size = self.settings.frame_size(self.term_size);
+                self.canvas = canvas_for(globe_size(self.settings, self.term_size), self.settings);
+                if let Some(bounce) = &mut self.bounce {
+                    bounce.resize(bounce_frame(self.settings, self.term_size), &self.canvas);
+                }
+                if let Some(anaglyph) = &mut self.anaglyph {
+                    *anaglyph = Anaglyph::new(size, self.settings);
+                }
+                if let Some(moon) = &mut self.moon {
+                    moon.resize(&self.canvas, &self.settings.palette);
+                }
+                self.clear = true;
+                self.redraw = true;
+            }
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    fn handle_command(&mut self, command: Command) -> Action {
+        match command {
+            Command::Focus(lat, lon) => {
+                self.globe.angle = -lon.to_radians();
+                self.cam_z = globe::tilt_for_latitude(lat);
+            }
+            Command::Zoom(zoom) => self.cam_zoom = zoom,
+            Command::Speed(s) => self.speed = s,
+            Command::AddMarker(marker) => self.layer.markers.push(marker),
+            Command::ClearMarkers => self.layer.markers.clear(),
+            Command::Quit => return Action::Quit,
+        }
+        self.redraw = true;
+        Action::Continue
+    }
+
+    fn update(&mut self, dt: Duration) {
+        let settings = self.settings;
+        let bands = self.globe.timezone_bands;
+        settings.update_globe(&mut self.globe);
+        self.redraw |= self.globe.timezone_bands != bands;
+
+        if self.flight.is_none()
+            && settings
+                .refocus
+                .is_some_and(|t| self.refocused.elapsed() >= t)
+        {
+            let (angle, tilt) = settings.initial_view();
+            let from = (self.globe.angle, self.cam_z, self.cam_zoom);
+            let to = (angle, tilt, settings.zoom);
+            self.flight = Some(Flight::new(from, to, Duration::from_secs(2)));
+        }
+
+        // make the globe spin, unless it's flying back to the view center;
+        // by the time since the last frame, so late frames don't slow the
+        // spin down
+        let turn = self.turn_rate() * dt.as_secs_f32();
+        if let Some(f) = &self.flight {
+            let (angle, tilt, distance) = f.value();
+            self.globe.angle = angle;
+            self.cam_z = tilt;
+            self.cam_zoom = distance;
+            if f.done() {
+                self.flight = None;
+                self.refocused = Instant::now();
+            }
+            self.redraw = true;
+        } else if let Some(cell) = self.cell() {
+            self.turned += turn;
+            if self.turned.abs() >= cell || self.redraw {
+                self.globe.angle -= self.turned;
+                self.turned = 0.;
+                self.redraw = true;
+            }
+        } else {
+            self.globe.angle -= turn;
+            self.redraw = true;
+        }
+
+        if let Some(bounce) = &mut self.bounce {
+            bounce.step(&self.canvas, dt.as_secs_f32());
+            self.redraw = true;
+        }
+    }
+
+    fn draw(&mut self, out: &mut impl Write, fps: Float) -> Option<&Canvas> {
+        if !self.redraw {
+            return None;
+        }
+        self.redraw = false;
+        let started = Instant::now();
+        let settings = self.settings;
+        let globe = &mut self.globe;
+        if let Some(budget) = &self.budget {
+            globe.quality = budget.quality();
+        }
+        globe.camera = Camera::new(self.cam_zoom, 0., self.cam_z + globe.wobble_offset())
+            .with_roll(settings.spin_roll());
+
+        let canvas = &mut self.canvas;
+        canvas.clear();
+        let mut render_stats = None;
+        match &mut self.anaglyph {
+            Some(anaglyph) => anaglyph.render(globe, canvas),
+            None if settings.stats => render_stats = Some(globe.render_on_with_stats(canvas)),
+            None => globe.render_on(canvas),
+        }
+        settings.apply_effects(canvas);
+        self.layer.draw(globe, canvas);
+        if let Some(moon) = &mut self.moon {
+            moon.draw(globe, canvas, self.cam_zoom, settings.start.elapsed());
+        }
+        let colors = settings.colors(globe, canvas);
+        // of the globe's own canvas, before it's put in a bouncing frame
+        let resolution = (globe.adaptive_resolution || self.budget.is_some())
+            .then(|| globe.render_resolution(canvas));
+        let canvas = match &mut self.bounce {
+            Some(bounce) => bounce.compose(canvas),
+            None => canvas,
+        };
+        if settings.stats {
+            let quality = self.budget.as_ref().map(Budget::level);
+            overlay::draw_stats(canvas, fps, resolution, quality, render_stats.as_ref());
+        }
+
+        app::print_frame(
+            out,
+            settings,
+            &mut self.title,
+            globe,
+            self.cam_zoom,
+            canvas,
+            colors
+                .as_ref()
+                .or(self.anaglyph.as_ref().map(|a| &a.colors)),
+            self.term_size,
+            std::mem::take(&mut self.clear),
+            None,
+        );
+        if let Some(budget) = &mut self.budget {
+            budget.record(started.elapsed());
+        }
+        Some(canvas)
+    }
+
+    fn wait(&self, pacer: &Pacer) -> Duration {
+        let turn_rate = self.turn_rate();
+        let wait = match self.cell() {
+            // sleep until the rotation shows, waking up at least once a
+            // second for stdin commands and the time zone highlight
+            Some(cell) if turn_rate.abs() > 0. => {
+                let left = (cell - self.turned.abs()).max(0.) / turn_rate.abs();
+                // never faster than the usual frame rate
+                Duration::from_secs_f32(left.min(1.)).max(pacer.wait())
+            }
+            Some(_) => Duration::from_secs(1),
+            None => pacer.wait(),
+        };
+        // don't overshoot the duration by a frame
+        match self.end {
+            Some(end) => wait.min(end.saturating_duration_since(Instant::now())),
+            None => wait,
+        }
+    }
+
+    fn done(&self) -> bool {
+        self.ran_out() || self.ramp.done()
+    }
+}
+
+/// Rotation in radians that moves the center of the view by one character,
+/// if the center is on the globe and rotating moves it at all. `cam_z` is
+/// the camera tilt.
+fn cell_angle(globe: &Globe, canvas: &Canvas, cam_z: Float) -> Option<Float> {
+    let lat = cam_z.to_degrees();
+    let lon = -globe.angle.to_degrees();
+    let (x0, _) = globe.project(canvas, lat, lon)?;
+    let (x1, _) = globe.project(canvas, lat, lon + 30.)?;
+    // a point 30 degrees further along is displaced by sin(30) times the
+    // distance per radian near the center, and still visible at any zoom
+    let cells = x1.abs_diff(x0) as Float;
+    Some(0.5 / cells).filter(|a| a.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::settings;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::from(code))
+    }
+
+    #[test]
+    fn exit_keys_slow_down_then_quit() {
+        let settings = settings(&["-s", "--exit-on", "q"]);
+        let mut saver = Screensaver::new(&settings, (80, 24));
+        assert_eq!(
+            saver.handle_event(key(KeyCode::Char('a'))),
+            Action::Continue
+        );
+        assert!(!saver.ramp.stopping());
+        assert_eq!(
+            saver.handle_event(key(KeyCode::Char('q'))),
+            Action::Continue
+        );
+        assert!(saver.ramp.stopping());
+        // a second press skips slowing down
+        assert_eq!(saver.handle_event(key(KeyCode::Char('q'))), Action::Quit);
+    }
+
+    #[test]
+    fn interrupt_quits_at_once() {
+        let settings = settings(&["-s", "--exit-on", "q"]);
+        let mut saver = Screensaver::new(&settings, (80, 24));
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(saver.handle_event(Event::Key(ctrl_c)), Action::Quit);
+    }
+
+    #[test]
+    fn resize() {
+        let settings = settings(&["-s"]);
+        let mut saver = Screensaver::new(&settings, (80, 24));
+        // resizing never counts as input that exits
+        assert_eq!(saver.handle_event(Event::Resize(120, 40)), Action::Continue);
+        assert!(!saver.ramp.stopping());
+        assert_eq!(saver.term_size, (120, 40));
+        assert_eq!(overlay::grid_size(&saver.canvas), (80, 40));
+        assert!(saver.clear);
+    }
+}
@@ -0,0 +1,92 @@
+//! The view interactive mode was left at, restored by `--resume`.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use globe::Float;
+
+use crate::bookmarks::View;
+use crate::config;
+use crate::json::Json;
+
+/// Format of the session file. Files of other versions are ignored.
+const VERSION: f64 = 1.;
+
+/// View and template of the last interactive session.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Session {
+    pub view: View,
+    pub template: String,
+}
+
+impl Session {
+    /// Loads the last session from the configuration directory, `None` if
+    /// there isn't one.
+    pub fn load() -> Result<Option<Session>, String> {
+        let path = match path() {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("{}: {}", path.display(), e)),
+        };
+        Session::parse(&text)
+            .map(Some)
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    fn parse(text: &str) -> Result<Session, String> {
+        let json = Json::parse(text)?;
+        match json.get("version").and_then(Json::as_f64) {
+            Some(v) if v == VERSION => (),
+            Some(v) => return Err(format!("unsupported version {}", v)),
+            None => return Err("missing `version`".to_string()),
+        }
+        let field = |name| json.get(name).and_then(Json::as_f64);
+        let template = json.get("template").and_then(Json::as_str);
+        match (field("lat"), field("lon"), field("zoom"), template) {
+            (Some(lat), Some(lon), Some(zoom), Some(template)) => Ok(Session {
+                view: View {
+                    lat: lat as Float,
+                    lon: lon as Float,
+                    zoom: zoom as Float,
+                },
+                template: template.to_string(),
+            }),
+            _ => Err("needs `lat`, `lon`, `zoom` and `template`".to_string()),
+        }
+    }
+
+    /// Writes the session to the configuration directory.
+    pub fn save(&self) -> Result<(), String> {
+        let path = path().ok_or("no configuration directory to save the session in")?;
+        let view = self.view;
+        let fields = vec![
+            ("version", Json::Num(VERSION)),
+            ("lat", Json::Num(view.lat as f64)),
+            ("lon", Json::Num(view.lon as f64)),
+            ("zoom", Json::Num(view.zoom as f64)),
+            ("template", Json::Str(self.template.clone())),
+        ];
+        let json = Json::Object(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+        );
+        let write = |path: &PathBuf| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(path, format!("{}\n", json))
+        };
+        write(&path).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+fn path() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join("session.json"))
+}
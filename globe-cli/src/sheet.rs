@@ -0,0 +1,145 @@
+//! Sprite sheets of a turning globe for `--export-sheet`.
+
+use std::convert::TryFrom;
+use std::f32::consts::PI;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use globe::{Camera, Canvas, Float, Globe, GLYPH_SIZE};
+
+use crate::graphics::{self, Raster};
+use crate::json::Json;
+use crate::Settings;
+
+/// Layout of a sprite sheet.
+pub struct Sheet {
+    pub frames: usize,
+    /// Width and height of every frame in pixels.
+    pub size: usize,
+    pub columns: usize,
+    /// Scale of the font frames are drawn as characters in, or `None` to
+    /// render them with a ray per pixel.
+    pub glyphs: Option<usize>,
+}
+
+impl Sheet {
+    /// `frames` frames of `size` pixels, in a roughly square grid unless
+    /// `columns` is given.
+    pub fn new(frames: usize, size: usize, columns: Option<usize>, glyphs: Option<usize>) -> Sheet {
+        let columns = columns.unwrap_or_else(|| (frames as f64).sqrt().ceil() as usize);
+        Sheet {
+            frames,
+            size,
+            columns: columns.clamp(1, frames.max(1)),
+            glyphs,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.frames.div_ceil(self.columns)
+    }
+}
+
+/// Renders a full turn of the globe, starting at the initial view, as
+/// `sheet.frames` evenly spaced frames tiled left to right and top to
+/// bottom. Writes the grid as a PNG image to `path` and its layout to a
+/// JSON file next to it, with the same name and a `.json` extension, then
+/// returns the path of the layout.
+///
+/// Nothing depends on the clock, so the same settings always give the same
+/// files.
+pub fn export(settings: &Settings, sheet: &Sheet, path: &Path) -> io::Result<PathBuf> {
+    let mut globe = settings.globe();
+    let (angle, cam_z) = settings.initial_view();
+    globe.camera = Camera::new(settings.zoom, 0., cam_z);
+
+    let width = sheet.columns * sheet.size;
+    let mut image = Raster {
+        width,
+        height: sheet.rows() * sheet.size,
+        pixels: vec![0; width * sheet.rows() * sheet.size],
+    };
+    let mut frames = Vec::with_capacity(sheet.frames);
+    for frame in 0..sheet.frames {
+        // the same way round as the screensaver turns
+        globe.angle = angle - frame as Float * 2. * PI / sheet.frames as Float;
+        let raster = match sheet.glyphs {
+            Some(scale) => render_glyphs(settings, &globe, sheet.size, scale),
+            None => Raster::render(&globe, sheet.size, sheet.size),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("frames of {} pixels are too large", sheet.size),
+            )
+        })?;
+        let (x, y) = (
+            frame % sheet.columns * sheet.size,
+            frame / sheet.columns * sheet.size,
+        );
+        for (row, pixels) in raster.pixels.chunks(sheet.size).enumerate() {
+            let start = (y + row) * width + x;
+            image.pixels[start..start + sheet.size].copy_from_slice(pixels);
+        }
+
+        // in degrees from the start, so the steps come out round
+        let turned = frame as f64 * 360. / sheet.frames as f64;
+        let lon = (-angle.to_degrees() as f64 + turned + 180.).rem_euclid(360.) - 180.;
+        frames.push(Json::Object(vec![
+            ("frame".to_string(), Json::Num(frame as f64)),
+            ("x".to_string(), Json::Num(x as f64)),
+            ("y".to_string(), Json::Num(y as f64)),
+            ("angle".to_string(), Json::Num(lon)),
+        ]));
+    }
+    fs::write(path, graphics::png(&image))?;
+
+    let image_name = path.file_name().map(|n| n.to_string_lossy().into_owned());
+    let layout = Json::Object(vec![
+        (
+            "image".to_string(),
+            image_name.map_or(Json::Null, Json::Str),
+        ),
+        ("frame_width".to_string(), Json::Num(sheet.size as f64)),
+        ("frame_height".to_string(), Json::Num(sheet.size as f64)),
+        ("columns".to_string(), Json::Num(sheet.columns as f64)),
+        ("rows".to_string(), Json::Num(sheet.rows() as f64)),
+        ("tilt".to_string(), Json::Num(cam_z.to_degrees() as f64)),
+        ("zoom".to_string(), Json::Num(settings.zoom as f64)),
+        (
+            "glyph_scale".to_string(),
+            sheet
+                .glyphs
+                .map_or(Json::Null, |scale| Json::Num(scale as f64)),
+        ),
+        ("frames".to_string(), Json::Array(frames)),
+    ]);
+    let layout_path = path.with_extension("json");
+    fs::write(&layout_path, format!("{}\n", layout))?;
+    Ok(layout_path)
+}
+
+/// Frame of `size` pixels square showing the globe as characters, drawn in
+/// the built-in font at `scale` and centered. Characters are square in the
+/// font, so the canvas is drawn for square cells. `None` if that's no
+/// characters or too many for a canvas.
+fn render_glyphs(settings: &Settings, globe: &Globe, size: usize, scale: usize) -> Option<Raster> {
+    let cells = u16::try_from(size / (GLYPH_SIZE * scale)).ok()?;
+    let mut canvas = Canvas::try_new(cells, cells, Some((1, 1)))?.with_palette(&settings.palette);
+    globe.render_on(&mut canvas);
+    settings.apply_effects(&mut canvas);
+    let image = canvas.to_image(scale);
+
+    let mut raster = Raster {
+        width: size,
+        height: size,
+        pixels: vec![0; size * size],
+    };
+    let offset = (size - image.width) / 2;
+    for (row, pixels) in image.pixels.chunks(image.width).enumerate() {
+        let start = (offset + row) * size + offset;
+        raster.pixels[start..start + image.width].copy_from_slice(pixels);
+    }
+    Some(raster)
+}
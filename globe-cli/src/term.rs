@@ -0,0 +1,632 @@
+//! Terminal setup and guaranteed cleanup.
+
+use std::io::{stderr, stdin, stdout, IsTerminal, Write};
+use std::os::raw::c_int;
+use std::panic;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Once;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{
+    DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers,
+};
+use crossterm::style::Print;
+use crossterm::{cursor, terminal, QueueableCommand};
+use globe::Float;
+
+use crate::graphics;
+
+/// Whether the terminal is currently set up for drawing.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+/// Whether mouse capture was enabled and has to be disabled on restore.
+static MOUSE: AtomicBool = AtomicBool::new(false);
+/// Whether frames are drawn on the alternate screen, which is left on
+/// restore. Otherwise the last frame is kept on the main screen.
+static ALT_SCREEN: AtomicBool = AtomicBool::new(false);
+/// Whether a kitty graphics image was shown and has to be deleted on restore.
+pub static KITTY_IMAGE: AtomicBool = AtomicBool::new(false);
+/// Whether the window title was changed and has to be restored.
+static TITLE: AtomicBool = AtomicBool::new(false);
+static PANIC_HOOK: Once = Once::new();
+/// Number of the termination signal received, or 0 before one is.
+static RECEIVED: AtomicUsize = AtomicUsize::new(0);
+/// Whether the terminal didn't report its size while set up for drawing,
+/// which is warned about on restore.
+static SIZE_UNKNOWN: AtomicBool = AtomicBool::new(false);
+
+/// Size in cells assumed when the terminal doesn't report one, or reports
+/// one without cells.
+pub const DEFAULT_SIZE: (u16, u16) = (40, 40);
+/// Largest terminal size in cells believed. Some terminals report absurd
+/// sizes, and canvases that large would take gigabytes.
+const MAX_SIZE: (u16, u16) = (500, 200);
+
+/// Signals that end the program cleanly.
+#[cfg(unix)]
+const SIGNALS: &[c_int] = &[
+    signal_hook::SIGINT,
+    signal_hook::SIGTERM,
+    signal_hook::SIGHUP,
+];
+#[cfg(not(unix))]
+const SIGNALS: &[c_int] = &[signal_hook::SIGINT, signal_hook::SIGTERM];
+
+/// Makes `SIGNALS` end the program cleanly instead of right away: they're
+/// only recorded for `terminated`, and wake up the wait for terminal events.
+fn register_signals() -> Vec<signal_hook::SigId> {
+    SIGNALS
+        .iter()
+        .map(|&signal| {
+            let action = move || {
+                RECEIVED.store(signal as usize, Ordering::SeqCst);
+                wake();
+            };
+            // storing to an atomic and raising a signal are both safe in a
+            // signal handler
+            unsafe { signal_hook::register(signal, action) }.unwrap()
+        })
+        .collect()
+}
+
+/// Interrupts a wait for terminal events. crossterm retries its poll when a
+/// signal interrupts it, so it would only notice at the timeout, but it
+/// watches for SIGWINCH itself and returns as soon as one arrives.
+#[cfg(unix)]
+fn wake() {
+    unsafe {
+        libc::raise(libc::SIGWINCH);
+    }
+}
+#[cfg(not(unix))]
+fn wake() {}
+
+/// Switches the terminal to raw mode with a hidden cursor, restoring it when
+/// dropped, when the program panics, or after an interrupt or termination
+/// signal.
+///
+/// With `alt_screen` frames are drawn on the alternate screen buffer so the
+/// shell contents come back on exit.
+pub struct TerminalGuard {
+    signals: Vec<signal_hook::SigId>,
+    /// Why mouse capture couldn't be enabled, reported after restoring.
+    mouse_error: Option<String>,
+}
+
+impl TerminalGuard {
+    pub fn new(mouse: bool, alt_screen: bool) -> TerminalGuard {
+        PANIC_HOOK.call_once(|| {
+            let default_hook = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                restore();
+                default_hook(info);
+            }));
+        });
+
+        // SIGINT can still be sent from outside while raw mode is on, and
+        // screen lockers or closing terminals end us with SIGTERM or SIGHUP
+        let signals = register_signals();
+
+        terminal::enable_raw_mode().unwrap();
+        ACTIVE.store(true, Ordering::SeqCst);
+        ALT_SCREEN.store(alt_screen, Ordering::SeqCst);
+
+        let mut stdout = stdout();
+        if alt_screen {
+            stdout.queue(terminal::EnterAlternateScreen).unwrap();
+        }
+        stdout
+            .queue(terminal::Clear(terminal::ClearType::All))
+            .unwrap();
+        stdout.queue(cursor::Hide).unwrap();
+        stdout.queue(cursor::DisableBlinking).unwrap();
+        // the keyboard still works without the mouse
+        let mut mouse_error = None;
+        if mouse {
+            match stdout.queue(EnableMouseCapture) {
+                Ok(_) => MOUSE.store(true, Ordering::SeqCst),
+                Err(e) => mouse_error = Some(e.to_string()),
+            }
+        }
+        stdout.flush().unwrap();
+
+        TerminalGuard {
+            signals,
+            mouse_error,
+        }
+    }
+
+    /// Whether mouse events are reported.
+    pub fn mouse(&self) -> bool {
+        MOUSE.load(Ordering::SeqCst)
+    }
+
+    /// Whether the process received SIGINT, SIGTERM or SIGHUP.
+    pub fn interrupted(&self) -> bool {
+        terminated()
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+        for &signal in &self.signals {
+            signal_hook::unregister(signal);
+        }
+        if let Some(e) = &self.mouse_error {
+            eprintln!("warning: couldn't capture the mouse: {}", e);
+        }
+        if SIZE_UNKNOWN.swap(false, Ordering::SeqCst) {
+            warn_size_unknown();
+        }
+    }
+}
+
+/// Makes SIGINT, SIGTERM and SIGHUP set `terminated` instead of ending the
+/// process, for modes that run without a terminal to restore and stop at
+/// their own pace, e.g. after finishing the file they're writing.
+pub fn catch_termination() {
+    register_signals();
+}
+
+/// Records Ctrl+C as if SIGINT was received, since raw mode delivers it as
+/// a key instead, so it ends the program with the same exit code.
+pub fn interrupt() {
+    let _ = RECEIVED.compare_exchange(
+        0,
+        signal_hook::SIGINT as usize,
+        Ordering::SeqCst,
+        Ordering::SeqCst,
+    );
+}
+
+/// Whether the process received SIGINT, SIGTERM or SIGHUP while they were
+/// caught.
+pub fn terminated() -> bool {
+    RECEIVED.load(Ordering::SeqCst) != 0
+}
+
+/// Conventional exit code after the termination signal received, 128 plus
+/// its number: 130 after SIGINT, 143 after SIGTERM and 129 after SIGHUP.
+pub fn exit_code() -> Option<i32> {
+    match RECEIVED.load(Ordering::SeqCst) {
+        0 => None,
+        signal => Some(128 + signal as i32),
+    }
+}
+
+/// Size of the terminal in cells, see `checked_size`. Falling back to
+/// `DEFAULT_SIZE` prints a warning, after restoring the terminal if it's
+/// set up for drawing.
+pub fn size() -> (u16, u16) {
+    let (size, known) = checked_size(terminal::size().ok());
+    if !known {
+        match ACTIVE.load(Ordering::SeqCst) {
+            true => SIZE_UNKNOWN.store(true, Ordering::SeqCst),
+            false => warn_size_unknown(),
+        }
+    }
+    size
+}
+
+/// `event` with the size of a resize checked like `size`'s. A resize
+/// without cells asks the terminal again instead, as some only know their
+/// real size once they've been resized, e.g. `screen` right after
+/// attaching.
+pub fn checked_resize(event: Event) -> Event {
+    match event {
+        Event::Resize(width, height) => {
+            let (width, height) = match checked_size(Some((width, height))) {
+                (size, true) => size,
+                (_, false) => size(),
+            };
+            Event::Resize(width, height)
+        }
+        event => event,
+    }
+}
+
+/// Size to draw at for what a terminal reported, and whether it reported a
+/// usable one: sizes past `MAX_SIZE` are clamped to it, and without a size
+/// or with no cells it's `DEFAULT_SIZE`.
+fn checked_size(reported: Option<(u16, u16)>) -> ((u16, u16), bool) {
+    match reported {
+        Some((width, height)) if width > 0 && height > 0 => {
+            ((width.min(MAX_SIZE.0), height.min(MAX_SIZE.1)), true)
+        }
+        _ => (DEFAULT_SIZE, false),
+    }
+}
+
+fn warn_size_unknown() {
+    eprintln!(
+        "warning: the terminal didn't report its size, using {}x{}",
+        DEFAULT_SIZE.0, DEFAULT_SIZE.1
+    );
+}
+
+/// Whether a key press is Ctrl+C, which always quits regardless of the key
+/// bindings.
+pub fn is_interrupt(event: &KeyEvent) -> bool {
+    event.code == KeyCode::Char('c') && event.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+/// Sends `request` to the terminal and collects its answer until `done`
+/// returns true for the bytes read so far. Raw mode has to be enabled.
+///
+/// Returns `None` if the terminal doesn't answer within `timeout`.
+#[cfg(unix)]
+pub fn query(request: &str, timeout: Duration, done: impl Fn(&[u8]) -> bool) -> Option<Vec<u8>> {
+    let mut stdout = stdout();
+    stdout.write_all(request.as_bytes()).ok()?;
+    stdout.flush().ok()?;
+
+    let start = Instant::now();
+    let mut reply = Vec::new();
+    while !done(&reply) {
+        let left = timeout.checked_sub(start.elapsed())?;
+        let mut fd = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        if unsafe { libc::poll(&mut fd, 1, left.as_millis() as libc::c_int) } <= 0 {
+            return None;
+        }
+        let mut byte = 0u8;
+        if unsafe { libc::read(libc::STDIN_FILENO, &mut byte as *mut u8 as *mut _, 1) } != 1 {
+            return None;
+        }
+        reply.push(byte);
+    }
+    Some(reply)
+}
+
+#[cfg(not(unix))]
+pub fn query(_request: &str, _timeout: Duration, _done: impl Fn(&[u8]) -> bool) -> Option<Vec<u8>> {
+    None
+}
+
+/// Reads and drops whatever the terminal sends until it's been quiet for
+/// `quiet`, so a late answer to a query isn't taken for key presses. Raw
+/// mode has to be enabled.
+#[cfg(unix)]
+pub fn discard_input(quiet: Duration) {
+    loop {
+        let mut fd = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        if unsafe { libc::poll(&mut fd, 1, quiet.as_millis() as libc::c_int) } <= 0 {
+            return;
+        }
+        let mut buffer = [0u8; 64];
+        if unsafe {
+            libc::read(
+                libc::STDIN_FILENO,
+                buffer.as_mut_ptr() as *mut _,
+                buffer.len(),
+            )
+        } <= 0
+        {
+            return;
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub fn discard_input(_quiet: Duration) {}
+
+/// Brightness of the terminal's background.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Background {
+    Dark,
+    Light,
+}
+
+/// Asks the terminal for its background color, `None` if it doesn't say.
+pub fn background() -> Option<Background> {
+    if !stdin().is_terminal() || !stdout().is_terminal() || terminal::enable_raw_mode().is_err() {
+        return None;
+    }
+    // every terminal answers the device attributes request sent after the
+    // color query, so the reply is complete once that answer is in, whether
+    // the color was reported or not
+    let reply = query("\x1b]11;?\x1b\\\x1b[c", Duration::from_millis(300), |r| {
+        r.ends_with(b"c") && r.windows(3).any(|w| w == b"\x1b[?")
+    });
+    if reply.is_none() {
+        discard_input(Duration::from_millis(100));
+    }
+    let _ = terminal::disable_raw_mode();
+    parse_background(&String::from_utf8_lossy(&reply?))
+}
+
+/// Background from an answer like `ESC ] 11 ; rgb:ffff/ffff/dddd ESC \`,
+/// with one to four hex digits per channel.
+fn parse_background(reply: &str) -> Option<Background> {
+    let start = reply.find("]11;rgb")?;
+    let color = reply[start + 7..]
+        .trim_start_matches('a')
+        .strip_prefix(':')?;
+    let channel = |s: &str| {
+        let digits: String = s.chars().take_while(char::is_ascii_hexdigit).collect();
+        let max = 16u32.checked_pow(digits.len() as u32)? - 1;
+        let value = u32::from_str_radix(&digits, 16).ok()?;
+        Some(value as Float / max as Float)
+    };
+    let mut channels = color.splitn(3, '/').map(channel);
+    let (r, g, b) = (channels.next()??, channels.next()??, channels.next()??);
+    match 0.2126 * r + 0.7152 * g + 0.0722 * b > 0.5 {
+        true => Some(Background::Light),
+        false => Some(Background::Dark),
+    }
+}
+
+/// When `--color` colors the globe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorChoice {
+    Always,
+    /// Unless `NO_COLOR` asks for no colors.
+    Auto,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn parse(s: &str) -> Result<ColorChoice, String> {
+        match s {
+            "always" => Ok(ColorChoice::Always),
+            "auto" => Ok(ColorChoice::Auto),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!(
+                "invalid color `{}`, expected always, auto or never",
+                s
+            )),
+        }
+    }
+
+    /// Whether to color, `no_color` being whether `NO_COLOR` is set.
+    pub fn colors(self, no_color: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Auto => !no_color,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// Whether `NO_COLOR` is set to anything but an empty string, asking
+/// programs not to color their output.
+pub fn no_color() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
+/// How many colors a terminal shows, from fewest to most.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorDepth {
+    /// None, only the characters.
+    Mono,
+    /// The 16 standard colors.
+    Ansi16,
+    /// The 256-color palette.
+    Ansi256,
+    /// Any 24-bit color.
+    TrueColor,
+}
+
+impl ColorDepth {
+    pub fn parse(s: &str) -> Result<ColorDepth, String> {
+        match s {
+            "truecolor" | "24bit" => Ok(ColorDepth::TrueColor),
+            "256" => Ok(ColorDepth::Ansi256),
+            "16" => Ok(ColorDepth::Ansi16),
+            "mono" => Ok(ColorDepth::Mono),
+            _ => Err(format!(
+                "invalid color depth `{}`, expected truecolor, 256, 16 or mono",
+                s
+            )),
+        }
+    }
+}
+
+/// How many colors the terminal says it shows, in `COLORTERM` or `TERM`.
+pub fn color_depth() -> ColorDepth {
+    let var = |name| std::env::var(name).ok();
+    depth_of(var("COLORTERM").as_deref(), var("TERM").as_deref())
+}
+
+/// Colors shown by a terminal that sets `COLORTERM` and `TERM` to these.
+fn depth_of(colorterm: Option<&str>, term: Option<&str>) -> ColorDepth {
+    if matches!(colorterm, Some("truecolor") | Some("24bit")) {
+        return ColorDepth::TrueColor;
+    }
+    match term {
+        Some("dumb") => ColorDepth::Mono,
+        Some(term) if term.contains("256color") => ColorDepth::Ansi256,
+        Some(_) => ColorDepth::Ansi16,
+        // Windows consoles set no TERM and show the 256-color palette
+        None => ColorDepth::Ansi256,
+    }
+}
+
+/// Whether this is a Windows console without escape sequence support, such
+/// as conhost before Windows 10. Crossterm then runs cursor movements
+/// through the console API as soon as they're queued, even when queued into
+/// a buffer, so frames have to be written straight to the console.
+#[cfg(windows)]
+pub fn legacy_console() -> bool {
+    !crossterm::ansi_support::supports_ansi()
+}
+
+#[cfg(not(windows))]
+pub fn legacy_console() -> bool {
+    false
+}
+
+/// Saves the window title on the terminal's title stack (xterm and most
+/// terminals derived from it).
+const PUSH_TITLE: &str = "\x1b[22;0t";
+/// Brings the saved window title back.
+const POP_TITLE: &str = "\x1b[23;0t";
+
+/// Shows `text` in the window title for `time`, then brings back the title
+/// from before, on terminals keeping a title stack. Written to stderr, as
+/// stdout may not be the terminal once it's restored.
+pub fn flash_title(text: &str, time: Duration) {
+    let mut stderr = stderr();
+    if !legacy_console() {
+        let _ = stderr.queue(Print(PUSH_TITLE));
+    }
+    let _ = stderr.queue(terminal::SetTitle(text));
+    let _ = stderr.flush();
+    thread::sleep(time);
+    let _ = stderr.queue(terminal::SetTitle(""));
+    if !legacy_console() {
+        let _ = stderr.queue(Print(POP_TITLE));
+    }
+    let _ = stderr.flush();
+}
+
+/// Window title showing the view center, changed at most once a second.
+/// The title from before is brought back on restore, on terminals keeping a
+/// title stack; others are left without a title.
+#[derive(Default)]
+pub struct Title {
+    shown: String,
+    updated: Option<Instant>,
+}
+
+impl Title {
+    /// Queues a new title for the view centered at `lat`, `lon` in degrees
+    /// with camera distance `zoom`, unless the title changed less than a
+    /// second ago or would stay the same.
+    pub fn update(&mut self, stdout: &mut impl Write, (lat, lon): (Float, Float), zoom: Float) {
+        if self
+            .updated
+            .is_some_and(|t| t.elapsed() < Duration::from_secs(1))
+        {
+            return;
+        }
+        let text = format!(
+            "globe \u{2014} {:.1}\u{b0}{} {:.1}\u{b0}{}, zoom {:.1}",
+            lat.abs(),
+            if lat >= 0. { 'N' } else { 'S' },
+            lon.abs(),
+            if lon >= 0. { 'E' } else { 'W' },
+            zoom
+        );
+        if text == self.shown {
+            return;
+        }
+        if !TITLE.swap(true, Ordering::SeqCst) && !legacy_console() {
+            let _ = stdout.queue(Print(PUSH_TITLE));
+        }
+        let _ = stdout.queue(terminal::SetTitle(&text));
+        self.shown = text;
+        self.updated = Some(Instant::now());
+    }
+}
+
+/// Size of a terminal cell in pixels, if the terminal reports it.
+#[cfg(unix)]
+pub fn cell_size() -> Option<(u16, u16)> {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } != 0 {
+        return None;
+    }
+    if size.ws_col == 0 || size.ws_row == 0 || size.ws_xpixel == 0 || size.ws_ypixel == 0 {
+        return None;
+    }
+    Some((size.ws_xpixel / size.ws_col, size.ws_ypixel / size.ws_row))
+}
+
+#[cfg(not(unix))]
+pub fn cell_size() -> Option<(u16, u16)> {
+    None
+}
+
+/// Undoes everything `TerminalGuard::new` did. Runs at most once per guard,
+/// errors are ignored since there's nothing better to do with them while
+/// shutting down.
+fn restore() {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let mut stdout = stdout();
+    let _ = stdout.queue(cursor::Show);
+    let _ = stdout.queue(cursor::EnableBlinking);
+    if MOUSE.swap(false, Ordering::SeqCst) {
+        let _ = stdout.queue(DisableMouseCapture);
+    }
+    if KITTY_IMAGE.swap(false, Ordering::SeqCst) {
+        let _ = stdout.queue(Print(graphics::kitty_delete()));
+    }
+    if TITLE.swap(false, Ordering::SeqCst) {
+        let _ = stdout.queue(terminal::SetTitle(""));
+        if !legacy_console() {
+            let _ = stdout.queue(Print(POP_TITLE));
+        }
+    }
+    if ALT_SCREEN.swap(false, Ordering::SeqCst) {
+        let _ = stdout.queue(terminal::LeaveAlternateScreen);
+    } else if let Ok((_, height)) = terminal::size() {
+        // leave the last frame on screen and put the prompt below it
+        let _ = stdout.queue(cursor::MoveTo(0, height.saturating_sub(1)));
+        let _ = stdout.queue(Print("\r\n"));
+    }
+    let _ = stdout.flush();
+    let _ = terminal::disable_raw_mode();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_depths() {
+        let depth = depth_of;
+        assert_eq!(
+            depth(Some("truecolor"), Some("xterm")),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(depth(Some("24bit"), None), ColorDepth::TrueColor);
+        assert_eq!(depth(None, Some("xterm-256color")), ColorDepth::Ansi256);
+        assert_eq!(
+            depth(None, Some("screen.xterm-256color")),
+            ColorDepth::Ansi256
+        );
+        // anything COLORTERM says besides 24-bit colors says nothing
+        assert_eq!(depth(Some("yes"), Some("xterm")), ColorDepth::Ansi16);
+        assert_eq!(depth(None, Some("linux")), ColorDepth::Ansi16);
+        assert_eq!(depth(None, Some("dumb")), ColorDepth::Mono);
+        assert_eq!(depth(None, None), ColorDepth::Ansi256);
+    }
+
+    #[test]
+    fn no_color() {
+        assert!(ColorChoice::Always.colors(true));
+        assert!(ColorChoice::Auto.colors(false));
+        assert!(!ColorChoice::Auto.colors(true));
+        assert!(!ColorChoice::Never.colors(false));
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(checked_size(Some((80, 24))), ((80, 24), true));
+        assert_eq!(checked_size(Some((1, 1))), ((1, 1), true));
+        assert_eq!(checked_size(Some(MAX_SIZE)), (MAX_SIZE, true));
+        // too large is clamped, each side on its own
+        assert_eq!(checked_size(Some((u16::MAX, 50))), ((MAX_SIZE.0, 50), true));
+        assert_eq!(checked_size(Some((120, 201))), ((120, MAX_SIZE.1), true));
+        assert_eq!(checked_size(Some((u16::MAX, u16::MAX))), (MAX_SIZE, true));
+        // no cells or no size at all is unknown
+        assert_eq!(checked_size(Some((0, 0))), (DEFAULT_SIZE, false));
+        assert_eq!(checked_size(Some((80, 0))), (DEFAULT_SIZE, false));
+        assert_eq!(checked_size(Some((0, 24))), (DEFAULT_SIZE, false));
+        assert_eq!(checked_size(None), (DEFAULT_SIZE, false));
+        // resizes are clamped the same way
+        let resize = checked_resize(Event::Resize(600, 40));
+        assert_eq!(resize, Event::Resize(MAX_SIZE.0, 40));
+    }
+}
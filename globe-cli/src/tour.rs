@@ -0,0 +1,246 @@
+//! The landmarks `--tour` visits, and the state of a tour between frames.
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use globe::Float;
+
+use crate::{events, parse_duration};
+
+/// How long a tour stays at a stop without a dwell time of its own.
+const DEFAULT_DWELL: Duration = Duration::from_secs(4);
+
+/// A place a tour visits.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stop {
+    pub name: String,
+    /// Position in degrees.
+    pub lat: Float,
+    pub lon: Float,
+    /// How long the tour stays once it got there.
+    pub dwell: Duration,
+}
+
+/// Stops of the built-in tour, from west to east.
+const LANDMARKS: &[(&str, Float, Float)] = &[
+    ("Golden Gate Bridge", 37.82, -122.48),
+    ("Grand Canyon", 36.10, -112.11),
+    ("Chichen Itza", 20.68, -88.57),
+    ("Statue of Liberty", 40.69, -74.04),
+    ("Machu Picchu", -13.16, -72.55),
+    ("Rio de Janeiro", -22.95, -43.21),
+    ("Reykjavik", 64.15, -21.94),
+    ("London", 51.50, -0.13),
+    ("Paris", 48.86, 2.29),
+    ("Rome", 41.89, 12.49),
+    ("Victoria Falls", -17.92, 25.86),
+    ("Pyramids of Giza", 29.98, 31.13),
+    ("Moscow", 55.75, 37.62),
+    ("Mount Kilimanjaro", -3.07, 37.35),
+    ("Taj Mahal", 27.18, 78.04),
+    ("Mount Everest", 27.99, 86.93),
+    ("Angkor Wat", 13.41, 103.87),
+    ("Great Wall of China", 40.43, 116.57),
+    ("Tokyo", 35.68, 139.77),
+    ("Sydney Opera House", -33.86, 151.22),
+];
+
+/// Stops of the built-in tour.
+pub fn landmarks() -> Vec<Stop> {
+    LANDMARKS
+        .iter()
+        .map(|&(name, lat, lon)| Stop {
+            name: name.to_string(),
+            lat,
+            lon,
+            dwell: DEFAULT_DWELL,
+        })
+        .collect()
+}
+
+/// Reads tour stops from a CSV file with a name, latitude, longitude and
+/// optionally a dwell time like `10s` per line, in that order. A first line
+/// whose latitude isn't a number is taken for a header and skipped.
+pub fn load_csv(path: &Path) -> Result<Vec<Stop>, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut stops = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let row = events::fields(line);
+        let field = |column: usize| row.get(column).map(|s| s.trim()).unwrap_or("");
+        if i == 0 && field(1).parse::<Float>().is_err() {
+            continue;
+        }
+        let number = |column: usize, what: &str| {
+            field(column)
+                .parse::<Float>()
+                .map_err(|_| format!("line {}: invalid {} `{}`", i + 1, what, field(column)))
+        };
+        let lat = number(1, "latitude")?;
+        let lon = number(2, "longitude")?;
+        if lat.abs() > 90. || lon.abs() > 180. {
+            return Err(format!("line {}: position out of range", i + 1));
+        }
+        let dwell = match field(3) {
+            "" => DEFAULT_DWELL,
+            s => parse_duration(s).map_err(|e| format!("line {}: {}", i + 1, e))?,
+        };
+        stops.push(Stop {
+            name: field(0).to_string(),
+            lat,
+            lon,
+            dwell,
+        });
+    }
+    if stops.is_empty() {
+        return Err("no stops".to_string());
+    }
+    Ok(stops)
+}
+
+/// Where a tour is: flying to a stop or staying at it, going through the
+/// stops in order and starting over after the last one.
+pub struct Tour {
+    stops: Vec<Stop>,
+    current: usize,
+    /// Time left at the current stop, or `None` before setting off to it.
+    dwell: Option<Duration>,
+}
+
+impl Tour {
+    /// Tour of `stops`, which can't be empty.
+    pub fn new(stops: Vec<Stop>) -> Tour {
+        assert!(!stops.is_empty(), "a tour needs stops");
+        Tour {
+            stops,
+            current: 0,
+            dwell: None,
+        }
+    }
+
+    /// Moves the tour on by `dt`, returning the stop to fly to if it's time
+    /// to set off. Nothing happens while `waiting`, e.g. for the flight to
+    /// a stop to land, so the time at a stop counts from the landing.
+    pub fn update(&mut self, dt: Duration, waiting: bool) -> Option<&Stop> {
+        if waiting {
+            return None;
+        }
+        match &mut self.dwell {
+            None => {
+                let stop = &self.stops[self.current];
+                self.dwell = Some(stop.dwell);
+                Some(stop)
+            }
+            Some(left) => {
+                *left = left.saturating_sub(dt);
+                if left.is_zero() {
+                    self.current = (self.current + 1) % self.stops.len();
+                    self.dwell = None;
+                }
+                None
+            }
+        }
+    }
+
+    /// The stop the tour is at or heading to.
+    pub fn stop(&self) -> &Stop {
+        &self.stops[self.current]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Frames of 40 a second.
+    const FRAME: Duration = Duration::from_millis(25);
+
+    /// Runs `tour` frame by frame for `time`, with each flight to a stop
+    /// taking `flight` like interactive mode's, and gives the stops it set
+    /// off to and when.
+    fn simulate(tour: &mut Tour, flight: Duration, time: Duration) -> Vec<(String, Duration)> {
+        let mut departures = Vec::new();
+        let mut landing = None;
+        let mut now = Duration::ZERO;
+        while now < time {
+            let waiting = landing.is_some_and(|at| now < at);
+            if let Some(stop) = tour.update(FRAME, waiting) {
+                departures.push((stop.name.clone(), now));
+                landing = Some(now + flight);
+            }
+            now += FRAME;
+        }
+        departures
+    }
+
+    fn stop(name: &str, dwell: Duration) -> Stop {
+        Stop {
+            name: name.to_string(),
+            lat: 0.,
+            lon: 0.,
+            dwell,
+        }
+    }
+
+    #[test]
+    fn stops_in_order() {
+        let dwells = [1, 2, 4].map(Duration::from_secs);
+        let stops = vec![
+            stop("a", dwells[0]),
+            stop("b", dwells[1]),
+            stop("c", dwells[2]),
+        ];
+        let flight = Duration::from_secs(3);
+        let mut tour = Tour::new(stops);
+        let departures = simulate(&mut tour, flight, Duration::from_secs(32));
+        let names: Vec<&str> = departures.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["a", "b", "c", "a", "b", "c"]);
+        assert_eq!(departures[0].1, Duration::ZERO);
+        // each stop is left its dwell after landing
+        for (i, pair) in departures.windows(2).enumerate() {
+            let dwell = dwells[i % 3];
+            assert_eq!(pair[1].1 - pair[0].1, flight + dwell, "{:?}", pair);
+        }
+    }
+
+    #[test]
+    fn waits_while_waiting() {
+        let mut tour = Tour::new(vec![stop("a", FRAME * 2), stop("b", FRAME)]);
+        assert_eq!(
+            tour.update(FRAME, false).map(|s| s.name.as_str()),
+            Some("a")
+        );
+        // a pause or a long flight doesn't use up the time at the stop
+        for _ in 0..100 {
+            assert_eq!(tour.update(FRAME, true), None);
+        }
+        assert_eq!(tour.stop().name, "a");
+        assert_eq!(tour.update(FRAME, false), None);
+        assert_eq!(tour.update(FRAME, false), None);
+        assert_eq!(tour.stop().name, "b");
+        assert_eq!(
+            tour.update(FRAME, false).map(|s| s.name.as_str()),
+            Some("b")
+        );
+    }
+
+    #[test]
+    fn landmarks_tour() {
+        let mut tour = Tour::new(landmarks());
+        let flight = Duration::from_secs(3);
+        let lap = (flight + DEFAULT_DWELL) * LANDMARKS.len() as u32;
+        let departures = simulate(&mut tour, flight, lap + FRAME);
+        // every landmark once in order, then the first again
+        assert_eq!(departures.len(), LANDMARKS.len() + 1);
+        for ((name, _), landmark) in departures.iter().zip(LANDMARKS) {
+            assert_eq!(name, landmark.0);
+        }
+        assert_eq!(
+            departures.last().unwrap(),
+            &(LANDMARKS[0].0.to_string(), lap)
+        );
+    }
+}
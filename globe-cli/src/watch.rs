@@ -0,0 +1,82 @@
+//! Frames written to a file at a fixed interval, for `--watch-file`.
+
+use std::f32::consts::PI;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use globe::{Camera, Canvas, FrameSink};
+
+use crate::{canvas_for, canvas_text, term, Settings};
+
+/// Longest sleep between checks for a termination signal.
+const TICK: Duration = Duration::from_millis(100);
+
+/// Writes a frame to `path` every `interval` until the process is told to
+/// stop, finishing the frame being written first. The globe turns by the
+/// time that passed between frames, as fast as the screensaver would at the
+/// same speed.
+pub fn run(settings: &Settings, path: &Path, interval: Duration) -> io::Result<()> {
+    term::catch_termination();
+    let mut sink = WatchFile {
+        path: path.to_path_buf(),
+    };
+    // the screensaver's default frame size when none is given
+    let mut canvas = canvas_for(settings.frame_size((40, 20)), settings);
+    let mut globe = settings.globe();
+    let (angle, cam_z) = settings.initial_view();
+    globe.angle = angle;
+    // radians per second
+    let turn_rate = PI / 50. * settings.speed * settings.fps;
+
+    let mut last = Instant::now();
+    while !term::terminated() {
+        let now = Instant::now();
+        globe.angle -= turn_rate * (now - last).as_secs_f32();
+        last = now;
+        settings.update_globe(&mut globe);
+        globe.camera = Camera::new(settings.zoom, 0., cam_z + globe.wobble_offset());
+        canvas.clear();
+        globe.render_on(&mut canvas);
+        settings.apply_effects(&mut canvas);
+        settings.layer.draw(&globe, &mut canvas);
+        sink.write_frame(&canvas)?;
+
+        let next = now + interval;
+        while !term::terminated() {
+            let left = next.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                break;
+            }
+            thread::sleep(left.min(TICK));
+        }
+    }
+    Ok(())
+}
+
+/// File holding only the latest frame.
+struct WatchFile {
+    path: PathBuf,
+}
+
+impl FrameSink for WatchFile {
+    fn write_frame(&mut self, canvas: &Canvas) -> io::Result<()> {
+        write_atomically(&self.path, &format!("{}\n", canvas_text(canvas)))
+    }
+}
+
+/// Replaces the contents of `path` so readers see either the old or the new
+/// contents, never part of them: the text goes to a temporary file in the
+/// same directory first, which is then renamed over `path`.
+fn write_atomically(path: &Path, text: &str) -> io::Result<()> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "the path has no file name"))?;
+    let mut temp = name.to_os_string();
+    temp.push(".tmp");
+    let temp: PathBuf = path.with_file_name(temp);
+    fs::write(&temp, text)?;
+    fs::rename(&temp, path)
+}
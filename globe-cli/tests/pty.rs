@@ -0,0 +1,188 @@
+//! End to end tests running the binary in a pseudo-terminal and checking
+//! what it writes to it. Windows consoles rewrite the output on its way
+//! through, so these only run on Unix.
+#![cfg(unix)]
+
+use std::io::{Read, Write};
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use portable_pty::{native_pty_system, CommandBuilder, ExitStatus, PtySize};
+
+const ENTER_ALT_SCREEN: &str = "\x1b[?1049h";
+const LEAVE_ALT_SCREEN: &str = "\x1b[?1049l";
+const HIDE_CURSOR: &str = "\x1b[?25l";
+const SHOW_CURSOR: &str = "\x1b[?25h";
+
+/// Longest a run may take before it's killed and the test fails.
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Something done to a running `globe`.
+enum Step<'a> {
+    /// Typing keys, or sending what the terminal sends for the mouse.
+    Type(&'a str),
+    /// Sending SIGTERM, like a closing session.
+    Terminate,
+}
+
+/// Runs `globe` with `args` and without a configuration file in an 80 by 24
+/// terminal, taking `steps` a second apart starting a second in, and gives
+/// everything it wrote and how it exited.
+fn run(args: &[&str], steps: &[Step]) -> (String, ExitStatus) {
+    let pair = native_pty_system()
+        .openpty(PtySize {
+            rows: 24,
+            cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .unwrap();
+    let mut command = CommandBuilder::new(env!("CARGO_BIN_EXE_globe"));
+    command.arg("--no-config");
+    command.args(args);
+    command.env("TERM", "xterm-256color");
+    let mut child = pair.slave.spawn_command(command).unwrap();
+    // reads end once the child is the only one left with the terminal open
+    drop(pair.slave);
+
+    let mut reader = pair.master.try_clone_reader().unwrap();
+    let output = thread::spawn(move || {
+        let mut output = Vec::new();
+        let mut buf = [0; 4096];
+        while let Ok(n @ 1..) = reader.read(&mut buf) {
+            output.extend_from_slice(&buf[..n]);
+        }
+        output
+    });
+
+    let mut writer = pair.master.take_writer().unwrap();
+    for step in steps {
+        thread::sleep(Duration::from_secs(1));
+        match step {
+            Step::Type(keys) => {
+                writer.write_all(keys.as_bytes()).unwrap();
+                writer.flush().unwrap();
+            }
+            Step::Terminate => {
+                let pid = child.process_id().unwrap().to_string();
+                let killed = Command::new("kill").args(["-TERM", &pid]).status();
+                assert!(killed.unwrap().success());
+            }
+        }
+    }
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break status;
+        }
+        if start.elapsed() > TIMEOUT {
+            child.kill().unwrap();
+            panic!("globe {} didn't exit", args.join(" "));
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+    drop(writer);
+    drop(pair.master);
+    let output = String::from_utf8_lossy(&output.join().unwrap()).into_owned();
+    (output, status)
+}
+
+/// Asserts a full screen run restored the terminal: it left the alternate
+/// screen it entered, showed the cursor after last hiding it, and wrote
+/// nothing after leaving.
+fn assert_restored(output: &str) {
+    assert!(
+        output.contains(ENTER_ALT_SCREEN),
+        "never entered the alternate screen"
+    );
+    let end: String = output
+        .chars()
+        .skip(output.chars().count().saturating_sub(80))
+        .collect();
+    assert!(
+        output.ends_with(LEAVE_ALT_SCREEN),
+        "didn't end by leaving the alternate screen: {:?}",
+        end
+    );
+    let hidden = output.rfind(HIDE_CURSOR).expect("never hid the cursor");
+    let shown = output.rfind(SHOW_CURSOR).expect("never showed the cursor");
+    assert!(shown > hidden, "the cursor is still hidden");
+}
+
+#[test]
+fn print() {
+    let (output, status) = run(&["--print"], &[]);
+    assert!(status.success());
+    assert!(!output.contains(ENTER_ALT_SCREEN));
+    assert!(!output.contains(HIDE_CURSOR));
+    assert!(output.contains('@'), "no globe in {:?}", output);
+}
+
+#[test]
+fn screensaver_duration() {
+    let (output, status) = run(&["-s", "--duration", "1s"], &[]);
+    assert!(status.success());
+    assert_restored(&output);
+}
+
+#[test]
+fn interactive_quit() {
+    let (output, status) = run(&["-i"], &[Step::Type("q")]);
+    assert!(status.success());
+    assert_restored(&output);
+}
+
+#[test]
+fn interactive_help() {
+    let (output, status) = run(&["-i"], &[Step::Type("?"), Step::Type("q")]);
+    assert!(status.success());
+    assert_restored(&output);
+    // the first rows of the box are over blanks, so their names are written
+    // in one go
+    for name in &["rotate_left", "tilt_up"] {
+        assert!(output.contains(name), "no {} in {:?}", name, output);
+    }
+}
+
+#[test]
+fn interactive_drag() {
+    // press, drag 10 cells to the right and release, in SGR mouse reports
+    let drag = "\x1b[<0;40;12M\x1b[<32;45;12M\x1b[<32;50;12M\x1b[<0;50;12m";
+    let (output, status) = run(
+        &["-i", "--title-coords"],
+        &[Step::Type(drag), Step::Type("q")],
+    );
+    assert!(status.success());
+    assert_restored(&output);
+    // the view turned east, so it faces west, and stayed on the equator;
+    // the blank title at the end is the one restored on exit
+    let titles: Vec<&str> = output
+        .split("\x1b]0;")
+        .skip(1)
+        .filter_map(|title| title.split('\x07').next())
+        .filter(|title| !title.is_empty())
+        .collect();
+    let first = titles.first().unwrap();
+    assert!(
+        first.starts_with("globe \u{2014} 0.0\u{b0}N 0.0\u{b0}E"),
+        "{:?}",
+        titles
+    );
+    let last = titles.last().unwrap();
+    assert!(
+        last.contains("0.0\u{b0}N") && last.contains("\u{b0}W"),
+        "{:?}",
+        titles
+    );
+}
+
+#[test]
+fn terminated() {
+    for args in [&["-s"][..], &["-i"]] {
+        let (output, status) = run(args, &[Step::Terminate]);
+        // exits with 128 + SIGTERM
+        assert_eq!(status.exit_code(), 143, "globe {}", args.join(" "));
+        assert_restored(&output);
+    }
+}
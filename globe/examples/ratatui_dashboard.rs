@@ -0,0 +1,88 @@
+//! A ratatui dashboard with the globe in one pane and a list of cities in
+//! the other. Up and down pick a city for the globe to turn to, q quits.
+//!
+//! Run with `cargo run --example ratatui_dashboard --features ratatui`.
+
+use std::io;
+use std::time::Duration;
+
+use globe::{Camera, GlobeConfig, GlobeState, GlobeTemplate, GlobeWidget};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, List, ListState};
+
+const CITIES: [(&str, f32, f32); 6] = [
+    ("London", 51.5, -0.1),
+    ("New York", 40.7, -74.0),
+    ("Rio de Janeiro", -22.9, -43.2),
+    ("Cairo", 30.0, 31.2),
+    ("Tokyo", 35.7, 139.7),
+    ("Sydney", -33.9, 151.2),
+];
+
+fn main() -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let globe = GlobeConfig::new()
+        .use_template(GlobeTemplate::Earth)
+        .build();
+    let mut state = GlobeState::new(globe);
+    let mut list = ListState::default().with_selected(Some(0));
+    // view center as (latitude, longitude) in radians, eased towards the
+    // selected city every frame
+    let mut view = (0f32, 0f32);
+
+    let result = loop {
+        let (_, lat, lon) = CITIES[list.selected().unwrap_or(0)];
+        let target = (lat.to_radians(), lon.to_radians());
+        view.0 += (target.0 - view.0) * 0.1;
+        view.1 += (target.1 - view.1) * 0.1;
+        // the view center is at longitude -angle, and the camera tilt is
+        // the latitude looked at
+        state.globe.angle = -view.1;
+        state.globe.camera = Camera::new(2., 0., view.0);
+
+        // ratatui resizes the buffer to the terminal on every draw, and the
+        // widget sizes the globe to its pane
+        let drawn = terminal.draw(|frame| {
+            let [left, right] =
+                Layout::horizontal([Constraint::Percentage(60), Constraint::Fill(1)])
+                    .areas(frame.area());
+            let pane = Block::bordered().title(" Globe ");
+            let inner = pane.inner(left);
+            frame.render_widget(pane, left);
+            frame.render_stateful_widget(GlobeWidget::new().shade_colors(), inner, &mut state);
+
+            let names = CITIES.iter().map(|&(name, _, _)| name);
+            let cities = List::new(names)
+                .block(Block::bordered().title(" Cities "))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+            frame.render_stateful_widget(cities, right, &mut list);
+        });
+        if let Err(e) = drawn {
+            break Err(e);
+        }
+
+        match event::poll(Duration::from_millis(33)) {
+            Ok(false) => continue,
+            Ok(true) => (),
+            Err(e) => break Err(e),
+        }
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => break Ok(()),
+                KeyCode::Up => list.select_previous(),
+                KeyCode::Down => list.select_next(),
+                _ => (),
+            },
+            // the next draw picks up the new size
+            Ok(_) => (),
+            Err(e) => break Err(e),
+        }
+        if list.selected().is_some_and(|i| i >= CITIES.len()) {
+            list.select(Some(CITIES.len() - 1));
+        }
+    };
+    ratatui::restore();
+    result
+}
@@ -17,11 +17,5 @@ fn main() {
     globe.render_on(&mut canvas);
 
     // print out the canvas
-    let (size_x, size_y) = canvas.get_size();
-    for i in 0..size_y / 8 {
-        for j in 0..size_x / 4 {
-            print!("{}", canvas.matrix[i][j]);
-        }
-        println!();
-    }
+    globe::print_frame(&canvas, &mut std::io::stdout()).unwrap();
 }
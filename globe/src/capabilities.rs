@@ -0,0 +1,37 @@
+//! What this build of the library supports, see `capabilities`.
+
+use crate::{GlobeTemplate, Theme};
+
+/// Version, optional features and built-in assets of the library, for
+/// programs that adapt to what the build they're linked against can do.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Capabilities {
+    /// Version of the `globe` crate.
+    pub version: &'static str,
+    /// Cargo features it was built with, like `geojson`.
+    pub features: Vec<&'static str>,
+    /// Names of the built-in textures, see `GlobeTemplate::name`.
+    pub templates: Vec<&'static str>,
+    /// Names of the built-in themes, see `Theme::by_name`.
+    pub themes: Vec<&'static str>,
+}
+
+/// What this build of the library supports.
+pub fn capabilities() -> Capabilities {
+    let features = [
+        ("geojson", cfg!(feature = "geojson")),
+        ("ratatui", cfg!(feature = "ratatui")),
+        ("serde", cfg!(feature = "serde")),
+    ];
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        features: features
+            .iter()
+            .filter(|&&(_, on)| on)
+            .map(|&(name, _)| name)
+            .collect(),
+        templates: GlobeTemplate::ALL.iter().map(|t| t.name()).collect(),
+        themes: Theme::NAMES.to_vec(),
+    }
+}
@@ -0,0 +1,551 @@
+//! Colors, and their nearest match on terminals with fewer of them, and
+//! coloring rendered globes with them.
+
+use crate::{find_index, Canvas, Float, Globe, PALETTE};
+
+/// The 16 standard colors as xterm shows them by default: black, red,
+/// green, yellow, blue, magenta, cyan and white, then their bright versions.
+const ANSI16: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Channel values of the 6x6x6 color cube of the 256-color palette.
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// A 24-bit color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color { r, g, b }
+    }
+
+    /// The color `t` of the way from this one to `other`, `t` being from 0
+    /// to 1.
+    pub fn mix(self, other: Color, t: Float) -> Color {
+        let t = t.clamp(0., 1.);
+        let channel = |a: u8, b: u8| (a as Float + (b as Float - a as Float) * t).round() as u8;
+        Color::rgb(
+            channel(self.r, other.r),
+            channel(self.g, other.g),
+            channel(self.b, other.b),
+        )
+    }
+
+    /// Color of an entry of the 256-color palette, with the first 16 as
+    /// xterm shows them by default.
+    pub fn from_ansi256(index: u8) -> Color {
+        match index {
+            0..=15 => {
+                let (r, g, b) = ANSI16[index as usize];
+                Color::rgb(r, g, b)
+            }
+            16..=231 => {
+                let i = (index - 16) as usize;
+                Color::rgb(CUBE[i / 36], CUBE[i / 6 % 6], CUBE[i % 6])
+            }
+            _ => {
+                let level = 8 + 10 * (index - 232);
+                Color::rgb(level, level, level)
+            }
+        }
+    }
+
+    /// Nearest entry of the 256-color palette, out of the color cube and the
+    /// gray ramp. The first 16 entries are left out since terminals change
+    /// them with their theme.
+    pub fn to_ansi256(self) -> u8 {
+        let cube = |v: u8| match v {
+            0..=47 => 0,
+            48..=114 => 1,
+            _ => (v - 35) / 40,
+        };
+        let cube = 16 + 36 * cube(self.r) + 6 * cube(self.g) + cube(self.b);
+
+        let mean = (self.r as u16 + self.g as u16 + self.b as u16) / 3;
+        let gray = 232 + (mean.saturating_sub(3) / 10).min(23) as u8;
+
+        match self.distance(Color::from_ansi256(gray)) < self.distance(Color::from_ansi256(cube)) {
+            true => gray,
+            false => cube,
+        }
+    }
+
+    /// Nearest of the 16 standard colors, 0 to 7 for the normal ones and 8
+    /// to 15 for the bright ones.
+    pub fn to_ansi16(self) -> u8 {
+        (0..16)
+            .min_by_key(|&i| self.distance(Color::from_ansi256(i)))
+            .unwrap()
+    }
+
+    /// Relative luminance from 0 for black to 1 for white, how bright the
+    /// color looks with the channels weighted as sRGB does.
+    pub fn luminance(self) -> Float {
+        let linear = |v: u8| {
+            let v = v as Float / 255.;
+            match v <= 0.04045 {
+                true => v / 12.92,
+                false => ((v + 0.055) / 1.055).powf(2.4),
+            }
+        };
+        0.2126 * linear(self.r) + 0.7152 * linear(self.g) + 0.0722 * linear(self.b)
+    }
+
+    /// Contrast ratio between two colors from their luminance, from 1 for
+    /// the same brightness to 21 for black on white. Text is usually
+    /// considered readable from about 3, and red and green of the same
+    /// brightness, which some can't tell apart, come out close to 1.
+    pub fn contrast(self, other: Color) -> Float {
+        let (a, b) = (self.luminance(), other.luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    /// How different two colors look, roughly: the squared difference of
+    /// the channels, weighted by how sensitive the eye is to each.
+    fn distance(self, other: Color) -> u32 {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+        2 * d(self.r, other.r) + 4 * d(self.g, other.g) + 3 * d(self.b, other.b)
+    }
+}
+
+/// How `Globe::cell_colors` colors a globe.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorMode {
+    /// Shades of gray following the shading characters, for any texture.
+    Shade,
+    /// Ocean, land and ice tinted by latitude as the `Theme` says, telling
+    /// them apart with `Globe::is_land`.
+    Stylized,
+    /// Heights of the globe's heightmap tinted by its `ElevationRamp`, see
+    /// `Globe::elevation`. Land the heightmap has no height for is at sea
+    /// level, and sea without a depth takes the lowest stop's color.
+    Elevation,
+}
+
+/// Hypsometric tint of `ColorMode::Elevation`: colors at heights in
+/// meters, blended in between.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ElevationRamp {
+    /// Heights and their colors, from the lowest height up.
+    stops: Vec<(Float, Color)>,
+}
+
+impl ElevationRamp {
+    /// Ramp through stops of a height in meters and its color, given in
+    /// any order. `None` without stops or with a height that isn't finite.
+    pub fn new(mut stops: Vec<(Float, Color)>) -> Option<ElevationRamp> {
+        if stops.is_empty() || stops.iter().any(|(height, _)| !height.is_finite()) {
+            return None;
+        }
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Some(ElevationRamp { stops })
+    }
+
+    /// The stops, from the lowest height up.
+    pub fn stops(&self) -> &[(Float, Color)] {
+        &self.stops
+    }
+
+    /// Color at `height` meters. Heights below the lowest stop or above
+    /// the highest take its color.
+    pub fn color(&self, height: Float) -> Color {
+        let above = self.stops.iter().position(|&(h, _)| h > height);
+        match above {
+            Some(0) => self.stops[0].1,
+            None => self.stops[self.stops.len() - 1].1,
+            Some(i) => {
+                let ((low, from), (high, to)) = (self.stops[i - 1], self.stops[i]);
+                from.mix(to, (height - low) / (high - low))
+            }
+        }
+    }
+}
+
+impl Default for ElevationRamp {
+    /// Deep blue sea floor, green lowlands, brown mountains and white
+    /// peaks.
+    fn default() -> Self {
+        ElevationRamp {
+            stops: vec![
+                (-6000., Color::rgb(15, 45, 125)),
+                (-1., Color::rgb(40, 110, 195)),
+                (0., Color::rgb(50, 135, 65)),
+                (1000., Color::rgb(170, 165, 90)),
+                (2500., Color::rgb(135, 90, 50)),
+                (4500., Color::rgb(245, 245, 245)),
+            ],
+        }
+    }
+}
+
+/// Colors and thresholds of `ColorMode::Stylized`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    /// Ocean at the equator, darkening towards `ocean_pole` at the poles.
+    pub ocean_equator: Color,
+    pub ocean_pole: Color,
+    /// Land at the equator, browning towards `land_pole` at the ice.
+    pub land_equator: Color,
+    pub land_pole: Color,
+    pub ice: Color,
+    /// Latitude in degrees, north or south, beyond which everything is ice.
+    pub ice_latitude: Float,
+    /// Markers, labels and other characters drawn over the globe.
+    pub marker: Color,
+    /// The terminal background around the globe, which isn't colored; only
+    /// for `contrast_report`.
+    pub background: Color,
+    /// Shading characters the theme is made for, if any, used unless others
+    /// are chosen.
+    pub palette: Option<&'static [char]>,
+}
+
+const MATRIX_PALETTE: [char; 8] = [' ', '.', ':', '-', '=', '+', '1', '0'];
+const AMBER_PALETTE: [char; 10] = [' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+const MONO_PALETTE: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            ocean_equator: Color::rgb(30, 110, 210),
+            ocean_pole: Color::rgb(15, 45, 110),
+            land_equator: Color::rgb(40, 160, 60),
+            land_pole: Color::rgb(150, 115, 70),
+            ice: Color::rgb(240, 245, 250),
+            ice_latitude: 70.,
+            marker: Color::rgb(255, 255, 255),
+            background: Color::rgb(0, 0, 0),
+            palette: None,
+        }
+    }
+}
+
+/// Contrast ratios between the parts of a `Theme`, see `Color::contrast`.
+/// Land and ocean change color with latitude, and each ratio is the lowest
+/// over those colors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ContrastReport {
+    pub land_ocean: Float,
+    pub marker_land: Float,
+    pub marker_ocean: Float,
+    pub land_background: Float,
+    pub ocean_background: Float,
+}
+
+impl ContrastReport {
+    /// Lowest contrast of markers against the terrain they're drawn on.
+    pub fn marker_terrain(&self) -> Float {
+        self.marker_land.min(self.marker_ocean)
+    }
+}
+
+impl Theme {
+    /// Names of the built-in themes, for `by_name`.
+    pub const NAMES: [&'static str; 8] = [
+        "default", "classic", "matrix", "amber", "mono", "natural", "cb-warm", "cb-cool",
+    ];
+
+    /// Built-in theme by one of its `NAMES`.
+    pub fn by_name(name: &str) -> Option<Theme> {
+        match name {
+            "default" => Some(Theme::default()),
+            "classic" => Some(Theme::classic()),
+            "matrix" => Some(Theme::matrix()),
+            "amber" => Some(Theme::amber()),
+            "mono" => Some(Theme::mono()),
+            "natural" => Some(Theme::natural()),
+            "cb-warm" => Some(Theme::cb_warm()),
+            "cb-cool" => Some(Theme::cb_cool()),
+            _ => None,
+        }
+    }
+
+    /// The default colors with the default shading characters, whatever
+    /// a texture pack suggests.
+    pub fn classic() -> Theme {
+        Theme {
+            palette: Some(&PALETTE),
+            ..Theme::default()
+        }
+    }
+
+    /// Bright green land on a black ocean, drawn in digits.
+    pub fn matrix() -> Theme {
+        Theme {
+            ocean_equator: Color::rgb(0, 45, 10),
+            ocean_pole: Color::rgb(0, 20, 5),
+            land_equator: Color::rgb(0, 255, 65),
+            land_pole: Color::rgb(0, 170, 45),
+            ice: Color::rgb(190, 255, 190),
+            ice_latitude: 70.,
+            marker: Color::rgb(220, 255, 220),
+            background: Color::rgb(0, 0, 0),
+            palette: Some(&MATRIX_PALETTE),
+        }
+    }
+
+    /// Shades of amber, like an old monochrome monitor.
+    pub fn amber() -> Theme {
+        Theme {
+            ocean_equator: Color::rgb(70, 35, 0),
+            ocean_pole: Color::rgb(35, 18, 0),
+            land_equator: Color::rgb(255, 176, 0),
+            land_pole: Color::rgb(205, 135, 0),
+            ice: Color::rgb(255, 225, 150),
+            ice_latitude: 70.,
+            marker: Color::rgb(255, 245, 215),
+            background: Color::rgb(0, 0, 0),
+            palette: Some(&AMBER_PALETTE),
+        }
+    }
+
+    /// Shades of gray, drawn in block characters.
+    pub fn mono() -> Theme {
+        Theme {
+            ocean_equator: Color::rgb(60, 60, 60),
+            ocean_pole: Color::rgb(35, 35, 35),
+            land_equator: Color::rgb(185, 185, 185),
+            land_pole: Color::rgb(150, 150, 150),
+            ice: Color::rgb(245, 245, 245),
+            ice_latitude: 70.,
+            marker: Color::rgb(255, 255, 255),
+            background: Color::rgb(0, 0, 0),
+            palette: Some(&MONO_PALETTE),
+        }
+    }
+
+    /// Muted greens and browns on a deep blue ocean, with ice from 65
+    /// degrees, closer to how the Earth looks from space.
+    pub fn natural() -> Theme {
+        Theme {
+            ocean_equator: Color::rgb(20, 70, 125),
+            ocean_pole: Color::rgb(10, 35, 70),
+            land_equator: Color::rgb(95, 130, 55),
+            land_pole: Color::rgb(140, 120, 90),
+            ice: Color::rgb(235, 240, 240),
+            ice_latitude: 65.,
+            marker: Color::rgb(255, 230, 120),
+            background: Color::rgb(0, 0, 0),
+            palette: None,
+        }
+    }
+
+    /// Theme for deuteranopia and protanopia telling land from ocean by
+    /// brightness and by orange against blue, never by red against green.
+    pub fn cb_warm() -> Theme {
+        Theme {
+            ocean_equator: Color::rgb(15, 45, 125),
+            ocean_pole: Color::rgb(10, 25, 75),
+            land_equator: Color::rgb(200, 125, 35),
+            land_pole: Color::rgb(160, 110, 70),
+            ice: Color::rgb(245, 245, 240),
+            ice_latitude: 70.,
+            marker: Color::rgb(255, 255, 255),
+            background: Color::rgb(0, 0, 0),
+            palette: None,
+        }
+    }
+
+    /// Like `cb_warm`, with slate gray land on a navy ocean.
+    pub fn cb_cool() -> Theme {
+        Theme {
+            ocean_equator: Color::rgb(15, 40, 110),
+            ocean_pole: Color::rgb(5, 20, 65),
+            land_equator: Color::rgb(115, 140, 165),
+            land_pole: Color::rgb(130, 130, 140),
+            ice: Color::rgb(250, 250, 255),
+            ice_latitude: 70.,
+            marker: Color::rgb(255, 250, 220),
+            background: Color::rgb(0, 0, 0),
+            palette: None,
+        }
+    }
+
+    /// Approximate contrast between land, ocean, markers and the
+    /// background, to check a theme is readable before using it.
+    pub fn contrast_report(&self) -> ContrastReport {
+        let land = [self.land_equator, self.land_pole];
+        let ocean = [self.ocean_equator, self.ocean_pole];
+        let lowest = |a: &[Color], b: &[Color]| {
+            a.iter()
+                .flat_map(|&a| b.iter().map(move |&b| a.contrast(b)))
+                .fold(Float::INFINITY, Float::min)
+        };
+        ContrastReport {
+            land_ocean: lowest(&land, &ocean),
+            marker_land: lowest(&[self.marker], &land),
+            marker_ocean: lowest(&[self.marker], &ocean),
+            land_background: lowest(&land, &[self.background]),
+            ocean_background: lowest(&ocean, &[self.background]),
+        }
+    }
+
+    /// Color of land or ocean at `lat` degrees.
+    pub fn color(&self, lat: Float, land: bool) -> Color {
+        let lat = lat.abs();
+        match land {
+            _ if lat >= self.ice_latitude => self.ice,
+            true => self
+                .land_equator
+                .mix(self.land_pole, lat / self.ice_latitude),
+            false => self.ocean_equator.mix(self.ocean_pole, lat / 90.),
+        }
+    }
+}
+
+impl Globe {
+    /// Colors for the characters of a canvas the globe was just rendered
+    /// on, by row and column, with `None` for the cells off the globe.
+    /// Markers and other characters drawn over the globe get the color of
+    /// the surface under them in shades of gray, and the theme's marker
+    /// color when stylized. `theme` is usually the globe's own.
+    pub fn cell_colors(
+        &self,
+        canvas: &Canvas,
+        mode: ColorMode,
+        theme: &Theme,
+    ) -> Vec<Vec<Option<Color>>> {
+        let (width, height) = canvas.grid_size();
+        let top = (canvas.palette.len() - 1).max(1) as Float;
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| {
+                        let p = self.cast_ray(canvas, x, y)?;
+                        let (lat, lon) = self.surface_coords(&p);
+                        Some(match mode {
+                            ColorMode::Shade => {
+                                let level = find_index(canvas.matrix[y][x], &canvas.palette).max(0)
+                                    as Float;
+                                let gray = (80. + 175. * level / top) as u8;
+                                Color::rgb(gray, gray, gray)
+                            }
+                            ColorMode::Stylized | ColorMode::Elevation
+                                if find_index(canvas.matrix[y][x], &canvas.palette) < 0 =>
+                            {
+                                theme.marker
+                            }
+                            ColorMode::Elevation => {
+                                let (lat, lon) = (lat.to_degrees(), lon.to_degrees());
+                                let ramp = &self.elevation_ramp;
+                                match self.elevation(lat, lon) {
+                                    Some(height) => ramp.color(height),
+                                    None if self.is_land(lat, lon) => ramp.color(0.),
+                                    None => ramp.stops()[0].1,
+                                }
+                            }
+                            ColorMode::Stylized => {
+                                let shine = self.specular(&p, lat, lon);
+                                let (lat, lon) = (lat.to_degrees(), lon.to_degrees());
+                                let color = theme.color(lat, self.is_land(lat, lon));
+                                color.mix(Color::rgb(255, 255, 255), shine)
+                            }
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GlobeConfig;
+
+    #[test]
+    fn elevation_ramp() {
+        let (blue, green, white) = (
+            Color::rgb(0, 0, 200),
+            Color::rgb(0, 200, 0),
+            Color::rgb(250, 250, 250),
+        );
+        // stops in any order, sorted by height
+        let ramp = ElevationRamp::new(vec![(4000., white), (-100., blue), (0., green)]).unwrap();
+        assert_eq!(ramp.stops()[0], (-100., blue));
+        assert_eq!(ramp.color(0.), green);
+        assert_eq!(ramp.color(2000.), green.mix(white, 0.5));
+        // past either end the nearest stop's color, not wrapping around
+        assert_eq!(ramp.color(-11000.), blue);
+        assert_eq!(ramp.color(9000.), white);
+        assert_eq!(ElevationRamp::new(Vec::new()), None);
+        assert_eq!(ElevationRamp::new(vec![(Float::NAN, blue)]), None);
+    }
+
+    #[test]
+    fn elevation() {
+        // land in the western half, a 3000 meter high eastern quarter of it
+        let globe = GlobeConfig::new()
+            .with_texture("@@::\n@@::")
+            .with_heightmap("03  \n0   ")
+            .build();
+        assert_eq!(globe.elevation(45., -135.), Some(0.));
+        assert_eq!(globe.elevation(45., -45.), Some(3000.));
+        assert_eq!(globe.elevation(-45., -45.), None);
+        assert_eq!(globe.elevation(45., 90.), None);
+        assert_eq!(
+            GlobeConfig::new()
+                .with_texture("@")
+                .build()
+                .elevation(0., 0.),
+            None
+        );
+    }
+
+    #[test]
+    fn ansi256() {
+        let ansi = |r, g, b| Color::rgb(r, g, b).to_ansi256();
+        // exact entries of the cube and the gray ramp
+        assert_eq!(ansi(0, 0, 0), 16);
+        assert_eq!(ansi(255, 255, 255), 231);
+        assert_eq!(ansi(255, 0, 0), 196);
+        assert_eq!(ansi(95, 135, 175), 67);
+        assert_eq!(ansi(128, 128, 128), 244);
+        assert_eq!(ansi(238, 238, 238), 255);
+        // in between, the nearest
+        assert_eq!(ansi(250, 10, 5), 196);
+        assert_eq!(ansi(100, 101, 99), 241);
+        assert_eq!(ansi(30, 110, 210), 26);
+        // every entry found again, the 16 themed ones left out
+        for i in 16..=255 {
+            let color = Color::from_ansi256(i);
+            assert_eq!(color.to_ansi256(), i, "{:?}", color);
+        }
+    }
+
+    #[test]
+    fn ansi16() {
+        let ansi = |r, g, b| Color::rgb(r, g, b).to_ansi16();
+        assert_eq!(ansi(0, 0, 0), 0);
+        assert_eq!(ansi(255, 255, 255), 15);
+        assert_eq!(ansi(200, 10, 10), 1);
+        assert_eq!(ansi(255, 20, 20), 9);
+        assert_eq!(ansi(10, 10, 230), 4);
+        assert_eq!(ansi(120, 130, 125), 8);
+        assert_eq!(ansi(220, 225, 230), 7);
+        for i in 0..16 {
+            assert_eq!(Color::from_ansi256(i).to_ansi16(), i);
+        }
+    }
+}
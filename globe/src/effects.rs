@@ -0,0 +1,84 @@
+//! Post-processing passes over a finished canvas, see `Canvas::apply`.
+//!
+//! Effects change shading characters by steps along the canvas palette, so
+//! they work with any palette; markers, text and other characters outside
+//! of it are left alone. Each one only depends on the canvas it's given.
+
+use crate::{Canvas, Float};
+
+/// A pass over the characters of a canvas.
+pub trait Effect {
+    fn apply(&self, canvas: &mut Canvas);
+}
+
+/// Dims every other row by a shade, like the scanlines of an old CRT.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Scanlines;
+
+impl Effect for Scanlines {
+    fn apply(&self, canvas: &mut Canvas) {
+        let (width, height) = canvas.grid_size();
+        for y in (1..height).step_by(2) {
+            for x in 0..width {
+                canvas.matrix[y][x] = canvas.shift_shade(canvas.matrix[y][x], -1);
+            }
+        }
+    }
+}
+
+/// Dims characters more the further they are from the center, up to
+/// `strength` shades in the corners of the ellipse touching the edges.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vignette {
+    pub strength: Float,
+}
+
+impl Default for Vignette {
+    fn default() -> Self {
+        Vignette { strength: 4. }
+    }
+}
+
+impl Effect for Vignette {
+    fn apply(&self, canvas: &mut Canvas) {
+        let (width, height) = canvas.grid_size();
+        let (half_x, half_y) = (width as Float / 2., height as Float / 2.);
+        for y in 0..height {
+            for x in 0..width {
+                let dx = (x as Float + 0.5 - half_x) / half_x;
+                let dy = (y as Float + 0.5 - half_y) / half_y;
+                let steps = ((dx * dx + dy * dy) * self.strength).floor() as isize;
+                canvas.matrix[y][x] = canvas.shift_shade(canvas.matrix[y][x], -steps);
+            }
+        }
+    }
+}
+
+/// Brightens the neighbors of the brightest characters by a shade, so the
+/// brightest areas seem to glow into their surroundings, blank ones
+/// included.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Glow;
+
+impl Effect for Glow {
+    fn apply(&self, canvas: &mut Canvas) {
+        let (width, height) = canvas.grid_size();
+        let brightest = *canvas.palette.last().unwrap();
+        let lit: Vec<Vec<bool>> = (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| canvas.matrix[y][x] == brightest)
+                    .collect()
+            })
+            .collect();
+        for y in 0..height {
+            for x in 0..width {
+                let near = (y.saturating_sub(1)..(y + 2).min(height))
+                    .any(|ny| (x.saturating_sub(1)..(x + 2).min(width)).any(|nx| lit[ny][nx]));
+                if near && !lit[y][x] {
+                    canvas.matrix[y][x] = canvas.shift_shade(canvas.matrix[y][x], 1);
+                }
+            }
+        }
+    }
+}
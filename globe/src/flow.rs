@@ -0,0 +1,250 @@
+//! Particles drifting along a vector field, like winds or ocean currents,
+//! see `Globe::set_flow_field`.
+
+use std::time::Duration;
+
+use crate::{Canvas, Float, Globe, Tally};
+
+/// Particles past this latitude in degrees die, so they don't bunch up
+/// where the meridians meet.
+const MAX_LATITUDE: Float = 80.;
+
+/// Tries at finding open ocean for a new particle before settling for
+/// wherever the last try landed.
+const SPAWN_TRIES: usize = 16;
+
+/// Share of a particle's life at either end drawn as a dot, so particles
+/// fade in and out instead of popping up.
+const FADE: Float = 0.2;
+
+/// Vectors on an evenly spaced grid of longitudes and latitudes, like wind
+/// or ocean current speeds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlowField {
+    cols: usize,
+    rows: usize,
+    /// Eastward and northward speeds in degrees of arc per second, row by
+    /// row from north to south.
+    vectors: Vec<(Float, Float)>,
+}
+
+impl FlowField {
+    /// Field of `cols` by `rows` vectors, given row by row from north to
+    /// south and west to east starting at the antimeridian. Each is an
+    /// eastward and northward speed in degrees of arc per second, taken at
+    /// the middle of its cell.
+    pub fn new(cols: usize, rows: usize, vectors: Vec<(Float, Float)>) -> FlowField {
+        assert!(cols > 0 && rows > 0, "flow field can't be empty");
+        assert_eq!(
+            vectors.len(),
+            cols * rows,
+            "flow field needs cols * rows vectors"
+        );
+        FlowField {
+            cols,
+            rows,
+            vectors,
+        }
+    }
+
+    /// Field of `cols` by `rows` vectors from a function of the latitude
+    /// and longitude in degrees of each cell's middle.
+    pub fn from_fn(
+        cols: usize,
+        rows: usize,
+        f: impl Fn(Float, Float) -> (Float, Float),
+    ) -> FlowField {
+        let vectors = (0..rows)
+            .flat_map(|y| (0..cols).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let lat = 90. - (y as Float + 0.5) * 180. / rows as Float;
+                let lon = -180. + (x as Float + 0.5) * 360. / cols as Float;
+                f(lat, lon)
+            })
+            .collect();
+        FlowField::new(cols, rows, vectors)
+    }
+
+    /// Vector at a position in degrees, interpolated between the four
+    /// nearest cells. Longitudes wrap around; latitudes past the middle of
+    /// the first or last row take its vectors.
+    pub fn at(&self, lat: Float, lon: Float) -> (Float, Float) {
+        let x = (lon + 180.) / 360. * self.cols as Float - 0.5;
+        let y = ((90. - lat) / 180. * self.rows as Float - 0.5).clamp(0., (self.rows - 1) as Float);
+        let (fx, fy) = (x - x.floor(), y - y.floor());
+        let x0 = (x.floor() as isize).rem_euclid(self.cols as isize) as usize;
+        let x1 = (x0 + 1) % self.cols;
+        let y0 = y.floor() as usize;
+        let y1 = (y0 + 1).min(self.rows - 1);
+        let get = |x: usize, y: usize| self.vectors[y * self.cols + x];
+        let lerp = |a: (Float, Float), b: (Float, Float), t: Float| {
+            (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+        };
+        let top = lerp(get(x0, y0), get(x1, y0), fx);
+        let bottom = lerp(get(x0, y1), get(x1, y1), fx);
+        lerp(top, bottom, fy)
+    }
+
+    /// The field with every vector `factor` times as fast.
+    pub fn scaled(mut self, factor: Float) -> FlowField {
+        for (u, v) in &mut self.vectors {
+            *u *= factor;
+            *v *= factor;
+        }
+        self
+    }
+
+    /// Speed of the fastest vector.
+    pub fn max_speed(&self) -> Float {
+        self.vectors
+            .iter()
+            .map(|&(u, v)| u.hypot(v))
+            .fold(0., Float::max)
+    }
+}
+
+/// A point drifting along the field.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Particle {
+    /// Position in degrees.
+    pub lat: Float,
+    pub lon: Float,
+    /// Time since it was spawned.
+    pub age: Duration,
+}
+
+/// Particles moving along a `FlowField`, see `Globe::set_flow_field`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Flow {
+    pub field: FlowField,
+    pub particles: Vec<Particle>,
+    /// How long a particle lives before it's spawned again elsewhere.
+    pub lifetime: Duration,
+    /// State of the generator picking where particles spawn.
+    seed: u32,
+}
+
+impl Flow {
+    /// Next pseudo-random number in `[0, 1)`, from a xorshift generator.
+    fn random(&mut self) -> Float {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        (self.seed >> 8) as Float / (1 << 24) as Float
+    }
+}
+
+impl Globe {
+    /// Animates `particle_count` particles drifting along `field` over the
+    /// ocean, each living for `lifetime` before it's spawned again
+    /// somewhere else. `advance` moves them.
+    ///
+    /// Particles spawn evenly over the sphere rather than the map, and die
+    /// on reaching land or the polar regions, so they don't bunch up where
+    /// the meridians meet. Their ages start spread over the lifetime, so
+    /// they don't all spawn again at once.
+    pub fn set_flow_field(&mut self, field: FlowField, particle_count: usize, lifetime: Duration) {
+        assert!(!lifetime.is_zero(), "particle lifetime can't be zero");
+        let mut flow = Flow {
+            field,
+            particles: Vec::with_capacity(particle_count),
+            lifetime,
+            seed: 0x2545_f491,
+        };
+        for _ in 0..particle_count {
+            let mut particle = self.spawn(&mut flow);
+            particle.age = lifetime.mul_f32(flow.random());
+            flow.particles.push(particle);
+        }
+        self.flow = Some(flow);
+    }
+
+    /// A new particle at a random position, on open ocean if one turns up
+    /// within a few tries.
+    fn spawn(&self, flow: &mut Flow) -> Particle {
+        let mut particle = Particle {
+            lat: 0.,
+            lon: 0.,
+            age: Duration::default(),
+        };
+        for _ in 0..SPAWN_TRIES {
+            // even over the sphere: the sine of the latitude is uniform
+            particle.lat = (flow.random() * 2. - 1.).asin().to_degrees();
+            particle.lon = flow.random() * 360. - 180.;
+            if self.flows_over(particle.lat, particle.lon) {
+                break;
+            }
+        }
+        particle
+    }
+
+    /// Whether particles can be at a position in degrees.
+    fn flows_over(&self, lat: Float, lon: Float) -> bool {
+        lat.abs() < MAX_LATITUDE && !self.is_land(lat, lon)
+    }
+
+    /// Moves the particles `dt` along the field, spawning the ones that
+    /// died again.
+    pub(crate) fn advance_flow(&mut self, dt: Duration) {
+        let mut flow = match self.flow.take() {
+            Some(flow) => flow,
+            None => return,
+        };
+        for i in 0..flow.particles.len() {
+            let mut p = flow.particles[i];
+            p.age += dt;
+            if p.age >= flow.lifetime {
+                // keep the spread of ages through long steps
+                let age =
+                    Duration::from_secs_f64(p.age.as_secs_f64() % flow.lifetime.as_secs_f64());
+                p = self.spawn(&mut flow);
+                p.age = age;
+            } else {
+                let (u, v) = flow.field.at(p.lat, p.lon);
+                let secs = dt.as_secs_f32();
+                // degrees of longitude grow shorter away from the equator
+                let cos_lat = p.lat.to_radians().cos().max(0.1);
+                p.lat += v * secs;
+                p.lon = (p.lon + u * secs / cos_lat + 180.).rem_euclid(360.) - 180.;
+                if !self.flows_over(p.lat, p.lon) {
+                    p = self.spawn(&mut flow);
+                }
+            }
+            flow.particles[i] = p;
+        }
+        self.flow = Some(flow);
+    }
+
+    /// Draws the particles at the cells `project` gives for their
+    /// positions: a dot while they fade in and out, and in between a dash,
+    /// or a tilde where the field is faster than half its top speed.
+    pub(crate) fn draw_flow(
+        &self,
+        canvas: &mut Canvas,
+        project: impl Fn(&Canvas, Float, Float) -> Option<(usize, usize)>,
+        tally: &mut impl Tally,
+    ) {
+        let flow = match &self.flow {
+            Some(flow) => flow,
+            None => return,
+        };
+        let top = flow.field.max_speed();
+        for p in &flow.particles {
+            let (x, y) = match project(canvas, p.lat, p.lon) {
+                Some(cell) => cell,
+                None => {
+                    tally.cull();
+                    continue;
+                }
+            };
+            let stage = p.age.as_secs_f32() / flow.lifetime.as_secs_f32();
+            let (u, v) = flow.field.at(p.lat, p.lon);
+            let c = match stage {
+                _ if !(FADE..=1. - FADE).contains(&stage) => '·',
+                _ if u.hypot(v) > top / 2. => '~',
+                _ => '-',
+            };
+            canvas.draw_point(x, y, c);
+        }
+    }
+}
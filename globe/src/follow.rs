@@ -0,0 +1,169 @@
+//! Camera smoothly following a moving position, see `Globe::follow`.
+
+use std::time::Duration;
+
+use crate::{angular_distance, great_circle, Float, Globe};
+
+/// Stiffness a globe follows with until `Globe::set_follow_stiffness`.
+const DEFAULT_STIFFNESS: Float = 4.;
+
+/// Views closer than this in degrees to the target are moved onto it, as
+/// they'd otherwise only ever get closer. Kept above the hundredth of a
+/// degree or so `angular_distance` can tell apart from zero, or a view on
+/// its target would keep being nudged around it.
+const CAUGHT_UP: Float = 0.05;
+
+/// View chasing a target position, closing a share of the distance left
+/// every step so it eases in without overshooting, however the target
+/// jumps and however long the steps are.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Follow {
+    /// How fast the view catches up: it covers all but `e^-stiffness` of
+    /// the way left each second.
+    pub stiffness: Float,
+    /// Position followed, latitude and longitude in degrees.
+    pub target: Option<(Float, Float)>,
+    /// Position the view is centered on, in degrees, moved towards the
+    /// target by `advance`.
+    pub view: Option<(Float, Float)>,
+}
+
+impl Follow {
+    pub fn new(stiffness: Float) -> Follow {
+        assert!(stiffness > 0., "follow stiffness must be positive");
+        Follow {
+            stiffness,
+            target: None,
+            view: None,
+        }
+    }
+
+    /// Moves the view `dt` towards the target along the shortest great
+    /// circle, and gives where it's centered now. Without a view yet it
+    /// starts at the target; without a target there's nothing to follow.
+    pub fn advance(&mut self, dt: Duration) -> Option<(Float, Float)> {
+        let target = self.target?;
+        let view = self.view.unwrap_or(target);
+        let share = 1. - (-self.stiffness * dt.as_secs_f32()).exp();
+        let view = match angular_distance(view, target) < CAUGHT_UP {
+            true => target,
+            false => great_circle(view, target, share),
+        };
+        self.view = Some(view);
+        Some(view)
+    }
+}
+
+impl Default for Follow {
+    fn default() -> Self {
+        Follow::new(DEFAULT_STIFFNESS)
+    }
+}
+
+impl Globe {
+    /// Makes `advance` turn the view towards a position in degrees, easing
+    /// in from wherever it's centered instead of jumping there. Call it
+    /// again as the position moves; the view keeps catching up smoothly
+    /// even when it only moves in steps.
+    pub fn follow(&mut self, lat: Float, lon: Float) {
+        if self.follow.view.is_none() {
+            self.follow.view = Some(self.facing());
+        }
+        self.follow.target = Some((lat, lon));
+    }
+
+    /// How fast the view catches up with `follow`'s position, see
+    /// `Follow::stiffness`.
+    pub fn set_follow_stiffness(&mut self, stiffness: Float) {
+        assert!(stiffness > 0., "follow stiffness must be positive");
+        self.follow.stiffness = stiffness;
+    }
+
+    /// Moves the view `dt` towards the followed position, if there is one.
+    pub(crate) fn advance_follow(&mut self, dt: Duration) {
+        if let Some((lat, lon)) = self.follow.advance(dt) {
+            self.focus(lat, lon);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Steps of 40 a second.
+    const FRAME: Duration = Duration::from_millis(25);
+
+    /// Follows from the equator at 0 degrees to 90 degrees east at
+    /// `stiffness`, `dt` at a time, and gives the longitudes the view moves
+    /// through until it's on the target. Checks it stays on the equator and
+    /// never turns back or goes past the target on the way.
+    fn chase(stiffness: Float, dt: Duration) -> Vec<Float> {
+        let mut follow = Follow::new(stiffness);
+        follow.view = Some((0., 0.));
+        follow.target = Some((0., 90.));
+        let mut lons = vec![0.];
+        while follow.view != follow.target {
+            let (lat, lon) = follow.advance(dt).unwrap();
+            let last = *lons.last().unwrap();
+            assert!(lat.abs() < 1e-3, "left the equator at {}", lat);
+            assert!(lon >= last, "turned back from {} to {}", last, lon);
+            assert!(lon <= 90. + 1e-3, "overshot to {}", lon);
+            lons.push(lon);
+            assert!(lons.len() < 10_000, "never caught up");
+        }
+        lons
+    }
+
+    #[test]
+    fn eases_in() {
+        for &stiffness in &[0.5, 4., 20.] {
+            let lons = chase(stiffness, FRAME);
+            // each step shorter than the last, up to the snap onto the target
+            let steps: Vec<Float> = lons.windows(2).map(|w| w[1] - w[0]).collect();
+            for pair in steps[..steps.len() - 1].windows(2) {
+                assert!(pair[1] <= pair[0] + 1e-3, "sped up at {}", stiffness);
+            }
+            // all but e^-stiffness of the way after a second
+            if let Some(&lon) = lons.get(40) {
+                let left = 90. * (-stiffness).exp();
+                assert!(
+                    ((90. - lon) - left).abs() < 0.05,
+                    "{} left at {}",
+                    90. - lon,
+                    stiffness
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn long_steps() {
+        for &dt in &[Duration::from_millis(500), Duration::from_secs(3600)] {
+            for &stiffness in &[0.5, 4., 20.] {
+                chase(stiffness, dt);
+            }
+        }
+        // an hour covers the whole way in one step
+        assert!(chase(4., Duration::from_secs(3600)).len() <= 3);
+    }
+
+    #[test]
+    fn snaps_onto_the_target() {
+        let mut follow = Follow::new(DEFAULT_STIFFNESS);
+        follow.view = Some((0., 90. - CAUGHT_UP / 2.));
+        follow.target = Some((0., 90.));
+        assert_eq!(follow.advance(FRAME), Some((0., 90.)));
+        assert_eq!(follow.view, follow.target);
+    }
+
+    #[test]
+    fn starts_on_the_target() {
+        let mut follow = Follow::default();
+        assert_eq!(follow.advance(FRAME), None);
+        follow.target = Some((10., 20.));
+        for _ in 0..3 {
+            assert_eq!(follow.advance(FRAME), Some((10., 20.)));
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! Textures rasterized from GeoJSON polygons.
+
+use std::io::{self, Read};
+
+use serde_json::Value;
+
+use crate::Texture;
+
+/// Ring of (longitude, latitude) points in degrees, as GeoJSON has them.
+type Ring = Vec<(f64, f64)>;
+
+/// Rasterizes the Polygon and MultiPolygon features of a GeoJSON document
+/// into an equirectangular texture of `width` by `height` characters, `land`
+/// inside the polygons and `ocean` everywhere else. The document can be a
+/// FeatureCollection, a single Feature or a bare geometry; other geometries
+/// are skipped.
+///
+/// Polygons crossing the antimeridian are filled on both sides of it
+/// whether or not they were split there, and rings going all the way around
+/// the globe, like Antarctica's, are closed over the nearest pole.
+pub fn texture_from_geojson(
+    reader: impl Read,
+    width: usize,
+    height: usize,
+    land: char,
+    ocean: char,
+) -> io::Result<Texture> {
+    let json: Value = serde_json::from_reader(reader)?;
+    let mut polygons = Vec::new();
+    collect_polygons(&json, &mut polygons)?;
+
+    let mut texture = vec![vec![ocean; width]; height];
+    if width == 0 {
+        return Ok(texture);
+    }
+    for polygon in &polygons {
+        let mut rings: Vec<Ring> = polygon.iter().map(|ring| unwrap_ring(ring)).collect();
+        // holes go around the same turn of longitudes as the outer ring
+        if let Some((outer, holes)) = rings.split_first_mut() {
+            let center = outer.iter().map(|&(lon, _)| lon).sum::<f64>() / outer.len().max(1) as f64;
+            for hole in holes.iter_mut().filter(|hole| !hole.is_empty()) {
+                let shift = ((center - hole[0].0) / 360.).round() * 360.;
+                hole.iter_mut().for_each(|point| point.0 += shift);
+            }
+        }
+        for (y, row) in texture.iter_mut().enumerate() {
+            let lat = 90. - (y as f64 + 0.5) / height as f64 * 180.;
+            let mut crossings: Vec<f64> =
+                rings.iter().flat_map(|ring| crossings(ring, lat)).collect();
+            crossings.sort_by(f64::total_cmp);
+            // inside between every other pair of crossings, which also
+            // leaves holes out
+            for span in crossings.chunks_exact(2) {
+                let first = ((span[0] + 180.) / 360. * width as f64 - 0.5).ceil() as i64;
+                let last = ((span[1] + 180.) / 360. * width as f64 - 0.5).floor() as i64;
+                let last = last.min(first + width as i64 - 1);
+                for x in first..=last {
+                    row[x.rem_euclid(width as i64) as usize] = land;
+                }
+            }
+        }
+    }
+    Ok(texture)
+}
+
+/// Adds the polygons, as lists of rings, found in a GeoJSON object.
+fn collect_polygons(json: &Value, polygons: &mut Vec<Vec<Ring>>) -> io::Result<()> {
+    let coordinates = || {
+        json.get("coordinates")
+            .ok_or_else(|| invalid("missing `coordinates`"))
+    };
+    match json.get("type").and_then(Value::as_str) {
+        Some("FeatureCollection") => {
+            let features = json.get("features").and_then(Value::as_array);
+            for feature in features.ok_or_else(|| invalid("missing `features`"))? {
+                collect_polygons(feature, polygons)?;
+            }
+        }
+        Some("Feature") => match json.get("geometry") {
+            Some(Value::Null) | None => (),
+            Some(geometry) => collect_polygons(geometry, polygons)?,
+        },
+        Some("GeometryCollection") => {
+            let geometries = json.get("geometries").and_then(Value::as_array);
+            for geometry in geometries.ok_or_else(|| invalid("missing `geometries`"))? {
+                collect_polygons(geometry, polygons)?;
+            }
+        }
+        Some("Polygon") => polygons.push(polygon(coordinates()?)?),
+        Some("MultiPolygon") => {
+            let parts = coordinates()?
+                .as_array()
+                .ok_or_else(|| invalid("invalid `coordinates`"))?;
+            for part in parts {
+                polygons.push(polygon(part)?);
+            }
+        }
+        Some(_) => (),
+        None => return Err(invalid("missing `type`")),
+    }
+    Ok(())
+}
+
+/// Rings of a Polygon's coordinates.
+fn polygon(coordinates: &Value) -> io::Result<Vec<Ring>> {
+    let bad = || invalid("invalid polygon coordinates");
+    let rings = coordinates.as_array().ok_or_else(bad)?;
+    rings
+        .iter()
+        .map(|ring| {
+            let points = ring.as_array().ok_or_else(bad)?;
+            points
+                .iter()
+                .map(|point| {
+                    let point = point.as_array().filter(|p| p.len() >= 2).ok_or_else(bad)?;
+                    match (point[0].as_f64(), point[1].as_f64()) {
+                        (Some(lon), Some(lat)) => Ok((lon, lat)),
+                        _ => Err(bad()),
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The ring with longitudes shifted by whole turns so no edge is longer
+/// than half a turn, the way it's meant to go around the globe. A ring
+/// that then ends a turn away from where it started went around a pole,
+/// and is closed over it.
+fn unwrap_ring(ring: &[(f64, f64)]) -> Ring {
+    let mut unwrapped: Ring = Vec::with_capacity(ring.len() + 2);
+    for &(lon, lat) in ring {
+        let lon = match unwrapped.last() {
+            Some(&(prev, _)) => lon + ((prev - lon) / 360.).round() * 360.,
+            None => lon,
+        };
+        unwrapped.push((lon, lat));
+    }
+    if let (Some(&(first, _)), Some(&(last, _))) = (unwrapped.first(), unwrapped.last()) {
+        if (last - first).abs() > 180. {
+            let mean = unwrapped.iter().map(|&(_, lat)| lat).sum::<f64>() / unwrapped.len() as f64;
+            let pole = if mean < 0. { -90. } else { 90. };
+            unwrapped.push((last, pole));
+            unwrapped.push((first, pole));
+        }
+    }
+    unwrapped
+}
+
+/// Longitudes where the edges of a ring cross a latitude.
+fn crossings(ring: &[(f64, f64)], lat: f64) -> impl Iterator<Item = f64> + '_ {
+    let n = ring.len();
+    (0..n).filter_map(move |i| {
+        let (lon1, lat1) = ring[i];
+        let (lon2, lat2) = ring[(i + 1) % n];
+        if (lat1 > lat) == (lat2 > lat) {
+            return None;
+        }
+        Some(lon1 + (lat - lat1) / (lat2 - lat1) * (lon2 - lon1))
+    })
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid GeoJSON: {}", message),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rows of a 36 by 18 texture of `geojson`, ten degrees a character,
+    /// with `#` for land.
+    fn rasterize(geojson: &str) -> Vec<String> {
+        texture_from_geojson(geojson.as_bytes(), 36, 18, '#', '.')
+            .unwrap()
+            .iter()
+            .map(|row| row.iter().collect())
+            .collect()
+    }
+
+    /// Rows of a 36 by 18 texture with land in the cells `land` says.
+    fn expected(land: impl Fn(usize, usize) -> bool) -> Vec<String> {
+        (0..18)
+            .map(|y| {
+                (0..36)
+                    .map(|x| if land(x, y) { '#' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn square() {
+        let texture = rasterize(
+            r#"{"type": "Polygon", "coordinates":
+                [[[0, 0], [30, 0], [30, 30], [0, 30], [0, 0]]]}"#,
+        );
+        // cells centered from 5 to 25 degrees east and north
+        let land = |x, y| (18..21).contains(&x) && (6..9).contains(&y);
+        assert_eq!(texture, expected(land));
+    }
+
+    #[test]
+    fn across_the_antimeridian() {
+        let texture = rasterize(
+            r#"{"type": "Feature", "geometry": {"type": "Polygon", "coordinates":
+                [[[170, 10], [-170, 10], [-170, 20], [170, 20], [170, 10]]]}}"#,
+        );
+        // 175 degrees east and west, at 15 degrees north
+        let land = |x, y| (x == 0 || x == 35) && y == 7;
+        assert_eq!(texture, expected(land));
+    }
+
+    #[test]
+    fn around_a_pole() {
+        let texture = rasterize(
+            r#"{"type": "Polygon", "coordinates": [[[-180, -70], [-90, -70],
+                [0, -70], [90, -70], [180, -70], [-180, -70]]]}"#,
+        );
+        // everything south of 70 degrees south
+        assert_eq!(texture, expected(|_, y| y >= 16));
+    }
+}
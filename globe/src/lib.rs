@@ -4,147 +4,1420 @@
 
 #![allow(dead_code)]
 
+mod capabilities;
+pub use capabilities::{capabilities, Capabilities};
+mod color;
+pub use color::{Color, ColorMode, ContrastReport, ElevationRamp, Theme};
+pub mod effects;
+pub use effects::Effect;
+mod flow;
+pub use flow::{Flow, FlowField, Particle};
+mod follow;
+pub use follow::Follow;
+mod font;
+pub use font::GLYPH_SIZE;
+mod pack;
+pub use pack::PACK_MANIFEST;
+mod presets;
+pub use presets::{Continent, Presets};
+mod route;
+pub use route::{Route, RouteHandle, RouteStyle};
+mod scale;
+mod stats;
+pub use stats::RenderStats;
+use stats::Tally;
+mod thumbnail;
+pub use thumbnail::{thumbnail, THUMBNAIL_PALETTE};
+#[cfg(feature = "geojson")]
+mod geojson;
+#[cfg(feature = "geojson")]
+pub use geojson::texture_from_geojson;
+// also in the crate's own tests, which compare frames with it
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
+/// The types needed to render a globe, for `use globe::prelude::*`.
+pub mod prelude {
+    pub use crate::{
+        Camera, Canvas, Color, Effect, Float, Globe, GlobeConfig, GlobeTemplate, PALETTE,
+    };
+}
+
+#[cfg(feature = "ratatui")]
+mod widget;
+#[cfg(feature = "ratatui")]
+pub use widget::{GlobeState, GlobeWidget};
+
 use std::f32::consts::PI;
 use std::fs::File;
-use std::io::Read;
+use std::io::{Read, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 pub type Int = i32;
 pub type Float = f32;
 pub type Texture = Vec<Vec<char>>;
 
-const PALETTE: [char; 18] = [
+/// Largest camera tilt in radians either way, about 85.9 degrees. Closer to
+/// the poles the view would flip over as the camera passes above them.
+pub const MAX_TILT: Float = 1.5;
+
+/// Camera tilt (the `beta` of `Camera::new`) centering the view on a
+/// latitude in degrees, up to `MAX_TILT` either way.
+///
+/// The tilt is the latitude in radians: a camera tilted by `t` above the
+/// equator looks straight down at the point `t` radians north of it, so 0°
+/// is a tilt of 0, ±45° is ±π/4 and ±90° is clamped to ±`MAX_TILT`.
+pub fn tilt_for_latitude(lat: Float) -> Float {
+    lat.to_radians().clamp(-MAX_TILT, MAX_TILT)
+}
+
+/// Latitude in degrees at the center of the view with the camera tilted by
+/// `tilt` radians, the inverse of `tilt_for_latitude` within ±`MAX_TILT`.
+pub fn latitude_for_tilt(tilt: Float) -> Float {
+    tilt.to_degrees()
+}
+
+/// Characters used for shading, from darkest to brightest.
+pub const PALETTE: [char; 18] = [
     ' ', '.', ':', ';', '\'', ',', 'w', 'i', 'o', 'g', 'O', 'L', 'X', 'H', 'W', 'Y', 'V', '@',
 ];
 
 static EARTH_TEXTURE: &str = include_str!("../textures/earth.txt");
+static MOON_TEXTURE: &str = include_str!("../textures/moon.txt");
+static EARTH_NIGHT_TEXTURE: &str = include_str!("../textures/earth_night.txt");
+
+/// Destination for a stream of rendered frames, like a file recording a
+/// session. Implementations should write frames out as they come rather
+/// than keep them all in memory.
+pub trait FrameSink {
+    /// Takes the next frame, rendered on `canvas`.
+    fn write_frame(&mut self, canvas: &Canvas) -> std::io::Result<()>;
+
+    /// Writes out anything still buffered, once after the last frame.
+    fn finish(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Grayscale image, see `Canvas::to_image`.
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    /// Brightness of every pixel from 0 to 255, row by row.
+    pub pixels: Vec<u8>,
+}
+
+/// Rectangle of character cells, see `Canvas::dirty_region`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    /// Column and row of the top left cell.
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
 
 pub struct Canvas {
     pub matrix: Vec<Vec<char>>,
+    /// Characters of the cells when they were last taken by
+    /// `take_dirty_cells`, to tell which changed since.
+    taken: Vec<Vec<char>>,
     size: (usize, usize),
     // character size
     char_pix: (usize, usize),
+    palette: Vec<char>,
+    /// Width of a character on screen over its height.
+    cell_aspect: Float,
 }
 
 impl Canvas {
+    /// Canvas of `x` by `y` pixels, drawn as characters of `cp` pixels each
+    /// (4x8 by default).
+    ///
+    /// # Panics
+    ///
+    /// If the canvas or a character has no pixels, see `try_new`.
     pub fn new(x: u16, y: u16, cp: Option<(usize, usize)>) -> Self {
+        Canvas::try_new(x, y, cp).expect("canvas and characters can't be empty")
+    }
+    /// Like `new`, or `None` if the canvas or a character has no pixels, as
+    /// with sizes taken from a terminal too small to draw anything in.
+    pub fn try_new(x: u16, y: u16, cp: Option<(usize, usize)>) -> Option<Self> {
+        let char_pix = cp.unwrap_or((4, 8));
+        if x == 0 || y == 0 || char_pix.0 == 0 || char_pix.1 == 0 {
+            return None;
+        }
         let x = x as usize;
         let y = y as usize;
 
         let matrix = vec![vec![' '; x]; y];
 
-        Self {
+        let mut canvas = Self {
             size: (x, y),
             matrix,
-            char_pix: cp.unwrap_or((4, 8)),
+            taken: Vec::new(),
+            char_pix,
+            palette: PALETTE.to_vec(),
+            cell_aspect: char_pix.0 as Float / char_pix.1 as Float,
+        };
+        canvas.mark_dirty();
+        Some(canvas)
+    }
+    /// Largest square canvas with the default 4x8 pixel characters that
+    /// fits in `cols` by `rows` terminal cells, which make the globe round
+    /// since cells are about twice as tall as they are wide. At least one
+    /// cell of each, and no more than `u16::MAX` pixels across.
+    pub fn fit_terminal_chars(cols: u16, rows: u16) -> Self {
+        let size = (cols.max(1) as u32 * 4).min(rows.max(1) as u32 * 8);
+        let size = size.min(u16::MAX as u32) as u16;
+        Canvas::try_new(size, size, None).expect("at least one cell either way")
+    }
+    /// The canvas with a palette, see `set_palette`.
+    pub fn with_palette(mut self, palette: &[char]) -> Self {
+        self.set_palette(palette);
+        self
+    }
+    /// The canvas drawn for characters `ratio` times as wide as they are
+    /// tall on screen, so the globe comes out round in fonts with unusual
+    /// metrics. Defaults to the shape of a character's pixels, 0.5 with the
+    /// default 4x8. Globes too tall for the canvas at that ratio are drawn
+    /// narrower instead, so they still fit.
+    ///
+    /// # Panics
+    ///
+    /// If the ratio isn't positive.
+    pub fn with_cell_aspect(mut self, ratio: Float) -> Self {
+        assert!(ratio > 0., "cell aspect ratio must be positive");
+        self.cell_aspect = ratio;
+        self
+    }
+    pub fn cell_aspect(&self) -> Float {
+        self.cell_aspect
+    }
+    /// Characters per unit of the camera's view plane, across and down,
+    /// from the center of the canvas.
+    fn view_scale(&self) -> (Float, Float) {
+        let (half_x, half_y) = self.grid_size();
+        let (half_x, half_y) = ((half_x / 2) as Float, (half_y / 2) as Float);
+        // how much taller than its pixels say a character is drawn
+        let stretch = self.cell_aspect * self.char_pix.1 as Float / self.char_pix.0 as Float;
+        match stretch <= 1. {
+            true => (half_x, half_y * stretch),
+            false => (half_x / stretch, half_y),
         }
     }
+    /// Sets the characters used for shading, from darkest to brightest.
+    /// Defaults to `PALETTE`.
+    pub fn set_palette(&mut self, palette: &[char]) {
+        assert!(!palette.is_empty(), "palette can't be empty");
+        self.palette = palette.to_vec();
+    }
+    /// Character for a shade, given as an index into `PALETTE`. Only the
+    /// darkest shade maps to the first character, so nothing visible turns
+    /// blank with short palettes.
+    fn shade(&self, index: usize) -> char {
+        let n = self.palette.len();
+        if index == 0 || n == 1 {
+            return self.palette[0];
+        }
+        self.palette[1 + (index - 1) * (n - 1) / (PALETTE.len() - 1)]
+    }
+    /// Character for a shade level between `PALETTE` indices, picking one
+    /// of the two nearest characters: the brighter one when `threshold`,
+    /// from 0 to 1, is below how far the level is towards it.
+    fn shade_dithered(&self, level: Float, threshold: Float) -> char {
+        let n = self.palette.len();
+        if n == 1 {
+            return self.palette[0];
+        }
+        // same scale as `shade`, with the blank first character below 1
+        let position = match level < 1. {
+            true => level.max(0.),
+            false => 1. + (level - 1.) * (n - 1) as Float / (PALETTE.len() - 1) as Float,
+        };
+        let index = (position + threshold).floor() as usize;
+        self.palette[index.min(n - 1)]
+    }
     pub fn get_size(&self) -> (usize, usize) {
         self.size
     }
+    /// Draws the characters of the canvas in a built-in 8x8 bitmap font,
+    /// white on black, each pixel of a glyph taking `scale` by `scale`
+    /// pixels. Characters the font doesn't have, which are all outside of
+    /// printable ASCII, are drawn as filled blocks.
+    pub fn to_image(&self, scale: usize) -> Image {
+        let (columns, rows) = self.grid_size();
+        let cell = GLYPH_SIZE * scale;
+        let (width, height) = (columns * cell, rows * cell);
+        let mut pixels = vec![0; width * height];
+        for (y, row) in self.matrix.iter().take(rows).enumerate() {
+            for (x, &c) in row.iter().take(columns).enumerate() {
+                let glyph = font::glyph(c).unwrap_or([0xFF; GLYPH_SIZE]);
+                for py in 0..cell {
+                    let bits = glyph[py / scale];
+                    let start = (y * cell + py) * width + x * cell;
+                    for (px, pixel) in pixels[start..start + cell].iter_mut().enumerate() {
+                        if bits >> (px / scale) & 1 == 1 {
+                            *pixel = 255;
+                        }
+                    }
+                }
+            }
+        }
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+    /// Runs a post-processing effect over the canvas, see `effects`.
+    pub fn apply(&mut self, effect: &dyn Effect) {
+        effect.apply(self);
+    }
+    /// Size in characters of the part of the canvas that gets printed.
+    pub(crate) fn grid_size(&self) -> (usize, usize) {
+        (self.size.0 / self.char_pix.0, self.size.1 / self.char_pix.1)
+    }
+    /// The character `steps` places further along the palette, towards
+    /// brighter ones when positive. Characters outside of the palette are
+    /// kept, and shaded ones are never dimmed down to blank.
+    pub(crate) fn shift_shade(&self, c: char, steps: isize) -> char {
+        let index = match self.palette.iter().position(|&p| p == c) {
+            Some(index) => index as isize,
+            None => return c,
+        };
+        let lowest = index.min(1);
+        let shifted = (index + steps).clamp(lowest, self.palette.len() as isize - 1);
+        self.palette[shifted.max(0) as usize]
+    }
     pub fn clear(&mut self) {
         for i in self.matrix.iter_mut().flatten() {
             *i = ' ';
         }
     }
-    fn draw_point(&mut self, a: usize, b: usize, c: char) {
+    /// Smallest rectangle of character cells holding all the cells that
+    /// changed since they were last taken with `take_dirty_cells`, or
+    /// `None` if none did. Everything counts as changed on a new canvas.
+    ///
+    /// Changes are found by comparing characters, so they include whatever
+    /// wrote to `matrix` directly, and a cell cleared and drawn again the
+    /// same doesn't count. A host drawing the globe in a pane only has to
+    /// redraw this part of it.
+    pub fn dirty_region(&self) -> Option<Rect> {
+        let mut cells = self.dirty_cells();
+        let (x, y) = cells.next()?;
+        let (mut min, mut max) = ((x, y), (x, y));
+        for (x, y) in cells {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+        Some(Rect {
+            x: min.0,
+            y: min.1,
+            width: max.0 - min.0 + 1,
+            height: max.1 - min.1 + 1,
+        })
+    }
+    /// Column and row of every character cell that changed since the last
+    /// call, row by row, see `dirty_region`. They then count as unchanged
+    /// until drawn over with something else.
+    pub fn take_dirty_cells(&mut self) -> Vec<(usize, usize)> {
+        let cells: Vec<_> = self.dirty_cells().collect();
+        for &(x, y) in &cells {
+            self.taken[y][x] = self.matrix[y][x];
+        }
+        cells
+    }
+    /// Makes every character cell count as changed, e.g. after the screen
+    /// under the canvas was cleared.
+    pub fn mark_dirty(&mut self) {
+        let (width, height) = self.grid_size();
+        // no cell is ever drawn with a NUL
+        self.taken = vec![vec!['\0'; width]; height];
+    }
+    fn dirty_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.taken.iter().enumerate().flat_map(move |(y, taken)| {
+            taken
+                .iter()
+                .zip(&self.matrix[y])
+                .enumerate()
+                .filter(|(_, (taken, c))| taken != c)
+                .map(move |(x, _)| (x, y))
+        })
+    }
+    pub fn draw_point(&mut self, a: usize, b: usize, c: char) {
         if a >= self.size.0 || b >= self.size.1 {
             return;
         }
         self.matrix[b][a] = c;
     }
+    /// Draws part of a circle around the character at `center`, from
+    /// `start` to `end` as fractions of a full turn clockwise from the top.
+    /// `radius` is in character widths; the circle is squashed vertically so
+    /// it looks round with characters taller than they're wide.
+    pub fn draw_ring_segment(
+        &mut self,
+        center: (Float, Float),
+        radius: Float,
+        start: Float,
+        end: Float,
+        c: char,
+    ) {
+        let aspect = self.cell_aspect;
+        // about two points per character along the arc
+        let steps = (4. * PI * radius * (end - start).abs()).ceil().max(1.) as usize;
+        for i in 0..=steps {
+            let turn = start + (end - start) * i as Float / steps as Float;
+            let angle = turn * 2. * PI;
+            let x = center.0 + radius * angle.sin();
+            let y = center.1 - radius * aspect * angle.cos();
+            if x >= 0. && y >= 0. {
+                self.draw_point(x.round() as usize, y.round() as usize, c);
+            }
+        }
+    }
+}
+
+/// What a texture has at a position, see `Globe::sample`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Texel {
+    /// The texture's character there.
+    pub c: char,
 }
 
+/// Characters of the built-in Earth texture that are ocean.
+pub const OCEAN_CHARS: [char; 2] = [':', ' '];
+
 pub struct Globe {
     pub camera: Camera,
     pub radius: Float,
     pub angle: Float,
     pub texture: Texture,
+    /// Texture shown on the night side, instead of dimming the day texture.
     pub texture_night: Option<Texture>,
+    /// Position in degrees where the sun is straight overhead. Without one
+    /// the whole globe is lit.
+    pub sun: Option<(Float, Float)>,
+    pub timezone_bands: Option<TimezoneBands>,
+    /// Region drawn brighter than the rest of the globe.
+    pub highlight: Option<Highlight>,
+    /// How shades between two characters are drawn.
+    pub dither: Dither,
+    /// Seed for `Dither::Noise`; changing it between frames makes the grain
+    /// move.
+    pub dither_seed: u32,
+    /// Whether to render fewer, larger characters while the globe covers
+    /// little of the canvas, see `render_resolution`.
+    pub adaptive_resolution: bool,
+    /// Quality the globe is rendered at, full by default.
+    pub quality: QualitySettings,
+    /// Whether to draw a scale bar in the bottom left corner, for as far as
+    /// `km_per_cell_at_equator` says, with ticks every 30 degrees of
+    /// longitude along the equator and the longitudes where it meets the
+    /// limb next to it. Nothing is drawn while the equator is out of sight.
+    pub scale_bar: bool,
+    /// Specular highlight, none by default.
+    pub lighting: Lighting,
+    /// Colors for `cell_colors` to use, see `GlobeConfig::with_theme`.
+    pub theme: Theme,
+    /// Heights for `ColorMode::Elevation`, see `GlobeConfig::with_heightmap`.
+    pub heightmap: Option<Texture>,
+    /// Colors of `ColorMode::Elevation`.
+    pub elevation_ramp: ElevationRamp,
+    /// Point events shown as they happen, ordered by magnitude.
+    pub events: Vec<Event>,
+    /// Text written along parallels, see `add_surface_text`.
+    pub surface_text: Vec<SurfaceText>,
+    /// Routes through several stops, see `add_route`.
+    pub routes: Vec<Route>,
+    /// How long an event stays visible after it happened.
+    pub event_fade: Duration,
+    /// Current time of the event replay, moved forward by `advance`.
+    pub event_time: Duration,
+    /// Oscillation of the camera tilt, see `set_wobble`.
+    pub wobble: Option<Wobble>,
+    /// Time into the wobble, moved forward by `advance`.
+    pub wobble_time: Duration,
+    /// Particles drifting along a vector field, see `set_flow_field`.
+    pub flow: Option<Flow>,
+    /// Position the view eases towards, see `follow`.
+    pub follow: Follow,
+    /// Texture characters that are ocean for `is_land`, `OCEAN_CHARS`
+    /// unless set with `GlobeConfig::with_ocean_chars`.
+    pub ocean_chars: Vec<char>,
+    /// Total time passed to `advance`.
+    clock: Duration,
+    /// Bumped by `set_events`, to tell replaced events apart.
+    events_generation: u64,
+    /// State at the last `take_changes`.
+    seen: Snapshot,
+}
+
+/// A change to the globe since the last `Globe::take_changes`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChangeEvent {
+    /// The globe turned to `angle`, in radians. `velocity` is the average
+    /// since the last `take_changes` in radians per second of time passed
+    /// to `advance`, zero if none was.
+    Rotated { angle: Float, velocity: Float },
+    /// The camera moved to a new position, by zooming or tilting.
+    CameraMoved { x: Float, y: Float, z: Float },
+    /// `set_events` replaced the events, now `count` of them.
+    EventsChanged { count: usize },
+    /// The event replay reached the end of its last event's fade, or was
+    /// started over after it.
+    ReplayFinished,
+}
+
+/// What `take_changes` compares against to find changes.
+#[derive(Clone, Copy, Debug)]
+struct Snapshot {
+    clock: Duration,
+    angle: Float,
+    camera: [Float; 19],
+    events_generation: u64,
+    event_time: Duration,
+}
+
+/// Slow sinusoidal oscillation of the camera tilt.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Wobble {
+    /// Largest tilt offset in radians.
+    pub amplitude: Float,
+    /// Time for a full oscillation.
+    pub period: Duration,
+}
+
+/// Circular region of the globe, like the rough extent of a country, drawn
+/// a few shades brighter so it stands out, sea included. Shades that are
+/// already among the brightest are darkened instead.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Highlight {
+    /// Center of the region in degrees.
+    pub lat: Float,
+    pub lon: Float,
+    /// Radius of the region in degrees of arc.
+    pub radius: Float,
+}
+
+impl Highlight {
+    fn shade(&self, level: Float, lat: Float, lon: Float) -> Float {
+        if angular_distance((lat, lon), (self.lat, self.lon)) > self.radius {
+            return level;
+        }
+        match level.round() as usize + 3 < PALETTE.len() {
+            true => level + 3.,
+            false => level - 3.,
+        }
+    }
+}
+
+/// Shine of the surface: a highlight where the light reflects towards the
+/// camera, brightening cells up to the brightest shade, which makes the
+/// globe look glossy. The light is the sun when there is one, and otherwise
+/// comes from above and to the left of the viewer. Off by default.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lighting {
+    /// Share of the way to the brightest shade the middle of the highlight
+    /// is brightened by, from 0 for no highlight to 1.
+    pub specular_strength: Float,
+    /// Blinn-Phong exponent of the highlight; higher is smaller and sharper.
+    pub shininess: Float,
+    /// Strength over the ocean instead of `specular_strength`, as water
+    /// shines more than land. `None` for the same everywhere.
+    pub ocean_specular_strength: Option<Float>,
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Lighting {
+            specular_strength: 0.,
+            shininess: 24.,
+            ocean_specular_strength: None,
+        }
+    }
+}
+
+/// Direction the light comes from without a sun, in the camera's frame: up
+/// and to the left of the viewer, towards the globe.
+const VIEW_LIGHT: [Float; 3] = [0.5, -0.6, 1.];
+
+/// Region the center of the view is kept in, see `Camera::with_bounds`.
+/// Latitudes and longitudes are in degrees, and the longitudes go east from
+/// `min` to `max`, so with `min.1 > max.1` the region crosses the
+/// antimeridian.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    pub min: (Float, Float),
+    pub max: (Float, Float),
+}
+
+impl Bounds {
+    pub fn contains(&self, pos: (Float, Float)) -> bool {
+        self.clamp(pos) == pos
+    }
+
+    /// Position in the region closest to `(lat, lon)`: the latitude clamped
+    /// to the region's, and a longitude outside it moved to the nearer edge.
+    pub fn clamp(&self, (lat, lon): (Float, Float)) -> (Float, Float) {
+        let lat = lat.clamp(self.min.0, self.max.0);
+        let width = (self.max.1 - self.min.1).rem_euclid(360.);
+        let east = (lon - self.min.1).rem_euclid(360.);
+        let lon = match east <= width {
+            true => lon,
+            false if east - width < 360. - east => self.max.1,
+            false => self.min.1,
+        };
+        (lat, lon)
+    }
+}
+
+/// How shades falling between two palette characters are drawn. Lighting
+/// from the sun and short palettes otherwise show as visible bands.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Dither {
+    /// The nearest character.
+    #[default]
+    None,
+    /// A fixed 4x4 Bayer pattern mixing the two nearest characters, the
+    /// same in every frame.
+    Ordered,
+    /// Random mixing of the two nearest characters, changing with
+    /// `Globe::dither_seed` like film grain.
+    Noise,
+}
+
+impl Dither {
+    /// Threshold from 0 to 1 for the character at `(x, y)`.
+    fn threshold(self, x: usize, y: usize, seed: u32) -> Float {
+        const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+        match self {
+            Dither::None => 0.5,
+            Dither::Ordered => (BAYER[y % 4][x % 4] as Float + 0.5) / 16.,
+            Dither::Noise => {
+                // integer hash of the position and seed
+                let mut h = (x as u32).wrapping_mul(0x9e37_79b1)
+                    ^ (y as u32).wrapping_mul(0x85eb_ca77)
+                    ^ seed.wrapping_mul(0xc2b2_ae3d);
+                h ^= h >> 15;
+                h = h.wrapping_mul(0x2c1b_3c6d);
+                h ^= h >> 12;
+                h = h.wrapping_mul(0x297a_2d39);
+                h ^= h >> 15;
+                (h >> 8) as Float / (1 << 24) as Float
+            }
+        }
+    }
+}
+
+/// Knobs trading the look of the globe for render time, grouped so they
+/// can be changed together between frames, see `Globe::quality`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QualitySettings {
+    /// Characters across filled by each rendered one at least, and half as
+    /// many down; 1 renders every character.
+    pub block: usize,
+    /// Whether `Globe::dither` is applied, otherwise shades are drawn with
+    /// the nearest character.
+    pub dither: bool,
+}
+
+impl QualitySettings {
+    /// Number of levels `level` knows.
+    pub const LEVELS: usize = 5;
+
+    /// Settings of a quality level from 0, the fastest, to `LEVELS - 1`,
+    /// full quality. Each level below full first drops dithering, then
+    /// renders larger blocks. Levels above the last are full quality.
+    pub fn level(level: usize) -> QualitySettings {
+        let steps_down = (QualitySettings::LEVELS - 1).saturating_sub(level);
+        QualitySettings {
+            block: steps_down.max(1),
+            dither: steps_down == 0,
+        }
+    }
+}
+
+impl Default for QualitySettings {
+    fn default() -> Self {
+        QualitySettings::level(QualitySettings::LEVELS - 1)
+    }
+}
+
+/// Something happening at a point on the globe, like an earthquake.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Event {
+    /// When it happens, from the start of the replay.
+    pub time: Duration,
+    /// Position in degrees.
+    pub lat: Float,
+    pub lon: Float,
+    /// Size of the event; magnitudes above 5 draw larger marks.
+    pub magnitude: Float,
+}
+
+/// Text written on the surface along a parallel, a letter every `spacing`
+/// degrees of longitude eastwards from `start_lon`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SurfaceText {
+    /// Position of the first letter in degrees.
+    pub lat: Float,
+    pub start_lon: Float,
+    pub text: String,
+    pub spacing: Float,
+}
+
+/// Characters events are drawn with as they fade, from new to old.
+const EVENT_FADE: [char; 5] = ['#', '%', '*', '+', '-'];
+
+/// Shading of idealized time zones: 15° wide meridian bands, one per hour of
+/// UTC offset, with every other band drawn slightly brighter.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TimezoneBands {
+    /// UTC offset in hours of a band to emphasize.
+    pub highlight: Option<i32>,
+}
+
+impl TimezoneBands {
+    /// UTC offset in hours of the band containing a longitude in degrees.
+    pub fn offset_at(lon: Float) -> i32 {
+        let lon = (lon + 180.).rem_euclid(360.) - 180.;
+        (lon / 15.).round() as i32
+    }
+
+    /// Shade level adjusted for the band containing `lon`, in degrees.
+    fn shade(&self, level: Float, lon: Float) -> Float {
+        let offset = TimezoneBands::offset_at(lon);
+        // the bands at +12 and -12 hours are the same one
+        let same = |a: i32, b: i32| (a - b).rem_euclid(24) == 0;
+        let boost = match self.highlight {
+            Some(h) if same(h, offset) => 4,
+            _ if offset.rem_euclid(2) == 1 => 1,
+            _ => 0,
+        };
+        (level + boost as Float).min((PALETTE.len() - 1) as Float)
+    }
 }
 
 impl Globe {
+    /// The Earth, facing the prime meridian from the default camera.
+    pub fn earth() -> Globe {
+        GlobeConfig::new()
+            .use_template(GlobeTemplate::Earth)
+            .build()
+    }
+
+    /// The globe turned to `angle` degrees, facing longitude `-angle`.
+    pub fn angle_deg(mut self, angle: Float) -> Self {
+        self.set_angle_deg(angle);
+        self
+    }
+
+    /// Turns the globe to `angle` degrees, like setting `angle` in radians.
+    /// The camera then faces longitude `-angle`.
+    pub fn set_angle_deg(&mut self, angle: Float) {
+        self.angle = angle.to_radians();
+    }
+
+    /// Longitude in degrees the camera faces, from -180 to 180: `-angle`
+    /// wrapped around. Unlike `facing` it doesn't depend on the camera.
+    pub fn facing_longitude_deg(&self) -> Float {
+        (180. - self.angle.to_degrees()).rem_euclid(360.) - 180.
+    }
+
+    /// The globe seen from `tilt` degrees above the equator, at the same
+    /// distance, up to `MAX_TILT` either way.
+    pub fn tilt_deg(mut self, tilt: Float) -> Self {
+        let distance = magnitude(&[self.camera.x, self.camera.y, self.camera.z]);
+        self.place_camera(distance, tilt_for_latitude(tilt));
+        self
+    }
+
+    /// The globe seen from `distance` away from its center, at the same tilt;
+    /// 2 is the default and larger distances zoom out.
+    pub fn zoom(mut self, distance: Float) -> Self {
+        let tilt = self.camera.tilt_deg().to_radians();
+        self.place_camera(distance, tilt);
+        self
+    }
+
+    /// Replaces the camera with one `distance` from the center and `tilt`
+    /// radians above the equator, keeping its bounds. Any roll is lost.
+    fn place_camera(&mut self, distance: Float, tilt: Float) {
+        let bounds = self.camera.bounds;
+        self.camera = Camera::new(distance, 0., tilt);
+        self.camera.bounds = bounds;
+    }
+
     pub fn render_on(&self, canvas: &mut Canvas) {
-        // Sun
-        let light: [Float; 3] = [0., 999999., 0.];
-        // shoot the ray through every pixel
+        self.render_tallied(canvas, &mut ());
+    }
 
-        let (size_x, size_y) = canvas.get_size();
-        for yi in 0..size_y {
-            let yif = yi as Int;
-            for xi in 0..size_x {
-                let xif = xi as Int;
-                // coordinates of the camera, origin of the ray
-                let o: [Float; 3] = [self.camera.x, self.camera.y, self.camera.z];
-                // u is unit vector, direction of the ray
-                let mut u: [Float; 3] = [
-                    -((xif - (size_x / canvas.char_pix.0 / 2) as Int) as Float + 0.5)
-                        / (size_x / canvas.char_pix.0 / 2) as Float,
-                    ((yif - (size_y / canvas.char_pix.1 / 2) as Int) as Float + 0.5)
-                        / (size_y / canvas.char_pix.1 / 2) as Float,
-                    -1.,
-                ];
-                transform_vector(&mut u, self.camera.matrix);
-                u[0] -= self.camera.x;
-                u[1] -= self.camera.y;
-                u[2] -= self.camera.z;
-                normalize(&mut u);
-                let dot_uo = dot(&u, &o);
-                let discriminant: Float = dot_uo * dot_uo - dot(&o, &o) + self.radius * self.radius;
-
-                // ray doesn't hit the sphere
-                if discriminant < 0. {
+    /// `render_on`, counting what it does into `tally`.
+    fn render_tallied(&self, canvas: &mut Canvas, tally: &mut impl Tally) {
+        if self.adaptive_resolution || self.quality.block > 1 {
+            self.render_blocks(canvas, self.render_block(canvas), tally);
+        } else {
+            // shoot the ray through every pixel
+            let (size_x, size_y) = canvas.get_size();
+            for yi in 0..size_y {
+                for xi in 0..size_x {
+                    // ray doesn't hit the sphere
+                    let inter = match self.cast_ray(canvas, xi, yi) {
+                        Some(inter) => inter,
+                        None => {
+                            tally.ray(false);
+                            continue;
+                        }
+                    };
+                    tally.ray(true);
+
+                    // computing coordinates for the sphere
+                    let (lat, lon) = self.surface_coords(&inter);
+                    if let Some(level) = self.shade_at(lat, lon, tally) {
+                        let level = self.shine(level, &inter, lat, lon);
+                        let c = self.shade_char(canvas, level, xi, yi);
+                        canvas.draw_point(xi, yi, c);
+                    }
+                }
+            }
+        }
+        let project = |canvas: &Canvas, lat, lon| self.project(canvas, lat, lon);
+        self.draw_flow(canvas, project, tally);
+        self.draw_routes(canvas, project, tally);
+        self.draw_events(canvas, project, tally);
+        self.draw_surface_text(canvas, project, tally);
+        if self.scale_bar {
+            self.draw_scale(canvas);
+        }
+    }
+
+    /// Renders the whole texture as a flat map, twice as wide as it is tall
+    /// and as large as fits in the top left corner of `canvas`, with the
+    /// same shading as `render_on`. The camera and angle don't matter.
+    pub fn render_flat_on(&self, canvas: &mut Canvas) {
+        let (width, height) = flat_size(canvas);
+        for y in 0..height {
+            for x in 0..width {
+                let lat = PI / 2. - (y as Float + 0.5) / height as Float * PI;
+                let lon = (x as Float + 0.5) / width as Float * 2. * PI - PI;
+                if let Some(level) = self.shade_at(lat, lon, &mut ()) {
+                    let c = self.shade_char(canvas, level, x, y);
+                    canvas.draw_point(x, y, c);
+                }
+            }
+        }
+        self.draw_flow(canvas, project_flat, &mut ());
+        self.draw_routes(canvas, project_flat, &mut ());
+        self.draw_events(canvas, project_flat, &mut ());
+        self.draw_surface_text(canvas, project_flat, &mut ());
+    }
+
+    /// Shade level at a position on the globe, in radians, from the
+    /// textures, the sun and the overlays. Levels are `PALETTE` indices,
+    /// with a fraction where the sun blends two of them.
+    fn shade_at(&self, lat: Float, lon: Float, tally: &mut impl Tally) -> Option<Float> {
+        let (earth_y, earth_x) = texel_at(&self.texture, lat, lon)?;
+        tally.sample();
+        let day = find_index(self.texture[earth_y][earth_x], &PALETTE).max(0);
+
+        let mut level = day as Float;
+        if let Some(sun) = self.sun {
+            let night = match &self.texture_night {
+                Some(texture) => texel_at(texture, lat, lon).map_or(0, |(y, x)| {
+                    tally.sample();
+                    find_index(texture[y][x], &PALETTE).max(0)
+                }),
+                None => day / 4,
+            };
+            let luminance = daylight(lat, lon, sun);
+            level = (1.0 - luminance) * night as Float + luminance * day as Float;
+        }
+        if level.round() as usize >= PALETTE.len() {
+            level = 0.;
+        }
+        if let Some(bands) = &self.timezone_bands {
+            level = bands.shade(level, lon.to_degrees());
+        }
+        if let Some(highlight) = &self.highlight {
+            level = highlight.shade(level, lat.to_degrees(), lon.to_degrees());
+        }
+        Some(level)
+    }
+
+    /// `level` brightened by the specular highlight at the point `p` of the
+    /// surface, at a latitude and longitude in radians.
+    fn shine(&self, level: Float, p: &[Float; 3], lat: Float, lon: Float) -> Float {
+        let top = (PALETTE.len() - 1) as Float;
+        level + (top - level).max(0.) * self.specular(p, lat, lon)
+    }
+
+    /// Strength of the specular highlight from 0 to 1 at the point `p` of
+    /// the surface, at a latitude and longitude in radians.
+    fn specular(&self, p: &[Float; 3], lat: Float, lon: Float) -> Float {
+        let lighting = &self.lighting;
+        let strength = match lighting.ocean_specular_strength {
+            Some(ocean) if !self.is_land(lat.to_degrees(), lon.to_degrees()) => ocean,
+            _ => lighting.specular_strength,
+        };
+        if strength <= 0. {
+            return 0.;
+        }
+        let mut normal = *p;
+        normalize(&mut normal);
+        let light = self.light_direction();
+        if dot(&normal, &light) <= 0. {
+            return 0.;
+        }
+        let mut view = [0.; 3];
+        vector(&mut view, &[self.camera.x, self.camera.y, self.camera.z], p);
+        normalize(&mut view);
+        // Blinn-Phong: how close the normal is to halfway between the light
+        // and the viewer
+        let mut half = [light[0] + view[0], light[1] + view[1], light[2] + view[2]];
+        normalize(&mut half);
+        strength.min(1.) * dot(&normal, &half).max(0.).powf(lighting.shininess)
+    }
+
+    /// Unit vector from the globe towards the light.
+    fn light_direction(&self) -> [Float; 3] {
+        let mut light = match self.sun {
+            Some((lat, lon)) => self.world_point(lat, lon),
+            None => {
+                // the camera matrix places points, so take the camera back out
+                let mut to = VIEW_LIGHT;
+                transform_vector(&mut to, self.camera.matrix);
+                [
+                    to[0] - self.camera.x,
+                    to[1] - self.camera.y,
+                    to[2] - self.camera.z,
+                ]
+            }
+        };
+        normalize(&mut light);
+        light
+    }
+
+    /// Renders one ray per block of `block` characters, filling the whole
+    /// block with its shade. Only the character grid is rendered, not the
+    /// rest of the canvas's pixels.
+    fn render_blocks(
+        &self,
+        canvas: &mut Canvas,
+        (block_x, block_y): (usize, usize),
+        tally: &mut impl Tally,
+    ) {
+        let (width, height) = (
+            canvas.size.0 / canvas.char_pix.0,
+            canvas.size.1 / canvas.char_pix.1,
+        );
+        for top in (0..height).step_by(block_y) {
+            for left in (0..width).step_by(block_x) {
+                // aim at the middle of the block
+                let x = (left + block_x / 2).min(width - 1);
+                let y = (top + block_y / 2).min(height - 1);
+                let inter = match self.cast_ray(canvas, x, y) {
+                    Some(inter) => inter,
+                    None => {
+                        tally.ray(false);
+                        continue;
+                    }
+                };
+                tally.ray(true);
+                let (lat, lon) = self.surface_coords(&inter);
+                let level = match self.shade_at(lat, lon, tally) {
+                    Some(level) => self.shine(level, &inter, lat, lon),
+                    None => continue,
+                };
+                for y in top..(top + block_y).min(height) {
+                    for x in left..(left + block_x).min(width) {
+                        let c = self.shade_char(canvas, level, x, y);
+                        canvas.draw_point(x, y, c);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Resolution in characters `render_on` renders the globe at on
+    /// `canvas`. That's the whole character grid, or with
+    /// `adaptive_resolution` down to a quarter of it across while the globe
+    /// covers less than half the canvas, each rendered character then
+    /// filling a block of them. The globe always gets at least eight
+    /// characters across, unless a lower `quality` asks for larger blocks.
+    pub fn render_resolution(&self, canvas: &Canvas) -> (usize, usize) {
+        let (block_x, block_y) = self.render_block(canvas);
+        (
+            (canvas.size.0 / canvas.char_pix.0).div_ceil(block_x),
+            (canvas.size.1 / canvas.char_pix.1).div_ceil(block_y),
+        )
+    }
+
+    /// Characters across and down filled by each rendered one, at least
+    /// `quality.block`. Blocks are half as tall as wide so they look square.
+    fn render_block(&self, canvas: &Canvas) -> (usize, usize) {
+        let half_x = (canvas.size.0 / canvas.char_pix.0 / 2) as Float;
+        let side = match self.adaptive_resolution && half_x > 0. {
+            true => {
+                let radius = self.limb_radius(canvas);
+                let side = (0.5 * half_x / radius).min(radius / 4.).floor();
+                side.clamp(1., 4.) as usize
+            }
+            false => 1,
+        };
+        let side = side.max(self.quality.block);
+        (side, side.div_ceil(2))
+    }
+
+    /// Character for a shade level at `(x, y)` on `canvas`, dithered if
+    /// asked to.
+    fn shade_char(&self, canvas: &Canvas, level: Float, x: usize, y: usize) -> char {
+        match self.dither {
+            _ if !self.quality.dither => canvas.shade(level.round() as usize),
+            Dither::None => canvas.shade(level.round() as usize),
+            dither => canvas.shade_dithered(level, dither.threshold(x, y, self.dither_seed)),
+        }
+    }
+
+    /// Replaces the events and restarts their replay. Events stay visible
+    /// for `fade` after they happen.
+    pub fn set_events(&mut self, mut events: Vec<Event>, fade: Duration) {
+        // larger events are drawn last, so they win where events overlap
+        events.sort_by(|a, b| a.magnitude.total_cmp(&b.magnitude));
+        self.events = events;
+        self.event_fade = fade;
+        self.event_time = Duration::default();
+        self.events_generation += 1;
+    }
+
+    /// Changes to the view and the event replay since the last call, or
+    /// since the globe was built. Changes are coalesced: each kind is
+    /// reported at most once per call with the latest state, however many
+    /// times it changed in between, so calling this once per frame gives
+    /// one batch per frame.
+    ///
+    /// Changes are found by comparing with the state at the last call
+    /// rather than reported as they happen, so there's no callback running
+    /// in the middle of a mutation and the globe can be changed freely
+    /// while handling them.
+    pub fn take_changes(&mut self) -> Vec<ChangeEvent> {
+        let now = self.snapshot();
+        let seen = std::mem::replace(&mut self.seen, now);
+        let mut changes = Vec::new();
+        if now.angle != seen.angle {
+            let secs = (now.clock - seen.clock).as_secs_f32();
+            let velocity = match secs > 0. {
+                true => (now.angle - seen.angle) / secs,
+                false => 0.,
+            };
+            changes.push(ChangeEvent::Rotated {
+                angle: now.angle,
+                velocity,
+            });
+        }
+        if now.camera != seen.camera {
+            changes.push(ChangeEvent::CameraMoved {
+                x: self.camera.x,
+                y: self.camera.y,
+                z: self.camera.z,
+            });
+        }
+        if now.events_generation != seen.events_generation {
+            changes.push(ChangeEvent::EventsChanged {
+                count: self.events.len(),
+            });
+        } else if let Some(end) = self.replay_end() {
+            let passed_end = seen.event_time < end && now.event_time >= end;
+            let started_over = now.event_time < seen.event_time;
+            if passed_end || started_over {
+                changes.push(ChangeEvent::ReplayFinished);
+            }
+        }
+        changes
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let mut camera = [0.; 19];
+        camera[0] = self.camera.x;
+        camera[1] = self.camera.y;
+        camera[2] = self.camera.z;
+        camera[3..].copy_from_slice(&self.camera.matrix);
+        Snapshot {
+            clock: self.clock,
+            angle: self.angle,
+            camera,
+            events_generation: self.events_generation,
+            event_time: self.event_time,
+        }
+    }
+
+    /// Replay time at which the last event has faded, if there are events.
+    fn replay_end(&self) -> Option<Duration> {
+        let last = self.events.iter().map(|e| e.time).max()?;
+        Some(last + self.event_fade)
+    }
+
+    /// Moves time forward by `dt`, advancing the event replay, the wobble,
+    /// the flow field particles and the view following a position.
+    pub fn advance(&mut self, dt: Duration) {
+        self.event_time += dt;
+        self.wobble_time += dt;
+        self.clock += dt;
+        self.advance_flow(dt);
+        self.advance_follow(dt);
+    }
+
+    /// Total time passed to `advance`.
+    pub fn elapsed(&self) -> Duration {
+        self.clock
+    }
+
+    /// Makes the camera tilt swing by up to `amplitude` radians either way,
+    /// once every `period`. The camera isn't changed here: `wobble_offset`
+    /// gives the tilt to add to it, so the wobble composes with any tilt
+    /// chosen by the user.
+    pub fn set_wobble(&mut self, amplitude: Float, period: Duration) {
+        assert!(!period.is_zero(), "wobble period can't be zero");
+        self.wobble = Some(Wobble { amplitude, period });
+        self.wobble_time = Duration::default();
+    }
+
+    /// Current tilt offset of the wobble in radians, zero without one.
+    pub fn wobble_offset(&self) -> Float {
+        match self.wobble {
+            Some(w) => {
+                let phase = self.wobble_time.as_secs_f32() / w.period.as_secs_f32();
+                w.amplitude * (phase.fract() * 2. * PI).sin()
+            }
+            None => 0.,
+        }
+    }
+
+    /// Draws the events at the cells `project` gives for their positions,
+    /// larger with magnitude and fading with age. Events it gives no cell
+    /// for, like those on the far side of the globe, are left out.
+    fn draw_events(
+        &self,
+        canvas: &mut Canvas,
+        project: impl Fn(&Canvas, Float, Float) -> Option<(usize, usize)>,
+        tally: &mut impl Tally,
+    ) {
+        for event in &self.events {
+            let age = match self.event_time.checked_sub(event.time) {
+                Some(age) if age < self.event_fade => age,
+                _ => continue,
+            };
+            let (x, y) = match project(canvas, event.lat, event.lon) {
+                Some(p) => p,
+                None => {
+                    tally.cull();
                     continue;
                 }
+            };
+            let stage = age.as_secs_f32() / self.event_fade.as_secs_f32();
+            let c = EVENT_FADE[(stage * EVENT_FADE.len() as Float) as usize];
+            // cells are about twice as tall as wide
+            let r = ((event.magnitude - 4.) / 1.5).floor().clamp(0., 3.) as isize;
+            for dy in -r..=r {
+                for dx in -2 * r..=2 * r {
+                    let (fx, fy) = (dx as Float / 2., dy as Float);
+                    if fx * fx + fy * fy > (r * r) as Float + 0.5 {
+                        continue;
+                    }
+                    let (px, py) = (x as isize + dx, y as isize + dy);
+                    if px >= 0 && py >= 0 {
+                        canvas.draw_point(px as usize, py as usize, c);
+                    }
+                }
+            }
+        }
+    }
 
-                let distance: Float = -discriminant.sqrt() - dot_uo;
-
-                // intersection point
-                let inter: [Float; 3] = [
-                    o[0] + distance * u[0],
-                    o[1] + distance * u[1],
-                    o[2] + distance * u[2],
-                ];
-
-                // surface normal
-                let mut n: [Float; 3] = [
-                    o[0] + distance * u[0],
-                    o[1] + distance * u[1],
-                    o[2] + distance * u[2],
-                ];
-                normalize(&mut n);
-                // unit vector pointing from intersection to light source
-                let mut l: [Float; 3] = [0.; 3];
-                vector(&mut l, &inter, &light);
-                normalize(&mut l);
-                let luminance: Float = clamp(5. * (dot(&n, &l)) + 0.5, 0., 1.);
-                let mut temp: [Float; 3] = [inter[0], inter[1], inter[2]];
-                rotate_x(&mut temp, -PI * 2. * 0. / 360.);
-                // computing coordinates for the sphere
-                let phi: Float = -temp[2] / self.radius / 2. + 0.5;
-                //let t: Float = (temp[1]/temp[0];
-                let mut theta: Float = (temp[1] / temp[0]).atan() / PI + 0.5 + self.angle / 2. / PI;
-                theta -= theta.floor();
-                let earth_x: usize = (theta * 202.) as usize;
-                let earth_y: usize = (phi * 80.) as usize;
-                let day = find_index(self.texture[earth_y][earth_x], &PALETTE);
-
-                // TODO night
-                //let night = findIndex(self.texture_night[earthY][earthX], &palette);
-                //let index = ((1.0 - luminance) * night as Float + luminance * day as Float) as usize;
-
-                let mut index =
-                    ((1.0 - luminance) * day as Float + luminance * day as Float) as usize;
-                if index >= PALETTE.len() {
-                    index = 0;
+    /// Writes `text` on the surface along the parallel at `lat`, starting at
+    /// `start_lon` and going east a letter every `spacing` degrees. It turns
+    /// with the globe and hides on the far side. Where letters crowd into
+    /// the same cell, near the limb or zoomed out, only the first is drawn.
+    pub fn add_surface_text(&mut self, lat: Float, start_lon: Float, text: &str, spacing: Float) {
+        self.surface_text.push(SurfaceText {
+            lat,
+            start_lon,
+            text: text.to_string(),
+            spacing,
+        });
+    }
+
+    fn draw_surface_text(
+        &self,
+        canvas: &mut Canvas,
+        project: impl Fn(&Canvas, Float, Float) -> Option<(usize, usize)>,
+        tally: &mut impl Tally,
+    ) {
+        for text in &self.surface_text {
+            let mut taken = Vec::new();
+            for (i, c) in text.text.chars().enumerate() {
+                if c == ' ' {
+                    continue;
                 }
+                let lon = text.start_lon + i as Float * text.spacing;
+                match project(canvas, text.lat, lon) {
+                    Some(cell) if !taken.contains(&cell) => {
+                        taken.push(cell);
+                        canvas.draw_point(cell.0, cell.1, c);
+                    }
+                    // hidden, or dropped rather than drawn over another letter
+                    _ => tally.cull(),
+                }
+            }
+        }
+    }
 
-                canvas.draw_point(xi, yi, PALETTE[index]);
+    /// Geographic position (latitude, longitude in degrees) shown at a
+    /// canvas cell, or `None` if the cell doesn't show the globe. The
+    /// inverse of `project`.
+    pub fn unproject(&self, canvas: &Canvas, x: usize, y: usize) -> Option<(Float, Float)> {
+        let inter = self.cast_ray(canvas, x, y)?;
+        let (lat, lon) = self.surface_coords(&inter);
+        let lon = angles::normalize_angle(lon);
+        Some((lat.to_degrees(), lon.to_degrees()))
+    }
+
+    /// Centers the view on a geographic position (in degrees), turning the
+    /// globe to its longitude and moving the camera, at the same distance,
+    /// to its latitude. Outside the camera's bounds it stops at their edge.
+    /// Any roll of the camera is lost.
+    pub fn focus(&mut self, lat: Float, lon: Float) {
+        let bounds = self.camera.bounds;
+        let (lat, lon) = bounds.map_or((lat, lon), |b| b.clamp((lat, lon)));
+        let distance = magnitude(&[self.camera.x, self.camera.y, self.camera.z]);
+        self.angle = -lon.to_radians();
+        self.place_camera(distance, lat.to_radians());
+    }
+
+    /// Turns the globe by `delta` radians, like adding to `angle`, but
+    /// stopping where the center of the view reaches the edge of the
+    /// camera's bounds.
+    pub fn rotate(&mut self, delta: Float) {
+        self.angle += delta;
+        if let Some(bounds) = self.camera.bounds {
+            let (lat, lon) = self.facing();
+            let (_, clamped) = bounds.clamp((lat, lon));
+            self.angle -= (clamped - lon).to_radians();
+        }
+    }
+
+    /// Stamps `c` onto the texture in a circle of `radius` degrees around a
+    /// geographic position (in degrees), so every render from then on shows
+    /// it. The circle is measured along the surface, so it stays round on
+    /// the globe and takes up more columns of the texture near the poles.
+    /// Always covers at least the texel at the center. The night texture is
+    /// left alone.
+    pub fn paint(&mut self, lat: Float, lon: Float, radius: Float, c: char) {
+        let height = self.texture.len();
+        if let Some((y, x)) = texel_at(&self.texture, lat.to_radians(), lon.to_radians()) {
+            self.texture[y][x] = c;
+        }
+        for (y, row) in self.texture.iter_mut().enumerate() {
+            let row_lat = 90. - (y as Float + 0.5) / height as Float * 180.;
+            if (row_lat - lat).abs() > radius {
+                continue;
+            }
+            let width = row.len();
+            for (x, texel) in row.iter_mut().enumerate() {
+                let texel_lon = (x as Float + 0.5) / width as Float * 360. - 180.;
+                if angular_distance((lat, lon), (row_lat, texel_lon)) <= radius {
+                    *texel = c;
+                }
             }
         }
     }
+
+    /// Geographic position (latitude, longitude in degrees) facing the
+    /// camera, at the center of the view.
+    pub fn facing(&self) -> (Float, Float) {
+        let mut p = [self.camera.x, self.camera.y, self.camera.z];
+        normalize(&mut p);
+        let p = [p[0] * self.radius, p[1] * self.radius, p[2] * self.radius];
+        let (lat, lon) = self.surface_coords(&p);
+        let lon = angles::normalize_angle(lon);
+        (lat.to_degrees(), lon.to_degrees())
+    }
+
+    /// Point where the ray through a canvas cell hits the globe.
+    fn cast_ray(&self, canvas: &Canvas, xi: usize, yi: usize) -> Option<[Float; 3]> {
+        let (size_x, size_y) = canvas.get_size();
+        // canvases smaller than two characters have no center to aim at
+        if size_x / canvas.char_pix.0 / 2 == 0 || size_y / canvas.char_pix.1 / 2 == 0 {
+            return None;
+        }
+        let (xif, yif) = (xi as Int, yi as Int);
+        let (scale_x, scale_y) = canvas.view_scale();
+        // coordinates of the camera, origin of the ray
+        let o: [Float; 3] = [self.camera.x, self.camera.y, self.camera.z];
+        // u is unit vector, direction of the ray
+        let mut u: [Float; 3] = [
+            -((xif - (size_x / canvas.char_pix.0 / 2) as Int) as Float + 0.5) / scale_x,
+            ((yif - (size_y / canvas.char_pix.1 / 2) as Int) as Float + 0.5) / scale_y,
+            -1.,
+        ];
+        transform_vector(&mut u, self.camera.matrix);
+        u[0] -= self.camera.x;
+        u[1] -= self.camera.y;
+        u[2] -= self.camera.z;
+        normalize(&mut u);
+        let dot_uo = dot(&u, &o);
+        let discriminant: Float = dot_uo * dot_uo - dot(&o, &o) + self.radius * self.radius;
+
+        if discriminant < 0. {
+            return None;
+        }
+
+        let distance: Float = -discriminant.sqrt() - dot_uo;
+        Some([
+            o[0] + distance * u[0],
+            o[1] + distance * u[1],
+            o[2] + distance * u[2],
+        ])
+    }
+
+    /// Projects a geographic position (in degrees) onto the canvas.
+    ///
+    /// Returns the canvas cell the point would be drawn at, or `None` if the
+    /// point is on the far side of the globe or falls outside the canvas.
+    pub fn project(&self, canvas: &Canvas, lat: Float, lon: Float) -> Option<(usize, usize)> {
+        let (x, y) = self.view_point(canvas, lat, lon)?;
+        let (xi, yi) = (x.floor(), y.floor());
+        let (size_x, size_y) = canvas.get_size();
+        if xi < 0. || yi < 0. || xi >= size_x as Float || yi >= size_y as Float {
+            return None;
+        }
+        Some((xi as usize, yi as usize))
+    }
+
+    /// Where on the canvas a geographic position (in degrees) is seen, in
+    /// cells with fractions and without the canvas edges clipping it, or
+    /// `None` on the far side of the globe.
+    pub(crate) fn view_point(
+        &self,
+        canvas: &Canvas,
+        lat: Float,
+        lon: Float,
+    ) -> Option<(Float, Float)> {
+        let p = self.world_point(lat, lon);
+        let o: [Float; 3] = [self.camera.x, self.camera.y, self.camera.z];
+        let mut d: [Float; 3] = [0.; 3];
+        vector(&mut d, &p, &o);
+
+        // surface facing away from the camera
+        if dot(&d, &p) >= 0. {
+            return None;
+        }
+
+        let m = self.camera.matrix;
+        let dx = dot(&d, &[m[0], m[1], m[2]]);
+        let dy = dot(&d, &[m[4], m[5], m[6]]);
+        let dz = dot(&d, &[m[8], m[9], m[10]]);
+        if dz >= 0. {
+            return None;
+        }
+
+        let (size_x, size_y) = canvas.get_size();
+        let half_x = (size_x / canvas.char_pix.0 / 2) as Float;
+        let half_y = (size_y / canvas.char_pix.1 / 2) as Float;
+        if half_x == 0. || half_y == 0. {
+            return None;
+        }
+        let (scale_x, scale_y) = canvas.view_scale();
+        Some((dx / dz * scale_x + half_x, -dy / dz * scale_y + half_y))
+    }
+
+    /// Whether a geographic position (in degrees) is on the side of the
+    /// globe facing the camera, whether or not it's within the canvas.
+    /// Cheaper than `project` for ruling out points on the far side.
+    pub fn is_visible(&self, lat: Float, lon: Float) -> bool {
+        self.cull_visible(&[(lat, lon)])[0]
+    }
+
+    /// `is_visible` for many positions (latitude, longitude in degrees) at
+    /// once. The camera is turned into the globe's frame only once, leaving
+    /// a dot product per position.
+    pub fn cull_visible(&self, positions: &[(Float, Float)]) -> Vec<bool> {
+        // a point p on the surface faces a camera at o when p.o > r^2, with
+        // o turned back by the globe's rotation so p needn't be turned
+        let (sin_a, cos_a) = self.angle.sin_cos();
+        let (ox, oy) = (self.camera.x, self.camera.y);
+        let u = cos_a * ox - sin_a * oy;
+        let v = sin_a * ox + cos_a * oy;
+        let oz = self.camera.z;
+        positions
+            .iter()
+            .map(|&(lat, lon)| {
+                let (sin_lat, cos_lat) = lat.to_radians().sin_cos();
+                let (sin_lon, cos_lon) = lon.to_radians().sin_cos();
+                cos_lat * (cos_lon * u - sin_lon * v) + sin_lat * oz > self.radius
+            })
+            .collect()
+    }
+
+    /// Texel of the day texture at a geographic position in degrees, found
+    /// the way the renderer finds it: longitudes wrap around and latitudes
+    /// past the poles stay on the first or last row. A blank for a texture
+    /// with nothing there.
+    pub fn sample(&self, lat: Float, lon: Float) -> Texel {
+        let c = texel_at(&self.texture, lat.to_radians(), lon.to_radians())
+            .map_or(' ', |(y, x)| self.texture[y][x]);
+        Texel { c }
+    }
+
+    /// Height in meters at a position in degrees, from the heightmap cell
+    /// found the way `sample` finds texels. `None` without a heightmap or
+    /// where it has no digit, which is sea with no depth.
+    pub fn elevation(&self, lat: Float, lon: Float) -> Option<Float> {
+        let heightmap = self.heightmap.as_ref()?;
+        let (y, x) = texel_at(heightmap, lat.to_radians(), lon.to_radians())?;
+        let km = heightmap[y][x].to_digit(10)?;
+        Some(km as Float * 1000.)
+    }
+
+    /// Whether the texture has land at a position in degrees, which is any
+    /// character but the `ocean_chars`.
+    pub fn is_land(&self, lat: Float, lon: Float) -> bool {
+        !self.ocean_chars.contains(&self.sample(lat, lon).c)
+    }
+
+    /// Radius of the globe's outline on `canvas` in character widths, around
+    /// the center of the canvas. Infinite with the camera inside the globe.
+    pub fn limb_radius(&self, canvas: &Canvas) -> Float {
+        let (scale_x, _) = canvas.view_scale();
+        let o: [Float; 3] = [self.camera.x, self.camera.y, self.camera.z];
+        let (d2, r2) = (dot(&o, &o), self.radius * self.radius);
+        if d2 <= r2 {
+            return Float::INFINITY;
+        }
+        scale_x * self.radius / (d2 - r2).sqrt()
+    }
+
+    /// Point on the surface of the globe for a geographic position (in
+    /// degrees), taking the current rotation into account.
+    fn world_point(&self, lat: Float, lon: Float) -> [Float; 3] {
+        let lat = lat.to_radians();
+        let lon = lon.to_radians() + self.angle;
+        [
+            self.radius * lat.cos() * lon.cos(),
+            -self.radius * lat.cos() * lon.sin(),
+            self.radius * lat.sin(),
+        ]
+    }
+
+    /// Geographic position (latitude, longitude in radians) of a point on the
+    /// surface, taking the current rotation into account.
+    fn surface_coords(&self, p: &[Float; 3]) -> (Float, Float) {
+        let lat = clamp(p[2] / self.radius, -1., 1.).asin();
+        let lon = (-p[1]).atan2(p[0]) - self.angle;
+        (lat, lon)
+    }
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct GlobeConfig {
     camera_cfg: Option<CameraConfig>,
     radius: Option<Float>,
@@ -152,12 +1425,31 @@ pub struct GlobeConfig {
     template: Option<GlobeTemplate>,
     texture: Option<Texture>,
     texture_night: Option<Texture>,
+    timezone_bands: Option<TimezoneBands>,
+    dither: Dither,
+    adaptive_resolution: bool,
+    scale_bar: bool,
+    /// Shading characters suggested by a texture pack.
+    palette: Option<Vec<char>>,
+    ocean_chars: Option<Vec<char>>,
+    theme: Option<Theme>,
+    heightmap: Option<Texture>,
+    elevation_ramp: Option<ElevationRamp>,
 }
 
 impl GlobeConfig {
     pub fn new() -> Self {
         Self::default()
     }
+    /// Palette a texture pack suggests for canvases showing the globe, see
+    /// `load_pack`.
+    pub fn palette(&self) -> Option<&[char]> {
+        self.palette.as_deref()
+    }
+    /// Heightmap set with `with_heightmap` or by a texture pack.
+    pub fn heightmap(&self) -> Option<&Texture> {
+        self.heightmap.as_ref()
+    }
     pub fn with_camera(mut self, config: CameraConfig) -> Self {
         self.camera_cfg = Some(config);
         self
@@ -166,18 +1458,72 @@ impl GlobeConfig {
         self.radius = Some(r);
         self
     }
+    /// Shades time zone bands, emphasizing the band `highlight` hours off
+    /// UTC if given.
+    pub fn with_timezone_bands(mut self, highlight: Option<i32>) -> Self {
+        self.timezone_bands = Some(TimezoneBands { highlight });
+        self
+    }
+    /// Mixes characters to draw shades between them, see `Dither`.
+    pub fn dithering(mut self, dither: Dither) -> Self {
+        self.dither = dither;
+        self
+    }
+    /// Renders at a lower resolution while zoomed out, see
+    /// `Globe::render_resolution`.
+    pub fn adaptive_resolution(mut self, adaptive: bool) -> Self {
+        self.adaptive_resolution = adaptive;
+        self
+    }
+    /// Draws a scale bar and longitude ticks, see `Globe::scale_bar`.
+    pub fn with_scale_bar(mut self, scale_bar: bool) -> Self {
+        self.scale_bar = scale_bar;
+        self
+    }
+    /// Texture characters `Globe::is_land` takes for ocean, for textures
+    /// that don't draw the ocean with `OCEAN_CHARS` like the Earth does.
+    pub fn with_ocean_chars(mut self, chars: &[char]) -> Self {
+        self.ocean_chars = Some(chars.to_vec());
+        self
+    }
+    /// Colors the globe with `theme`, whose shading characters, if it has
+    /// any, take the place of those a texture pack suggests.
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        if let Some(palette) = theme.palette {
+            self.palette = Some(palette.to_vec());
+        }
+        self.theme = Some(theme);
+        self
+    }
+    /// Heights for `ColorMode::Elevation`, as an equirectangular map like
+    /// textures are where each digit is that many thousands of meters
+    /// above sea level, and anything else is sea.
+    pub fn with_heightmap(mut self, heightmap: &str) -> Self {
+        self.heightmap = Some(parse_texture(heightmap));
+        self
+    }
+    /// Colors `ColorMode::Elevation` tints heights with, instead of the
+    /// default `ElevationRamp`.
+    pub fn with_elevation_ramp(mut self, ramp: ElevationRamp) -> Self {
+        self.elevation_ramp = Some(ramp);
+        self
+    }
     pub fn use_template(mut self, t: GlobeTemplate) -> Self {
         self.template = Some(t);
         self
     }
     pub fn with_texture(mut self, texture: &str) -> Self {
-        let mut tex = Texture::new();
-        let lines = texture.lines();
-        for line in lines {
-            let row: Vec<char> = line.chars().rev().collect();
-            tex.push(row);
-        }
-        self.texture = Some(tex);
+        self.texture = Some(parse_texture(texture));
+        self
+    }
+    /// Shows a built-in texture on the night side once the globe has a sun.
+    pub fn with_night_texture(mut self, t: GlobeTemplate) -> Self {
+        let texture = match t {
+            GlobeTemplate::Earth => EARTH_TEXTURE,
+            GlobeTemplate::Moon => MOON_TEXTURE,
+            GlobeTemplate::EarthNight => EARTH_NIGHT_TEXTURE,
+        };
+        self.texture_night = Some(parse_texture(texture));
         self
     }
     pub fn with_texture_at(self, path: &str) -> Self {
@@ -190,27 +1536,81 @@ impl GlobeConfig {
         if let Some(template) = &self.template {
             match template {
                 GlobeTemplate::Earth => self = self.with_texture(EARTH_TEXTURE),
+                GlobeTemplate::Moon => self = self.with_texture(MOON_TEXTURE),
+                GlobeTemplate::EarthNight => self = self.with_texture(EARTH_NIGHT_TEXTURE),
             }
         }
         let texture = self.texture.expect("texture not provided");
-        let camera = self
-            .camera_cfg
-            .unwrap_or_else(CameraConfig::default)
-            .build();
-        Globe {
+        let camera = self.camera_cfg.unwrap_or_default().build();
+        let mut globe = Globe {
             camera,
             radius: self.radius.unwrap_or(1.),
             angle: self.angle.unwrap_or(0.),
             texture,
             texture_night: self.texture_night,
-        }
+            sun: None,
+            timezone_bands: self.timezone_bands,
+            highlight: None,
+            dither: self.dither,
+            dither_seed: 0,
+            adaptive_resolution: self.adaptive_resolution,
+            quality: QualitySettings::default(),
+            scale_bar: self.scale_bar,
+            lighting: Lighting::default(),
+            theme: self.theme.unwrap_or_default(),
+            heightmap: self.heightmap,
+            elevation_ramp: self.elevation_ramp.unwrap_or_default(),
+            events: Vec::new(),
+            surface_text: Vec::new(),
+            routes: Vec::new(),
+            event_fade: Duration::default(),
+            event_time: Duration::default(),
+            wobble: None,
+            wobble_time: Duration::default(),
+            flow: None,
+            follow: Follow::default(),
+            ocean_chars: self.ocean_chars.unwrap_or_else(|| OCEAN_CHARS.to_vec()),
+            clock: Duration::default(),
+            events_generation: 0,
+            seen: Snapshot {
+                clock: Duration::default(),
+                angle: 0.,
+                camera: [0.; 19],
+                events_generation: 0,
+                event_time: Duration::default(),
+            },
+        };
+        globe.seen = globe.snapshot();
+        globe
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum GlobeTemplate {
     Earth,
+    Moon,
+    /// City lights of the Earth at night.
+    EarthNight,
 }
 
+impl GlobeTemplate {
+    pub const ALL: [GlobeTemplate; 3] = [
+        GlobeTemplate::Earth,
+        GlobeTemplate::EarthNight,
+        GlobeTemplate::Moon,
+    ];
+
+    /// Short name, like `earth-night`, for command lines and files.
+    pub fn name(self) -> &'static str {
+        match self {
+            GlobeTemplate::Earth => "earth",
+            GlobeTemplate::EarthNight => "earth-night",
+            GlobeTemplate::Moon => "moon",
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct CameraConfig {
     radius: Float,
     alpha: Float,
@@ -225,16 +1625,205 @@ impl CameraConfig {
             beta,
         }
     }
-    pub fn default() -> Self {
+    pub fn build(&self) -> Camera {
+        Camera::new(self.radius, self.alpha, self.beta)
+    }
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
         Self {
             radius: 2.,
             alpha: 0.,
             beta: 0.,
         }
     }
-    pub fn build(&self) -> Camera {
-        Camera::new(self.radius, self.alpha, self.beta)
+}
+
+/// Writes the characters of `canvas` to `out`, a line per row, e.g. to
+/// print a frame to a terminal or a file.
+pub fn print_frame(canvas: &Canvas, out: &mut impl Write) -> std::io::Result<()> {
+    let (width, height) = canvas.grid_size();
+    for row in canvas.matrix.iter().take(height) {
+        let line: String = row.iter().take(width).collect();
+        writeln!(out, "{}", line)?;
     }
+    Ok(())
+}
+
+/// Size in characters of the flat map `Globe::render_flat_on` draws on
+/// `canvas`.
+pub fn flat_size(canvas: &Canvas) -> (usize, usize) {
+    let (size_x, size_y) = canvas.get_size();
+    let width = size_x.min(size_y * 2);
+    (width / canvas.char_pix.0, width / 2 / canvas.char_pix.1)
+}
+
+/// Character where a geographic position (latitude, longitude in degrees)
+/// is on the flat map `Globe::render_flat_on` draws on `canvas`.
+pub fn project_flat(canvas: &Canvas, lat: Float, lon: Float) -> Option<(usize, usize)> {
+    let (width, height) = flat_size(canvas);
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let lon = (lon + 180.).rem_euclid(360.);
+    let x = ((lon / 360. * width as Float) as usize).min(width - 1);
+    let y = (((90. - lat) / 180. * height as Float) as usize).min(height - 1);
+    Some((x, y))
+}
+
+fn parse_texture(texture: &str) -> Texture {
+    let mut tex = Texture::new();
+    let lines = texture.lines();
+    for line in lines {
+        let row: Vec<char> = line.chars().collect();
+        tex.push(row);
+    }
+    tex
+}
+
+/// Position (latitude, longitude in degrees) where the sun is straight
+/// overhead at `time`, accurate to a fraction of a degree.
+pub fn subsolar_point(time: SystemTime) -> (Float, Float) {
+    let secs = match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs_f64(),
+        Err(e) => -e.duration().as_secs_f64(),
+    };
+    // days since noon on 1 January 2000
+    let days = secs / 86400. - 10957.5;
+    let anomaly = (357.529 + 0.98560028 * days).to_radians();
+    let mean_lon = 280.459 + 0.98564736 * days;
+    let ecliptic_lon =
+        (mean_lon + 1.915 * anomaly.sin() + 0.020 * (2. * anomaly).sin()).to_radians();
+    let obliquity = (23.439 - 0.00000036 * days).to_radians();
+    let declination = (obliquity.sin() * ecliptic_lon.sin()).asin();
+    let right_ascension = (obliquity.cos() * ecliptic_lon.sin()).atan2(ecliptic_lon.cos());
+    // how far the real sun is ahead of a sun moving at a constant rate
+    let equation_of_time = (mean_lon - right_ascension.to_degrees() + 180.).rem_euclid(360.) - 180.;
+    let utc_hours = secs.rem_euclid(86400.) / 3600.;
+    let lon = (15. * (12. - utc_hours) - equation_of_time + 180.).rem_euclid(360.) - 180.;
+    (declination.to_degrees() as Float, lon as Float)
+}
+
+/// Distances on the Earth's surface.
+pub mod geo {
+    use crate::Float;
+
+    /// Mean radius of the Earth.
+    pub const EARTH_RADIUS_KM: Float = 6371.0;
+
+    /// Great-circle distance in kilometers between two positions (latitude,
+    /// longitude in degrees), by the haversine formula, which stays accurate
+    /// for points close together.
+    pub fn distance_km(from: (Float, Float), to: (Float, Float)) -> Float {
+        let (lat1, lat2) = (from.0.to_radians(), to.0.to_radians());
+        let half_dlat = (lat2 - lat1) / 2.;
+        let half_dlon = (to.1 - from.1).to_radians() / 2.;
+        let h = half_dlat.sin().powi(2) + lat1.cos() * lat2.cos() * half_dlon.sin().powi(2);
+        2. * EARTH_RADIUS_KM * h.sqrt().min(1.).asin()
+    }
+}
+
+/// Angle constants and helpers, in radians unless the name says otherwise.
+pub mod angles {
+    use crate::Float;
+
+    pub const PI: Float = std::f32::consts::PI;
+    /// A full turn.
+    pub const TAU: Float = 2. * PI;
+
+    pub fn deg_to_rad(deg: Float) -> Float {
+        deg.to_radians()
+    }
+
+    pub fn rad_to_deg(rad: Float) -> Float {
+        rad.to_degrees()
+    }
+
+    /// The same angle within `-PI..PI`.
+    pub fn normalize_angle(angle: Float) -> Float {
+        (angle + PI).rem_euclid(TAU) - PI
+    }
+
+    /// Signed rotation from `from` to `to` the short way round, within
+    /// `-PI..PI`, so animations don't spin all the way around when the
+    /// angles are on either side of the wrap.
+    pub fn shortest_arc(from: Float, to: Float) -> Float {
+        normalize_angle(to - from)
+    }
+
+    /// Angle a fraction `t` of the way from `from` to `to`, the short way
+    /// round. Not normalized, so it moves smoothly from `from`.
+    pub fn lerp_angle(from: Float, to: Float, t: Float) -> Float {
+        from + shortest_arc(from, to) * t
+    }
+}
+
+/// Angle in degrees between two geographic positions (latitude, longitude in
+/// degrees), measured along the great circle through them.
+pub fn angular_distance(from: (Float, Float), to: (Float, Float)) -> Float {
+    let (a, b) = (unit_vector(from), unit_vector(to));
+    clamp(dot(&a, &b), -1., 1.).acos().to_degrees()
+}
+
+/// Geographic position a fraction `t` of the way from `from` to `to` along
+/// the shortest great circle, in degrees.
+pub fn great_circle(from: (Float, Float), to: (Float, Float), t: Float) -> (Float, Float) {
+    let (a, b) = (unit_vector(from), unit_vector(to));
+    let angle = clamp(dot(&a, &b), -1., 1.).acos();
+    if angle < 1e-6 {
+        return from;
+    }
+    // spherical linear interpolation
+    let (wa, wb) = (
+        ((1. - t) * angle).sin() / angle.sin(),
+        (t * angle).sin() / angle.sin(),
+    );
+    let p = [
+        wa * a[0] + wb * b[0],
+        wa * a[1] + wb * b[1],
+        wa * a[2] + wb * b[2],
+    ];
+    let lat = clamp(p[2], -1., 1.).asin();
+    let lon = p[1].atan2(p[0]);
+    (lat.to_degrees(), lon.to_degrees())
+}
+
+/// Point on the unit sphere for a geographic position in degrees.
+fn unit_vector((lat, lon): (Float, Float)) -> [Float; 3] {
+    let (lat, lon) = (lat.to_radians(), lon.to_radians());
+    [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+}
+
+/// Texture row and column for a geographic position in radians.
+///
+/// Textures are equirectangular: the first row is the north pole and the
+/// first column is the antimeridian.
+fn texel_at(texture: &Texture, lat: Float, lon: Float) -> Option<(usize, usize)> {
+    let height = texture.len();
+    if height == 0 {
+        return None;
+    }
+    let v = 0.5 - lat / PI;
+    let y = ((v * height as Float) as usize).min(height - 1);
+
+    let width = texture[y].len();
+    if width == 0 {
+        return None;
+    }
+    let u = (lon / (2. * PI) + 0.5).rem_euclid(1.);
+    let x = ((u * width as Float) as usize).min(width - 1);
+    Some((y, x))
+}
+
+/// How much daylight a point at `lat`, `lon` (in radians) gets with the sun
+/// straight above `sun` (latitude, longitude in degrees), from 0 at night to
+/// 1 in the day. Twilight blends the two within about 6 degrees of the
+/// terminator.
+fn daylight(lat: Float, lon: Float, sun: (Float, Float)) -> Float {
+    let (sun_lat, sun_lon) = (sun.0.to_radians(), sun.1.to_radians());
+    let elevation = lat.sin() * sun_lat.sin() + lat.cos() * sun_lat.cos() * (lon - sun_lon).cos();
+    clamp(elevation / 0.2 + 0.5, 0., 1.)
 }
 
 fn find_index(c: char, s: &[char]) -> Int {
@@ -247,12 +1836,14 @@ fn find_index(c: char, s: &[char]) -> Int {
     -1
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct Camera {
     pub x: Float,
     pub y: Float,
     pub z: Float,
     matrix: [Float; 16],
     inv: [Float; 16],
+    bounds: Option<Bounds>,
 }
 
 impl Camera {
@@ -304,10 +1895,111 @@ impl Camera {
             z,
             matrix,
             inv,
+            bounds: None,
         }
     }
+
+    /// Turns the camera about its line of sight by `roll` radians, so the
+    /// image turns the other way and the polar axis appears tilted by `roll`
+    /// clockwise from vertical.
+    pub fn with_roll(mut self, roll: Float) -> Self {
+        let (sin_r, cos_r) = roll.sin_cos();
+        let m = self.matrix;
+        for i in 0..3 {
+            self.matrix[i] = cos_r * m[i] + sin_r * m[4 + i];
+            self.matrix[4 + i] = cos_r * m[4 + i] - sin_r * m[i];
+        }
+        invert(&mut self.inv, self.matrix);
+        self
+    }
+
+    /// Swings the camera sideways around the center of the globe by `angle`
+    /// radians, still looking at it, like the left (negative) or right
+    /// (positive) eye of a pair looking at the same point. Rendering both
+    /// gives a stereo pair.
+    pub fn with_eye_offset(mut self, angle: Float) -> Self {
+        // Rodrigues' rotation about the camera's vertical axis
+        let k = [self.matrix[4], self.matrix[5], self.matrix[6]];
+        let (sin, cos) = angle.sin_cos();
+        let rotate = |v: [Float; 3]| {
+            let mut kv = [0.; 3];
+            cross(&mut kv, k, v);
+            let kd = dot(&k, &v) * (1. - cos);
+            [
+                v[0] * cos + kv[0] * sin + k[0] * kd,
+                v[1] * cos + kv[1] * sin + k[1] * kd,
+                v[2] * cos + kv[2] * sin + k[2] * kd,
+            ]
+        };
+        for axis in [0, 8, 12] {
+            let v = [
+                self.matrix[axis],
+                self.matrix[axis + 1],
+                self.matrix[axis + 2],
+            ];
+            self.matrix[axis..axis + 3].copy_from_slice(&rotate(v));
+        }
+        self.x = self.matrix[12];
+        self.y = self.matrix[13];
+        self.z = self.matrix[14];
+        invert(&mut self.inv, self.matrix);
+        self
+    }
+
+    /// Keeps the center of the view between `min` and `max` (latitude,
+    /// longitude in degrees) when moved with `Globe::focus` and
+    /// `Globe::rotate`, see `Bounds`.
+    pub fn with_bounds(mut self, min: (Float, Float), max: (Float, Float)) -> Self {
+        self.bounds = Some(Bounds { min, max });
+        self
+    }
+
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    /// How far above the equator the camera is, in degrees; the latitude
+    /// it looks down at, see `latitude_for_tilt`.
+    pub fn tilt_deg(&self) -> Float {
+        let p = [self.x, self.y, self.z];
+        latitude_for_tilt(clamp(p[2] / magnitude(&p), -1., 1.).asin())
+    }
+
+    /// Closest and farthest camera distances worth zooming to on `canvas`
+    /// for a globe of radius 1 showing `texture`; distances scale with the
+    /// radius. Farther out the globe shrinks below `MIN_ZOOM_RADIUS`
+    /// characters and turns into a blob, closer in a single texel covers
+    /// more than `MAX_TEXEL_CELLS` characters across.
+    ///
+    /// Texels are largest at the equator, where a row of the texture spans
+    /// the whole circumference; towards the poles they cover ever less
+    /// ground east to west, so the equator sets the limit. On canvases too
+    /// small for both, the closest distance gives way to the farthest.
+    pub fn zoom_bounds(canvas: &Canvas, texture: &Texture) -> (Float, Float) {
+        let (scale_x, _) = canvas.view_scale();
+        // the limb radius in characters is scale / sqrt(d² - 1)
+        let far = (1. + (scale_x / MIN_ZOOM_RADIUS).powi(2)).sqrt();
+        let width = texture.iter().map(Vec::len).max().unwrap_or(0);
+        let height = texture.len();
+        if width == 0 || height == 0 {
+            return (1., far);
+        }
+        // radians a texel spans, across at the equator or down
+        let texel = (2. * PI / width as Float).max(PI / height as Float);
+        // near the center of the view a character covers (d - 1) / scale
+        // radians of the surface
+        let near = 1. + texel * scale_x / MAX_TEXEL_CELLS;
+        (near.min(far), far)
+    }
 }
 
+/// Smallest radius in characters `Camera::zoom_bounds` lets the globe
+/// shrink to.
+pub const MIN_ZOOM_RADIUS: Float = 4.;
+
+/// Most characters across `Camera::zoom_bounds` lets a texel grow to.
+pub const MAX_TEXEL_CELLS: Float = 8.;
+
 fn transform_vector(vec: &mut [Float; 3], m: [Float; 16]) {
     let tx: Float = vec[0] * m[0] + vec[1] * m[4] + vec[2] * m[8] + m[12];
     let ty: Float = vec[0] * m[1] + vec[1] * m[5] + vec[2] * m[9] + m[13];
@@ -500,3 +2192,495 @@ fn clamp(mut x: Float, min: Float, max: Float) -> Float {
     }
     x
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_terminal_chars_wide() {
+        // 20000 columns are 80000 pixels, more than a u16 holds
+        let canvas = Canvas::fit_terminal_chars(20000, 10);
+        assert_eq!(canvas.get_size(), (80, 80));
+    }
+
+    #[test]
+    fn render_earth() {
+        let globe = GlobeConfig::new()
+            .use_template(GlobeTemplate::Earth)
+            .with_camera(CameraConfig::default())
+            .build();
+        let mut canvas = Canvas::new(160, 160, None);
+        globe.render_on(&mut canvas);
+        // facing 0 degrees from above the equator, from the default distance
+        let expected = Canvas::from_lines(&[
+            "                                        ",
+            "                                        ",
+            "                                        ",
+            "                                        ",
+            "                .:..@@@@                ",
+            "             ::::OOOOOOOoo              ",
+            "           ::::OOOOOOOOOOOOO@           ",
+            "          ::::@@@@@@@@@@OOOOOO          ",
+            "          '''..:@@@@@:@@@@@@@@@         ",
+            "        @:::::::::::::::@@@@@@O:        ",
+            "        @O:::::  :::::::@@@@@OO:        ",
+            "         O::::  :::::::::@OOOO@         ",
+            "          ::::: ::::::::OOOOOO          ",
+            "           :::: :::::::::OOO:           ",
+            "             ::::::::::::::             ",
+            "                ::::::::                ",
+            "                                        ",
+            "                                        ",
+            "                                        ",
+            "                                        ",
+        ]);
+        testing::assert_frames_eq(&canvas, &expected);
+    }
+
+    #[test]
+    fn distances_between_cities() {
+        let london = (51.5074, -0.1278);
+        let pairs = [
+            (london, (48.8566, 2.3522), 343.6),
+            ((40.7128, -74.006), london, 5570.2),
+            // across the antimeridian
+            ((35.6762, 139.6503), (37.7749, -122.4194), 8274.6),
+            ((-33.8688, 151.2093), (-36.8485, 174.7633), 2155.9),
+            // antipodes, half the circumference
+            ((0., 0.), (0., 180.), 20015.1),
+            ((90., 0.), (-90., 0.), 20015.1),
+            ((0., 0.), (0., 0.001), 0.111),
+        ];
+        for &(from, to, km) in &pairs {
+            let distance = geo::distance_km(from, to);
+            assert!(
+                (distance - km).abs() <= km * 0.002,
+                "{:?} to {:?} is {} km, not {}",
+                from,
+                to,
+                distance,
+                km
+            );
+            assert_eq!(distance, geo::distance_km(to, from));
+        }
+        assert_eq!(geo::distance_km(london, london), 0.);
+    }
+
+    /// Frame of a plain globe with a broad highlight, so its shades sweep
+    /// smoothly across several characters of a four character palette.
+    fn render_highlight(dither: Dither) -> Canvas {
+        let mut globe = GlobeConfig::new()
+            .with_texture(".")
+            .with_camera(CameraConfig::default())
+            .dithering(dither)
+            .build();
+        globe.lighting = Lighting {
+            specular_strength: 0.7,
+            shininess: 3.,
+            ocean_specular_strength: None,
+        };
+        let mut canvas = Canvas::new(160, 160, None).with_palette(&[' ', '.', 'o', '@']);
+        globe.render_on(&mut canvas);
+        canvas
+    }
+
+    #[test]
+    fn render_undithered() {
+        // the nearest character to each shade, in flat bands
+        let expected = Canvas::from_lines(&[
+            "                                        ",
+            "                                        ",
+            "                                        ",
+            "                                        ",
+            "                ........                ",
+            "             .oooooooo.....             ",
+            "           .oooooooooooo.....           ",
+            "          .oooooooooooooo.....          ",
+            "         .oooooooooooooooo.....         ",
+            "        ..ooooooooooooooo.......        ",
+            "        ...oooooooooooooo.......        ",
+            "         ...ooooooooooo........         ",
+            "          ......ooo...........          ",
+            "           ..................           ",
+            "             ..............             ",
+            "                ........                ",
+            "                                        ",
+            "                                        ",
+            "                                        ",
+            "                                        ",
+        ]);
+        testing::assert_frames_eq(&render_highlight(Dither::None), &expected);
+    }
+
+    #[test]
+    fn render_ordered_dither() {
+        // the same shades mixing the two nearest characters in the Bayer
+        // pattern, which repeats every 4 cells across and down
+        let expected = Canvas::from_lines(&[
+            "                                        ",
+            "                                        ",
+            "                                        ",
+            "                                        ",
+            "                .o.o.o..                ",
+            "             ooo@o@oooooo.o             ",
+            "           oo@o@o@o@o@oooo...           ",
+            "          oo@@@@@@@@@@@o@oo.o.          ",
+            "         oooo@o@o@@@o@o@oo.o...         ",
+            "        .ooo@o@@@@@@@o@oooooo.o.        ",
+            "        .o.oo@o@o@o@o@oooo.o....        ",
+            "         .oooo@o@o@o@oooooo.o..         ",
+            "          .o.ooooooo.o.o......          ",
+            "           .o.o.o.ooo.o.o.o..           ",
+            "             ..............             ",
+            "                ........                ",
+            "                                        ",
+            "                                        ",
+            "                                        ",
+            "                                        ",
+        ]);
+        testing::assert_frames_eq(&render_highlight(Dither::Ordered), &expected);
+    }
+
+    #[test]
+    fn tilt_round_trip() {
+        for &lat in &[0., 45., -45.] {
+            let tilt = tilt_for_latitude(lat);
+            assert!((tilt - (lat as Float).to_radians()).abs() < 1e-6);
+            assert!((latitude_for_tilt(tilt) - lat).abs() < 1e-4, "{}", lat);
+        }
+        assert!((tilt_for_latitude(45.) - PI / 4.).abs() < 1e-6);
+        // the poles are out of reach, the view stops short of them
+        assert_eq!(tilt_for_latitude(90.), MAX_TILT);
+        assert_eq!(tilt_for_latitude(-90.), -MAX_TILT);
+        assert_eq!(tilt_for_latitude(1000.), MAX_TILT);
+        let pole = latitude_for_tilt(MAX_TILT);
+        assert!((pole - 85.94).abs() < 0.01, "{}", pole);
+        assert!((latitude_for_tilt(-MAX_TILT) + pole).abs() < 1e-4);
+        assert!((tilt_for_latitude(pole) - MAX_TILT).abs() < 1e-6);
+    }
+
+    #[test]
+    fn tilt_centers_the_latitude() {
+        let mut globe = GlobeConfig::new()
+            .use_template(GlobeTemplate::Earth)
+            .with_camera(CameraConfig::default())
+            .build();
+        let canvas = Canvas::new(160, 160, None);
+        for &lat in &[0., 45., -45., 80.] {
+            globe.camera = Camera::new(2., 0., tilt_for_latitude(lat));
+            // the middle of the canvas is between two rows of cells
+            let above = globe.unproject(&canvas, 20, 9).unwrap().0;
+            let below = globe.unproject(&canvas, 20, 10).unwrap().0;
+            let center = (above + below) / 2.;
+            assert!((center - lat).abs() < 1., "{} centered at {}", lat, center);
+        }
+    }
+
+    /// Angles in radians to check `angles` on: a sweep over several turns
+    /// either way, and the values around the wrap.
+    fn sample_angles() -> Vec<Float> {
+        let mut samples: Vec<Float> = (-1000..=1000).map(|i| i as Float * 0.0173).collect();
+        for turns in -3..=3 {
+            let wrap = PI + turns as Float * angles::TAU;
+            samples.extend(&[wrap, -wrap, wrap + 1e-4, wrap - 1e-4]);
+        }
+        samples.extend(&[0., 1e-7, -1e-7, 1e4, -1e4]);
+        samples
+    }
+
+    #[test]
+    fn angles_normalize() {
+        use angles::normalize_angle;
+        for a in sample_angles() {
+            let n = normalize_angle(a);
+            assert!((-PI..=PI).contains(&n), "{} normalized to {}", a, n);
+            // the same angle
+            let error = 1e-6 * a.abs().max(1.);
+            assert!(
+                (n.sin() - a.sin()).abs() < error,
+                "{} normalized to {}",
+                a,
+                n
+            );
+            assert!(
+                (n.cos() - a.cos()).abs() < error,
+                "{} normalized to {}",
+                a,
+                n
+            );
+            // and normalizing again changes nothing but rounding
+            let again = normalize_angle(n);
+            let moved = angles::shortest_arc(n, again).abs();
+            assert!(moved < 1e-6, "{} normalized to {} then {}", a, n, again);
+        }
+    }
+
+    #[test]
+    fn angles_shortest_arc() {
+        let samples = sample_angles();
+        for &from in samples.iter().step_by(7) {
+            for &to in samples.iter().step_by(11) {
+                let arc = angles::shortest_arc(from, to);
+                assert!((-PI..=PI).contains(&arc), "{} to {} is {}", from, to, arc);
+                // lands on `to`
+                let error = 1e-5 * from.abs().max(to.abs()).max(1.);
+                assert!(((from + arc) - to).sin().abs() < error);
+                assert!(((from + arc) - to).cos() > 0.);
+            }
+        }
+    }
+
+    #[test]
+    fn angles_lerp() {
+        let samples = sample_angles();
+        for &from in samples.iter().step_by(7) {
+            for &to in samples.iter().step_by(11) {
+                assert_eq!(angles::lerp_angle(from, to, 0.), from);
+                let end = angles::lerp_angle(from, to, 1.);
+                let off = angles::shortest_arc(end, to).abs();
+                assert!(
+                    off < 1e-5 * from.abs().max(1.),
+                    "{} to {} ends at {}",
+                    from,
+                    to,
+                    end
+                );
+                // never more than half a turn from the start on the way
+                let half = angles::lerp_angle(from, to, 0.5);
+                assert!((half - from).abs() <= PI / 2. + 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn angles_deg_rad() {
+        for a in sample_angles() {
+            let back = angles::deg_to_rad(angles::rad_to_deg(a));
+            assert!(
+                (back - a).abs() <= 1e-6 * a.abs().max(1.),
+                "{} became {}",
+                a,
+                back
+            );
+            let deg = a * 100.;
+            let back = angles::rad_to_deg(angles::deg_to_rad(deg));
+            assert!(
+                (back - deg).abs() <= 1e-6 * deg.abs().max(1.),
+                "{} became {}",
+                deg,
+                back
+            );
+        }
+        assert_eq!(angles::deg_to_rad(180.), PI);
+        assert_eq!(angles::rad_to_deg(PI), 180.);
+    }
+
+    /// Frame of the Earth like `render_earth`'s, for characters `aspect`
+    /// times as wide as they are tall.
+    fn render_earth_cells(aspect: Float) -> Canvas {
+        let globe = GlobeConfig::new()
+            .use_template(GlobeTemplate::Earth)
+            .with_camera(CameraConfig::default())
+            .build();
+        let mut canvas = Canvas::new(160, 160, None).with_cell_aspect(aspect);
+        globe.render_on(&mut canvas);
+        canvas
+    }
+
+    #[test]
+    fn render_square_cells() {
+        // as many characters across as down, so half as wide as at 0.5
+        let expected = Canvas::from_lines(&[
+            "                                        ",
+            "                                        ",
+            "                                        ",
+            "                                        ",
+            "                  :.@@                  ",
+            "                 : OOOoO                ",
+            "               ::OOOOOOOO               ",
+            "               ::@@@@@OO@               ",
+            "              :'.@@@:@@@@:              ",
+            "              : ::::::@@@O              ",
+            "              @:: ::::@@@:              ",
+            "              O:: ::::OOO:              ",
+            "               :::::::OOO               ",
+            "               :::::::@O:               ",
+            "                ::::::@:                ",
+            "                  ::::                  ",
+            "                                        ",
+            "                                        ",
+            "                                        ",
+            "                                        ",
+        ]);
+        testing::assert_frames_eq(&render_earth_cells(1.), &expected);
+    }
+
+    #[test]
+    fn render_narrow_cells() {
+        // cells taller than the default's, so the globe takes fewer rows
+        let expected = Canvas::from_lines(&[
+            "                                        ",
+            "                                        ",
+            "                                        ",
+            "                                        ",
+            "                                        ",
+            "                 . .@o@                 ",
+            "            :::  OOOOOOOOo O            ",
+            "          ::::OOOOOOOOOOOOOOOO          ",
+            "          '''.@@@@@@@@@@@@@@@@@         ",
+            "         :::::::::::::::@@@@@@O         ",
+            "         O:::::  :::::::@@@@@OO         ",
+            "         @::::  ::::::::@@OOOO@         ",
+            "          : ::: ::::::::@OOOO:          ",
+            "            :::: ::::::::@::            ",
+            "                 ::::::                 ",
+            "                                        ",
+            "                                        ",
+            "                                        ",
+            "                                        ",
+            "                                        ",
+        ]);
+        testing::assert_frames_eq(&render_earth_cells(0.4), &expected);
+    }
+
+    /// Earth globe for the `sample` and `is_land` checks.
+    fn earth() -> Globe {
+        GlobeConfig::new()
+            .use_template(GlobeTemplate::Earth)
+            .with_camera(CameraConfig::default())
+            .build()
+    }
+
+    #[test]
+    fn land_and_water() {
+        let globe = earth();
+        for &(lat, lon) in &[(0., -160.), (0., -30.), (-40., 80.), (30., -45.)] {
+            assert!(!globe.is_land(lat, lon), "land at {}, {}", lat, lon);
+            let c = globe.sample(lat, lon).c;
+            assert!(OCEAN_CHARS.contains(&c), "{:?} at {}, {}", c, lat, lon);
+        }
+        for &(lat, lon) in &[
+            (45., 75.),
+            (23., 13.),
+            (-25., 135.),
+            (40., -100.),
+            (-85., 0.),
+        ] {
+            assert!(globe.is_land(lat, lon), "water at {}, {}", lat, lon);
+        }
+    }
+
+    #[test]
+    fn sample_wraps_and_clamps() {
+        let globe = earth();
+        for lat in (-18..=18).map(|i| i as Float * 5.) {
+            for lon in (-36..36).map(|i| i as Float * 5. + 2.5) {
+                let here = globe.sample(lat, lon);
+                // longitudes any number of turns away are the same place
+                for turns in [-2., -1., 1., 3.] {
+                    assert_eq!(globe.sample(lat, lon + turns * 360.), here);
+                }
+                // latitudes past a pole stay on its row
+                assert_eq!(
+                    globe.sample(90. + lat.abs() + 1., lon),
+                    globe.sample(90., lon)
+                );
+                assert_eq!(
+                    globe.sample(-90. - lat.abs() - 1., lon),
+                    globe.sample(-90., lon)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sample_like_the_renderer() {
+        // the flat map shades every cell from the texel at its center, and
+        // the default palette is the one texture characters are found in
+        let globe = earth();
+        let mut canvas = Canvas::new(288, 144, None);
+        globe.render_flat_on(&mut canvas);
+        let (width, height) = flat_size(&canvas);
+        for y in 0..height {
+            for x in 0..width {
+                let lat = 90. - (y as Float + 0.5) / height as Float * 180.;
+                let lon = (x as Float + 0.5) / width as Float * 360. - 180.;
+                let c = globe.sample(lat, lon).c;
+                assert_eq!(canvas.matrix[y][x], c, "at {}, {}", lat, lon);
+            }
+        }
+    }
+
+    /// Texture of `width` by `height` texels, all the same.
+    fn plain_texture(width: usize, height: usize) -> String {
+        (0..height).map(|_| ".".repeat(width) + "\n").collect()
+    }
+
+    /// Characters across the texel in the middle of `canvas`, along the
+    /// middle row, with `globe` facing longitude `lon`.
+    fn center_texel_cells(globe: &mut Globe, canvas: &Canvas, lon: Float) -> usize {
+        globe.angle = -lon.to_radians();
+        let (width, height) = canvas.grid_size();
+        let texel = |x| {
+            let (lat, lon) = globe.unproject(canvas, x, height / 2)?;
+            texel_at(&globe.texture, lat.to_radians(), lon.to_radians())
+        };
+        let center = texel(width / 2);
+        (0..width).filter(|&x| texel(x) == center).count()
+    }
+
+    #[test]
+    fn zoom_bounds() {
+        // 10 degree texels
+        let texture = parse_texture(&plain_texture(36, 18));
+        let mut globe = GlobeConfig::new()
+            .with_texture(&plain_texture(36, 18))
+            .build();
+        let mut last_far = 0.;
+        for &(width, height) in &[(160, 160), (320, 640), (800, 400)] {
+            let canvas = Canvas::new(width, height, None);
+            let (near, far) = Camera::zoom_bounds(&canvas, &texture);
+            assert!(
+                1. < near && near < far,
+                "{} to {} on {}x{}",
+                near,
+                far,
+                width,
+                height
+            );
+            // larger canvases show more, so the bounds move out
+            assert!(far > last_far);
+            last_far = far;
+            // as far out it's still a few characters wide
+            globe.camera = Camera::new(far, 0., 0.);
+            let radius = globe.limb_radius(&canvas);
+            assert!((radius - MIN_ZOOM_RADIUS).abs() < 1e-3, "radius {}", radius);
+            // and as close in a texel is as wide as allowed, in the middle
+            // of the view facing the middle of one
+            globe.camera = Camera::new(near, 0., 0.);
+            let cells = center_texel_cells(&mut globe, &canvas, 5.);
+            assert_eq!(cells, MAX_TEXEL_CELLS as usize, "on {}x{}", width, height);
+        }
+    }
+
+    #[test]
+    fn zoom_bounds_of_textures() {
+        let canvas = Canvas::new(160, 160, None);
+        let bounds = |width, height| {
+            let texture = parse_texture(&plain_texture(width, height));
+            Camera::zoom_bounds(&canvas, &texture)
+        };
+        let (coarse, far) = bounds(36, 18);
+        // finer textures can be looked at closer, from as far out
+        let (fine, fine_far) = bounds(360, 180);
+        assert!(1. < fine && fine < coarse);
+        assert_eq!(fine_far, far);
+        // tall texels set the limit as well as wide ones
+        assert_eq!(bounds(36, 9).0, bounds(18, 9).0);
+        // a texture too coarse for the canvas gives way to the farthest
+        assert_eq!(bounds(2, 1), (far, far));
+        // nothing to get close to
+        assert_eq!(Camera::zoom_bounds(&canvas, &Vec::new()), (1., far));
+    }
+}
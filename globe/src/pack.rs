@@ -0,0 +1,142 @@
+//! Texture packs: a directory of texture files with a `pack.toml` manifest
+//! saying which is which, e.g.
+//!
+//! ```toml
+//! texture = "mars.txt"
+//! night = "mars-night.txt"   # optional
+//! height = "mars-height.txt" # optional, see `GlobeConfig::with_heightmap`
+//! palette = " .:-=+*#%@"     # optional
+//! ```
+//!
+//! The manifest is `key = "value"` lines with `#` comments. Texture files are
+//! equirectangular maps like the built-in ones, and all of a pack's textures
+//! have to be the same size.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::{parse_texture, GlobeConfig, Texture};
+
+/// Name of the manifest in a pack directory.
+pub const PACK_MANIFEST: &str = "pack.toml";
+
+impl GlobeConfig {
+    /// Configuration with the textures and palette of the texture pack in
+    /// the directory `path`. Builder calls made afterwards override what
+    /// the pack sets, e.g. `use_template` shows a built-in texture instead.
+    ///
+    /// Errors name the file they're about: a missing manifest or texture,
+    /// an invalid manifest line or textures of different sizes.
+    pub fn load_pack(path: &Path) -> io::Result<GlobeConfig> {
+        let manifest_path = path.join(PACK_MANIFEST);
+        let manifest = read(&manifest_path)?;
+        let (mut texture, mut night, mut height, mut palette) = (None, None, None, None);
+        for (i, line) in manifest.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = |message: String| {
+                let line = format!("{}: line {}: {}", manifest_path.display(), i + 1, message);
+                io::Error::new(io::ErrorKind::InvalidData, line)
+            };
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| invalid("expected `key = \"value\"`".to_string()))?;
+            let key = key.trim();
+            let slot = match key {
+                "texture" => &mut texture,
+                "night" => &mut night,
+                "height" => &mut height,
+                "palette" => &mut palette,
+                _ => return Err(invalid(format!("unknown key `{}`", key))),
+            };
+            let value = unquote(value.trim())
+                .ok_or_else(|| invalid(format!("`{}`: expected a quoted string", key)))?;
+            if slot.replace(value.to_string()).is_some() {
+                return Err(invalid(format!("duplicate key `{}`", key)));
+            }
+        }
+
+        let texture_name = texture.ok_or_else(|| {
+            let message = format!("{}: missing `texture`", manifest_path.display());
+            io::Error::new(io::ErrorKind::InvalidData, message)
+        })?;
+        let day = load_texture(&path.join(&texture_name))?;
+        // other textures of the pack have to match the day texture
+        let load_matching = |name: &str| {
+            let texture = load_texture(&path.join(name))?;
+            let (size, day_size) = (texture_size(&texture), texture_size(&day));
+            if size != day_size {
+                let message = format!(
+                    "{}: {}x{} characters, but {} is {}x{}",
+                    path.join(name).display(),
+                    size.0,
+                    size.1,
+                    texture_name,
+                    day_size.0,
+                    day_size.1
+                );
+                return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+            }
+            Ok(texture)
+        };
+        let mut config = GlobeConfig::new();
+        if let Some(night_name) = night {
+            config.texture_night = Some(load_matching(&night_name)?);
+        }
+        if let Some(height_name) = height {
+            config.heightmap = Some(load_matching(&height_name)?);
+        }
+        if let Some(palette) = palette {
+            if palette.is_empty() {
+                let message = format!("{}: `palette` can't be empty", manifest_path.display());
+                return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+            }
+            config.palette = Some(palette.chars().collect());
+        }
+        config.texture = Some(day);
+        Ok(config)
+    }
+}
+
+fn read(path: &Path) -> io::Result<String> {
+    fs::read_to_string(path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
+
+fn load_texture(path: &Path) -> io::Result<Texture> {
+    let texture = parse_texture(&read(path)?);
+    if texture_size(&texture).0 == 0 {
+        let message = format!("{}: empty texture", path.display());
+        return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+    }
+    Ok(texture)
+}
+
+/// Width of the widest row and number of rows.
+fn texture_size(texture: &Texture) -> (usize, usize) {
+    let width = texture.iter().map(Vec::len).max().unwrap_or(0);
+    (width, texture.len())
+}
+
+/// Removes a trailing `#` comment, ignoring `#` inside strings.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match (c, quote) {
+            ('"', None) | ('\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('#', None) => return &line[..i],
+            _ => (),
+        }
+    }
+    line
+}
+
+/// Contents of a single or double quoted string.
+fn unquote(s: &str) -> Option<&str> {
+    let quote = s.chars().next().filter(|&c| c == '"' || c == '\'')?;
+    s[1..].strip_suffix(quote)
+}
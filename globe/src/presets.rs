@@ -0,0 +1,91 @@
+//! Views of well-known regions, see `Presets`.
+
+use crate::Float;
+
+/// Distance of the default camera, which frames the whole globe.
+const DEFAULT_DISTANCE: Float = 2.;
+
+/// Room left around a region when framing it, as a share of its extent.
+const MARGIN: Float = 1.1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Continent {
+    Africa,
+    Antarctica,
+    Asia,
+    Europe,
+    NorthAmerica,
+    Oceania,
+    SouthAmerica,
+}
+
+impl Continent {
+    pub const ALL: [Continent; 7] = [
+        Continent::Africa,
+        Continent::Antarctica,
+        Continent::Asia,
+        Continent::Europe,
+        Continent::NorthAmerica,
+        Continent::Oceania,
+        Continent::SouthAmerica,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Continent::Africa => "Africa",
+            Continent::Antarctica => "Antarctica",
+            Continent::Asia => "Asia",
+            Continent::Europe => "Europe",
+            Continent::NorthAmerica => "North America",
+            Continent::Oceania => "Oceania",
+            Continent::SouthAmerica => "South America",
+        }
+    }
+
+    /// Middle of the continent, latitude and longitude in degrees, and the
+    /// angle in degrees from there to its farthest coast.
+    ///
+    /// The middle is halfway between the coasts rather than the centroid,
+    /// so the continent sits evenly in the view. Antarctica's is moved off
+    /// the pole, to where cameras can still tilt.
+    fn extent(self) -> (Float, Float, Float) {
+        match self {
+            Continent::Africa => (2., 17., 38.),
+            Continent::Antarctica => (-85., 0., 25.),
+            Continent::Asia => (43., 95., 50.),
+            Continent::Europe => (54., 20., 25.),
+            Continent::NorthAmerica => (47., -100., 42.),
+            Continent::Oceania => (-25., 145., 33.),
+            Continent::SouthAmerica => (-20., -60., 37.),
+        }
+    }
+}
+
+/// Views of well-known regions, as a latitude and longitude in degrees to
+/// `Globe::focus` on and a camera distance to zoom to.
+pub struct Presets;
+
+impl Presets {
+    /// View centered on a continent, close enough that it fills about as
+    /// much of the canvas as the whole globe does from the default
+    /// distance, which is what fits any canvas.
+    pub fn continent(continent: Continent) -> (Float, Float, Float) {
+        let (lat, lon, extent) = continent.extent();
+        (lat, lon, framing_distance(extent * MARGIN))
+    }
+}
+
+/// Camera distance at which a circle with a radius of `angle` degrees
+/// around the view center looks as large as the whole globe does from
+/// `DEFAULT_DISTANCE`.
+///
+/// The edge of the circle is `sin(angle)` off the axis and `d - cos(angle)`
+/// in front of a camera at distance `d`, and the limb seen from the default
+/// distance is at a slope of `1 / sqrt(DEFAULT_DISTANCE^2 - 1)`. Past the
+/// angle where the limb is closer already, the default distance stays.
+fn framing_distance(angle: Float) -> Float {
+    let limb = (DEFAULT_DISTANCE * DEFAULT_DISTANCE - 1.).sqrt();
+    let farthest = (1. / DEFAULT_DISTANCE).acos();
+    let angle = angle.to_radians().min(farthest);
+    angle.cos() + angle.sin() * limb
+}
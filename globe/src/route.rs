@@ -0,0 +1,170 @@
+//! Routes through several stops along great circles, see
+//! `Globe::add_route`.
+
+use crate::{angular_distance, geo, great_circle, Canvas, Float, Globe, Tally};
+
+/// Largest spacing in degrees of the points a route is first traced with,
+/// before the gaps between their cells are filled in.
+const TRACE_STEP: Float = 0.5;
+
+/// Times a piece of a segment is halved looking for the cells between two
+/// points, at most.
+const MAX_DEPTH: usize = 8;
+
+/// Stops closer than this in degrees count as the same place.
+const SAME_STOP: Float = 0.01;
+
+/// How a route is drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RouteStyle {
+    /// Character the segments are drawn with.
+    pub line: char,
+    /// Character drawn at each stop.
+    pub stop: char,
+    /// Whether to write each stop's number, starting at 1, to its right.
+    pub numbered: bool,
+}
+
+impl Default for RouteStyle {
+    fn default() -> Self {
+        RouteStyle {
+            line: '~',
+            stop: '+',
+            numbered: false,
+        }
+    }
+}
+
+/// Stops connected by great circles, see `Globe::add_route`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Route {
+    /// Stops in order, latitude and longitude in degrees.
+    pub stops: Vec<(Float, Float)>,
+    pub style: RouteStyle,
+}
+
+impl Route {
+    /// Length of the whole route along the surface of the Earth, in km.
+    pub fn distance_km(&self) -> Float {
+        self.stops
+            .windows(2)
+            .map(|pair| geo::distance_km(pair[0], pair[1]))
+            .sum()
+    }
+}
+
+/// A route added with `Globe::add_route`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RouteHandle {
+    /// Position of the route in `Globe::routes`.
+    pub index: usize,
+    /// Length of the route along the surface of the Earth, in km.
+    pub distance_km: Float,
+}
+
+impl Globe {
+    /// Draws a route through `stops` (latitude, longitude in degrees) in
+    /// order, each one connected to the next along the shortest great
+    /// circle. It turns with the globe and hides on the far side.
+    ///
+    /// Segments are traced cell by cell, without gaps and without drawing
+    /// a cell twice where one ends and the next starts. Repeated stops make
+    /// no segment, and antipodal stops, with no one shortest great circle
+    /// between them, make none either.
+    pub fn add_route(&mut self, stops: &[(Float, Float)], style: RouteStyle) -> RouteHandle {
+        let route = Route {
+            stops: stops.to_vec(),
+            style,
+        };
+        let handle = RouteHandle {
+            index: self.routes.len(),
+            distance_km: route.distance_km(),
+        };
+        self.routes.push(route);
+        handle
+    }
+
+    /// Draws the routes at the cells `project` gives for positions on them.
+    pub(crate) fn draw_routes(
+        &self,
+        canvas: &mut Canvas,
+        project: impl Fn(&Canvas, Float, Float) -> Option<(usize, usize)>,
+        tally: &mut impl Tally,
+    ) {
+        for route in &self.routes {
+            let mut cells = Vec::new();
+            for pair in route.stops.windows(2) {
+                let (from, to) = (pair[0], pair[1]);
+                let angle = angular_distance(from, to);
+                if angle < SAME_STOP || 180. - angle < SAME_STOP {
+                    continue;
+                }
+                let cell_at = |t: Float| {
+                    let (lat, lon) = great_circle(from, to, t);
+                    project(canvas, lat, lon)
+                };
+                let steps = (angle / TRACE_STEP).ceil() as usize;
+                let mut last = (0., cell_at(0.));
+                cells.extend(last.1);
+                for i in 1..=steps {
+                    let t = i as Float / steps as Float;
+                    let next = (t, cell_at(t));
+                    trace(&cell_at, last, next, MAX_DEPTH, &mut cells);
+                    last = next;
+                }
+            }
+            // where segments meet, and wherever points share a cell
+            cells.dedup();
+            for &(x, y) in &cells {
+                canvas.draw_point(x, y, route.style.line);
+            }
+
+            let mut previous: Option<(Float, Float)> = None;
+            for (i, &(lat, lon)) in route.stops.iter().enumerate() {
+                if previous.is_some_and(|p| angular_distance(p, (lat, lon)) < SAME_STOP) {
+                    continue;
+                }
+                previous = Some((lat, lon));
+                let (x, y) = match project(canvas, lat, lon) {
+                    Some(cell) => cell,
+                    None => {
+                        tally.cull();
+                        continue;
+                    }
+                };
+                canvas.draw_point(x, y, route.style.stop);
+                if route.style.numbered {
+                    for (j, c) in (i + 1).to_string().chars().enumerate() {
+                        canvas.draw_point(x + 1 + j, y, c);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Adds the cells from just after `from` up to `to`, two points along a
+/// segment with their fraction of the way and cell, halving the piece
+/// between them until consecutive cells touch. Pieces hidden at both ends
+/// are skipped.
+fn trace(
+    cell_at: &impl Fn(Float) -> Option<(usize, usize)>,
+    from: (Float, Option<(usize, usize)>),
+    to: (Float, Option<(usize, usize)>),
+    depth: usize,
+    cells: &mut Vec<(usize, usize)>,
+) {
+    let touching = match (from.1, to.1) {
+        (Some(a), Some(b)) => a.0.abs_diff(b.0) <= 1 && a.1.abs_diff(b.1) <= 1,
+        (None, None) => true,
+        _ => false,
+    };
+    if touching || depth == 0 {
+        cells.extend(to.1);
+        return;
+    }
+    let t = (from.0 + to.0) / 2.;
+    let middle = (t, cell_at(t));
+    trace(cell_at, from, middle, depth - 1, cells);
+    trace(cell_at, middle, to, depth - 1, cells);
+}
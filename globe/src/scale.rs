@@ -0,0 +1,122 @@
+//! Scale bar and longitude labels for screenshots, see `Globe::scale_bar`.
+
+use crate::{geo, Canvas, Float, Globe};
+
+/// Degrees of longitude between the ticks along the equator.
+const TICK_SPACING: Float = 30.;
+
+/// Largest share of the canvas width the scale bar takes.
+const BAR_SHARE: Float = 0.25;
+
+/// Steps in degrees the equator is followed in looking for the limb.
+const LIMB_STEP: Float = 0.5;
+
+impl Globe {
+    /// Kilometers a cell across covers on the Earth's equator below the
+    /// middle of the view, from the camera distance and the canvas size.
+    /// `None` when that point isn't on the canvas, as when the camera is
+    /// tilted so far the equator is out of sight.
+    pub fn km_per_cell_at_equator(&self, canvas: &Canvas) -> Option<Float> {
+        let (_, lon) = self.facing();
+        self.project(canvas, 0., lon)?;
+        let (west, east) = ((0., lon - 0.5), (0., lon + 0.5));
+        let (x0, _) = self.view_point(canvas, west.0, west.1)?;
+        let (x1, _) = self.view_point(canvas, east.0, east.1)?;
+        let cells = (x1 - x0).abs();
+        match cells > 0. {
+            true => Some(geo::distance_km(west, east) / cells),
+            false => None,
+        }
+    }
+
+    /// Draws the scale bar in the bottom left corner, ticks along the
+    /// equator, and the longitudes where the equator meets the limb next to
+    /// it, or nothing while the equator is out of sight.
+    pub(crate) fn draw_scale(&self, canvas: &mut Canvas) {
+        let km_per_cell = match self.km_per_cell_at_equator(canvas) {
+            Some(km) => km,
+            None => return,
+        };
+        let (width, height) = canvas.grid_size();
+        let (_, center) = self.facing();
+
+        // out to the limb both ways, or as far as the canvas goes
+        let visible = |steps: Float| self.project(canvas, 0., center + steps * LIMB_STEP);
+        let end = |direction: Float| {
+            let mut steps = 0.;
+            while steps * LIMB_STEP < 180. && visible(direction * (steps + 1.)).is_some() {
+                steps += 1.;
+            }
+            direction * steps * LIMB_STEP
+        };
+        let (west, east) = (center + end(-1.), center + end(1.));
+
+        let first = (west / TICK_SPACING).ceil() as i32;
+        let last = (east / TICK_SPACING).floor() as i32;
+        for tick in first..=last {
+            if let Some((x, y)) = self.project(canvas, 0., tick as Float * TICK_SPACING) {
+                canvas.draw_point(x, y, '|');
+            }
+        }
+        if let Some((x, y)) = self.project(canvas, 0., west) {
+            let label = longitude_label(west);
+            let len = label.chars().count();
+            if x >= len + 2 {
+                write(canvas, x - len - 2, y, &label);
+            }
+        }
+        if let Some((x, y)) = self.project(canvas, 0., east) {
+            let label = longitude_label(east);
+            if x + 2 + label.chars().count() <= width {
+                write(canvas, x + 2, y, &label);
+            }
+        }
+
+        let longest = width as Float * BAR_SHARE * km_per_cell;
+        let km = match round_distance(longest) {
+            Some(km) => km,
+            None => return,
+        };
+        let cells = (km as Float / km_per_cell).round() as usize;
+        let text = format!("{} km at equator", km);
+        if cells < 2 || height == 0 || 1 + cells + 1 + text.chars().count() > width {
+            return;
+        }
+        let y = height - 1;
+        let bar: String = (0..cells)
+            .map(|i| if i == 0 || i == cells - 1 { '|' } else { '-' })
+            .collect();
+        write(canvas, 1, y, &format!("{} {}", bar, text));
+    }
+}
+
+/// Longest distance in km of 1, 2 or 5 times a power of ten up to `km`,
+/// for a bar that's easy to read, or `None` below a kilometer.
+fn round_distance(km: Float) -> Option<u64> {
+    if km < 1. {
+        return None;
+    }
+    let power = 10u64.pow(km.log10().floor() as u32);
+    [5, 2, 1]
+        .iter()
+        .map(|m| m * power)
+        .find(|&d| d as Float <= km)
+}
+
+/// A longitude in degrees as whole degrees east or west, like `45°W`.
+fn longitude_label(lon: Float) -> String {
+    let lon = ((lon + 180.).rem_euclid(360.) - 180.).round() as i32;
+    match lon {
+        0 => "0°".to_string(),
+        180 | -180 => "180°".to_string(),
+        lon if lon < 0 => format!("{}°W", -lon),
+        lon => format!("{}°E", lon),
+    }
+}
+
+/// Writes `text` from a cell to the right.
+fn write(canvas: &mut Canvas, x: usize, y: usize, text: &str) {
+    for (i, c) in text.chars().enumerate() {
+        canvas.draw_point(x + i, y, c);
+    }
+}
@@ -0,0 +1,78 @@
+//! Counts of what rendering a frame took, see `Globe::render_on_with_stats`.
+
+use std::time::Duration;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::time::Instant;
+
+use crate::{Canvas, Globe};
+
+/// What a `Globe::render_on_with_stats` call did, for tuning and catching
+/// regressions, like a change doubling the texture samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// Rays that hit the sphere, one per canvas point, or one per block of
+    /// characters with lower resolutions.
+    pub hit_cells: usize,
+    /// Rays that missed it and left the background. At full resolution
+    /// these include the rays cast through canvas points past the
+    /// character grid, which never hit.
+    pub background_cells: usize,
+    /// Texels looked up, in the day and night textures.
+    pub samples: usize,
+    /// Events and surface text letters left out, on the far side of the
+    /// globe, off the canvas or crowded out by other letters.
+    pub culled: usize,
+    /// How long the render took, or `None` where there's no clock, on
+    /// `wasm32-unknown-unknown`.
+    pub time: Option<Duration>,
+}
+
+/// Counts what a render does. Rendering without stats counts into `()`,
+/// whose methods do nothing and compile away.
+pub(crate) trait Tally {
+    fn ray(&mut self, hit: bool);
+    fn sample(&mut self);
+    fn cull(&mut self);
+}
+
+impl Tally for () {
+    #[inline(always)]
+    fn ray(&mut self, _hit: bool) {}
+    #[inline(always)]
+    fn sample(&mut self) {}
+    #[inline(always)]
+    fn cull(&mut self) {}
+}
+
+impl Tally for RenderStats {
+    fn ray(&mut self, hit: bool) {
+        match hit {
+            true => self.hit_cells += 1,
+            false => self.background_cells += 1,
+        }
+    }
+
+    fn sample(&mut self) {
+        self.samples += 1;
+    }
+
+    fn cull(&mut self) {
+        self.culled += 1;
+    }
+}
+
+impl Globe {
+    /// Renders like `render_on`, counting what it took. Plain `render_on`
+    /// doesn't count anything, so it costs nothing there.
+    pub fn render_on_with_stats(&self, canvas: &mut Canvas) -> RenderStats {
+        let mut stats = RenderStats::default();
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        let start = Instant::now();
+        self.render_tallied(canvas, &mut stats);
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        {
+            stats.time = Some(start.elapsed());
+        }
+        stats
+    }
+}
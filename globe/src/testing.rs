@@ -0,0 +1,152 @@
+//! Helpers for tests comparing rendered frames, with the `testing`
+//! feature.
+
+use crate::Canvas;
+
+impl Canvas {
+    /// Canvas with the default 4x8 pixel characters holding `lines`, one
+    /// per row, for expected frames in tests. Shorter lines are padded with
+    /// blanks to the longest.
+    ///
+    /// # Panics
+    ///
+    /// If there are no lines or they're all empty.
+    pub fn from_lines(lines: &[&str]) -> Canvas {
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let mut canvas = Canvas::new((width * 4) as u16, (lines.len() * 8) as u16, None);
+        for (row, line) in canvas.matrix.iter_mut().zip(lines) {
+            for (cell, c) in row.iter_mut().zip(line.chars()) {
+                *cell = c;
+            }
+        }
+        canvas
+    }
+}
+
+/// Asserts that two canvases show the same characters, like `assert_eq!`.
+///
+/// On a mismatch it panics with both frames side by side, the rows that
+/// differ marked with `>` and the differing cells with `^` under them.
+#[track_caller]
+pub fn assert_frames_eq(left: &Canvas, right: &Canvas) {
+    let (a, b) = (rows(left), rows(right));
+    if a == b {
+        return;
+    }
+    let (found, expected) = (left.grid_size(), right.grid_size());
+    if found != expected {
+        panic!(
+            "frames differ in size: left is {}x{}, right is {}x{}\n{}",
+            found.0,
+            found.1,
+            expected.0,
+            expected.1,
+            side_by_side(&a, &b)
+        );
+    }
+    let differing: usize = a
+        .iter()
+        .zip(&b)
+        .map(|(x, y)| x.chars().zip(y.chars()).filter(|(p, q)| p != q).count())
+        .sum();
+    panic!(
+        "frames differ in {} of {} cells\n{}",
+        differing,
+        found.0 * found.1,
+        side_by_side(&a, &b)
+    );
+}
+
+/// The character grid of a canvas, a string per row.
+fn rows(canvas: &Canvas) -> Vec<String> {
+    let (width, height) = canvas.grid_size();
+    canvas.matrix[..height]
+        .iter()
+        .map(|row| row[..width].iter().collect())
+        .collect()
+}
+
+/// Two frames next to each other between bars, so trailing blanks show,
+/// with a line of `^` under each row that differs.
+fn side_by_side(left: &[String], right: &[String]) -> String {
+    let width = |rows: &[String]| rows.iter().map(|r| r.chars().count()).max().unwrap_or(0);
+    let (left_width, right_width) = (width(left), width(right));
+    let mut out = format!("  {:<w$} {}\n", "left", "right", w = left_width + 2);
+    let blank = String::new();
+    for i in 0..left.len().max(right.len()) {
+        let (a, b) = (
+            left.get(i).unwrap_or(&blank),
+            right.get(i).unwrap_or(&blank),
+        );
+        let mark = if a == b { ' ' } else { '>' };
+        out += &format!(
+            "{} |{:<lw$}| |{:<rw$}|\n",
+            mark,
+            a,
+            b,
+            lw = left_width,
+            rw = right_width
+        );
+        if a != b {
+            let mut a = a.chars();
+            let mut b = b.chars();
+            let carets: String = (0..left_width.max(right_width))
+                .map(|_| match (a.next(), b.next()) {
+                    (x, y) if x == y => ' ',
+                    _ => '^',
+                })
+                .collect();
+            let carets = carets.trim_end();
+            out += &format!(
+                "   {:<lw$}   {}\n",
+                &carets[..carets.len().min(left_width)],
+                carets,
+                lw = left_width
+            );
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(rows: &[&str]) -> Vec<String> {
+        rows.iter().map(|row| row.to_string()).collect()
+    }
+
+    #[test]
+    fn carets_past_the_left_frame() {
+        // the left frame's carets stop at its edge, so the right one's
+        // still line up under its cells
+        let out = side_by_side(&strings(&["ab", "xy"]), &strings(&["abcd", "xy"]));
+        assert_eq!(
+            out,
+            "  left right\n\
+             > |ab| |abcd|\n\
+             \x20         ^^\n\
+             \x20 |xy| |xy  |\n"
+        );
+    }
+
+    #[test]
+    fn carets_past_the_right_frame() {
+        // cells missing on the right are marked past the end of its frame
+        let out = side_by_side(&strings(&["abcd"]), &strings(&["ab"]));
+        assert_eq!(
+            out,
+            "  left   right\n\
+             > |abcd| |ab|\n\
+             \x20    ^^     ^^\n"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "frames differ in 1 of 4 cells")]
+    fn differing_frames() {
+        let left = Canvas::from_lines(&["ab", "cd"]);
+        let right = Canvas::from_lines(&["ab", "cx"]);
+        assert_frames_eq(&left, &right);
+    }
+}
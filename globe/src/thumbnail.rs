@@ -0,0 +1,156 @@
+//! Tiny globes for prompts and status lines, see `thumbnail`.
+
+use crate::{parse_texture, texel_at, Float, Texture, EARTH_TEXTURE, OCEAN_CHARS};
+
+/// Characters of a thumbnail from open ocean to solid land. Only a few, so
+/// neighboring cells differ clearly at sizes where every one counts.
+pub const THUMBNAIL_PALETTE: [char; 4] = ['.', ':', 'o', '@'];
+
+/// Share of a cell's samples that have to be land for each character of the
+/// palette after the first.
+const LAND_STEPS: [Float; 3] = [0.15, 0.4, 0.7];
+
+/// Share of a cell the disk has to cover for it to be drawn at all. Lower
+/// and small thumbnails come out square, higher and they lose their sides.
+const MIN_DISK: Float = 0.4;
+
+/// Samples taken across and down each cell.
+const SAMPLES: usize = 6;
+
+/// The Earth `cols` characters across with `facing_longitude` (in degrees)
+/// in the middle, as rows about half as many, for cells twice as tall as
+/// they are wide.
+///
+/// Meant for sizes the renderer can't do much with, like the 5 to 9
+/// columns of a prompt: instead of looking up a texel per character, each
+/// character shows how much of its cell is land, with the reduced
+/// `THUMBNAIL_PALETTE`. The ocean is drawn too, so the outline stays
+/// round, and cells mostly off the disk are left blank.
+pub fn thumbnail(cols: usize, facing_longitude: Float) -> Vec<String> {
+    let texture = parse_texture(EARTH_TEXTURE);
+    let rows = cols.div_ceil(2);
+    (0..rows)
+        .map(|y| {
+            (0..cols)
+                .map(|x| {
+                    let (disk, land) = coverage(&texture, (x, y), (cols, rows), facing_longitude);
+                    if disk < MIN_DISK {
+                        return ' ';
+                    }
+                    let step = LAND_STEPS.iter().filter(|&&t| land >= t).count();
+                    THUMBNAIL_PALETTE[step]
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Shares of the cell `(x, y)` in a grid of `size` cells covered by the
+/// disk of the globe, and of that covered part showing land.
+fn coverage(
+    texture: &Texture,
+    (x, y): (usize, usize),
+    size: (usize, usize),
+    facing_longitude: Float,
+) -> (Float, Float) {
+    let (half_x, half_y) = (size.0 as Float / 2., size.1 as Float / 2.);
+    let (mut disk, mut land) = (0, 0);
+    for sy in 0..SAMPLES {
+        for sx in 0..SAMPLES {
+            let px = (x as Float + (sx as Float + 0.5) / SAMPLES as Float - half_x) / half_x;
+            let py = (half_y - y as Float - (sy as Float + 0.5) / SAMPLES as Float) / half_y;
+            let r = px * px + py * py;
+            if r > 1. {
+                continue;
+            }
+            disk += 1;
+            // orthographic view from above the equator
+            let lat = py.asin();
+            let lon = facing_longitude.to_radians() + px.atan2((1. - r).sqrt());
+            let is_land = texel_at(texture, lat, lon)
+                .is_some_and(|(ty, tx)| !OCEAN_CHARS.contains(&texture[ty][tx]));
+            land += is_land as usize;
+        }
+    }
+    let samples = (SAMPLES * SAMPLES) as Float;
+    match disk {
+        0 => (0., 0.),
+        _ => (disk as Float / samples, land as Float / disk as Float),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts the thumbnails of `cols` facing each of 0, 90 west and 120
+    /// east are `expected`. Moving a `LAND_STEPS` threshold past a cell's
+    /// share of land shows up here, and so does moving `MIN_DISK` out from
+    /// between the 5 column corners, a little over a third on the disk, and
+    /// the 9 column sides, a little over half.
+    fn assert_thumbnails(cols: usize, expected: [&[&str]; 3]) {
+        for (lon, expected) in [0., -90., 120.].iter().zip(expected) {
+            assert_eq!(
+                thumbnail(cols, *lon),
+                expected,
+                "{} columns at {}",
+                cols,
+                lon
+            );
+        }
+    }
+
+    #[test]
+    fn five() {
+        assert_thumbnails(
+            5,
+            [
+                &[" :@@ ", "o::@:", " ..: "],
+                &[" @o. ", "..o@:", " .:: "],
+                &[" @@: ", "::o:.", " .:o "],
+            ],
+        );
+    }
+
+    #[test]
+    fn seven() {
+        assert_thumbnails(
+            7,
+            [
+                &[" :.o@@ ", ":.o@@@o", "@:..o@:", " ..::. "],
+                &[" :@@:. ", "..:oo::", "...:@@:", " ..::. "],
+                &[" @@@o. ", "o:oo:..", ":..:o@.", " ..:.: "],
+            ],
+        );
+    }
+
+    #[test]
+    fn nine() {
+        assert_thumbnails(
+            9,
+            [
+                &[
+                    "  :oo@@  ",
+                    ":.:o@@@@o",
+                    "@o:::o@@.",
+                    "@:...:@:.",
+                    "  ..:..  ",
+                ],
+                &[
+                    "  @@@o.  ",
+                    "..ooo:..o",
+                    "....o@@o:",
+                    "...:.@@:.",
+                    "  ..::.  ",
+                ],
+                &[
+                    "  @@@@:  ",
+                    "@o@@@o...",
+                    ":..:::.:.",
+                    ":....o@o.",
+                    "  ..::.  ",
+                ],
+            ],
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! Globe widget for [ratatui](https://ratatui.rs) interfaces, with the
+//! `ratatui` feature.
+
+use std::convert::TryFrom;
+
+use ratatui_core::buffer::Buffer;
+use ratatui_core::layout::Rect;
+use ratatui_core::style::{Color, Style};
+use ratatui_core::widgets::StatefulWidget;
+
+use crate::{Canvas, Globe, PALETTE};
+
+/// Draws a globe as large as fits in the area it's given, centered in it.
+/// The globe to draw is kept in a `GlobeState`.
+#[derive(Clone, Debug, Default)]
+pub struct GlobeWidget {
+    style: Style,
+    palette: Option<Vec<char>>,
+    shade_colors: bool,
+}
+
+impl GlobeWidget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Style of the cells covered by the globe.
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+    /// Shading characters from darkest to brightest, `PALETTE` by default.
+    ///
+    /// # Panics
+    ///
+    /// If `palette` is empty.
+    pub fn palette(mut self, palette: &[char]) -> Self {
+        assert!(!palette.is_empty(), "palette can't be empty");
+        self.palette = Some(palette.to_vec());
+        self
+    }
+    /// Colors each cell in a shade of gray matching its character, on top of
+    /// the style.
+    pub fn shade_colors(mut self) -> Self {
+        self.shade_colors = true;
+        self
+    }
+}
+
+/// The globe drawn by a `GlobeWidget` and the canvas it's rendered on,
+/// kept between frames and replaced when the area changes size.
+pub struct GlobeState {
+    pub globe: Globe,
+    canvas: Option<Canvas>,
+}
+
+impl GlobeState {
+    pub fn new(globe: Globe) -> Self {
+        GlobeState {
+            globe,
+            canvas: None,
+        }
+    }
+}
+
+impl StatefulWidget for GlobeWidget {
+    type State = GlobeState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut GlobeState) {
+        // square in pixels, with the default 4x8 pixel characters
+        let size = (area.width as usize * 4).min(area.height as usize * 8);
+        let (width, height) = (size / 4, size / 8);
+        if width == 0 || height == 0 {
+            return;
+        }
+        let palette = self.palette.as_deref().unwrap_or(&PALETTE);
+        let canvas = match &mut state.canvas {
+            Some(canvas) if canvas.get_size() == (size, size) && canvas.palette == palette => {
+                canvas
+            }
+            canvas => {
+                let side = u16::try_from(size).ok();
+                match side.and_then(|side| Canvas::try_new(side, side, None)) {
+                    Some(new) => canvas.insert(new),
+                    // areas too large for a canvas are left blank
+                    None => return,
+                }
+            }
+        };
+        canvas.set_palette(palette);
+        canvas.clear();
+        state.globe.render_on(canvas);
+
+        let left = area.x + (area.width - width as u16) / 2;
+        let top = area.y + (area.height - height as u16) / 2;
+        for (y, row) in canvas.matrix.iter().take(height).enumerate() {
+            for (x, &c) in row.iter().take(width).enumerate() {
+                if c == ' ' {
+                    continue;
+                }
+                let mut style = self.style;
+                if self.shade_colors {
+                    style = style.fg(gray(palette, c));
+                }
+                buf[(left + x as u16, top + y as u16)]
+                    .set_char(c)
+                    .set_style(style);
+            }
+        }
+    }
+}
+
+/// Gray of a shading character, from dim for the darkest to white.
+fn gray(palette: &[char], c: char) -> Color {
+    let index = palette.iter().position(|&p| p == c).unwrap_or(0);
+    let level = 64 + index * 191 / (palette.len() - 1).max(1);
+    Color::Rgb(level as u8, level as u8, level as u8)
+}